@@ -3,6 +3,16 @@ set -e
 cd "`dirname $0`"
 echo ">> Building contract"
 rustup target add wasm32-unknown-unknown
-RUSTFLAGS='-C link-arg=-s' cargo build --all --target wasm32-unknown-unknown --release
+RUSTFLAGS='-C link-arg=-s' cargo build -p contract -p mock-dao --target wasm32-unknown-unknown --release
 cp target/wasm32-unknown-unknown/release/contract.wasm ./out/nearlott.wasm
+cp target/wasm32-unknown-unknown/release/mock_dao.wasm ./out/mock_dao.wasm
 
+# the mainnet wasm only draws with the VRF, the methods of the test randomness are never exported
+if grep -q queue_final_numbers ./out/nearlott.wasm; then
+    echo "out/nearlott.wasm includes the test randomness source"
+    exit 1
+fi
+
+echo ">> Building staging contract"
+RUSTFLAGS='-C link-arg=-s' cargo build -p contract --features test-randomness --target wasm32-unknown-unknown --release
+cp target/wasm32-unknown-unknown/release/contract.wasm ./out/nearlott_staging.wasm
@@ -0,0 +1,126 @@
+//! Arguments of the `welott` binary
+use clap::{Parser, Subcommand};
+use near_sdk::AccountId;
+use std::path::PathBuf;
+use welott_client::{BracketPosition, LotteryId, TicketId};
+
+#[derive(Parser, Debug)]
+#[clap(name = "welott", version, about = "Operate the Welott contract")]
+pub struct Cli {
+    /// Config file with the network profiles
+    #[clap(long, default_value = "welott.toml")]
+    pub config: PathBuf,
+    /// Network profile of the config file
+    #[clap(long, short, default_value = "testnet")]
+    pub profile: String,
+    /// Print the function calls with their deposit instead of sending them
+    #[clap(long)]
+    pub dry_run: bool,
+    #[clap(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Deploy the wasm to the contract account, signed by its own key
+    Deploy {
+        #[clap(long)]
+        wasm: PathBuf,
+        /// JSON arguments of `new` to initialize the contract
+        #[clap(long)]
+        init: Option<PathBuf>,
+    },
+    /// Contract and lottery config
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+    /// Lottery rounds
+    #[clap(subcommand)]
+    Round(RoundCommand),
+    /// Inject funds into a lottery, the latest one by default
+    Inject {
+        /// Amount in NEAR
+        #[clap(long)]
+        amount: String,
+        #[clap(long, conflicts_with = "reserve")]
+        lottery_id: Option<LotteryId>,
+        /// Inject into the reserve instead, it funds the jackpots of the next lotteries
+        #[clap(long)]
+        reserve: bool,
+    },
+    /// Lotteries and tickets
+    #[clap(subcommand)]
+    View(ViewCommand),
+    /// Claim the rewards of tickets, in their highest winning bracket by default
+    Claim {
+        #[clap(long)]
+        lottery_id: LotteryId,
+        #[clap(long, required = true, use_value_delimiter = true)]
+        ticket_ids: Vec<TicketId>,
+        #[clap(long, use_value_delimiter = true)]
+        brackets: Vec<BracketPosition>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Contract config and lottery config in use
+    Get,
+    /// Queue new limits of buying tickets
+    Set {
+        #[clap(long)]
+        max_number_tickets_per_buy: u64,
+        #[clap(long)]
+        min_discount_divisor: u128,
+    },
+    /// Queue a new lottery config read from a JSON file
+    SetLottery {
+        #[clap(long)]
+        file: PathBuf,
+    },
+    /// Execute a queued change once the timelock delay has passed
+    Execute { change_id: u64 },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RoundCommand {
+    /// Start a lottery with the lottery config
+    Start,
+    /// Close the current lottery once its end time has passed
+    Close,
+    /// Draw the final number of a closed lottery, the latest one by default
+    Draw {
+        lottery_id: Option<LotteryId>,
+        /// Leave the unclaimed rewards out of the next lottery
+        #[clap(long)]
+        no_auto_injection: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ViewCommand {
+    /// A lottery, the current one by default
+    Lottery { lottery_id: Option<LotteryId> },
+    /// Tickets of an account in a lottery with their statuses, the signer and the latest lottery by default
+    Tickets {
+        account_id: Option<AccountId>,
+        #[clap(long)]
+        lottery_id: Option<LotteryId>,
+    },
+    /// Tickets of an account in every lottery and its storage balance, the signer by default
+    User { account_id: Option<AccountId> },
+}
+
+impl Command {
+    /// Account signing the transactions of the command, none for the views
+    pub fn signer_id<'a>(
+        &self,
+        contract_id: &'a AccountId,
+        signer_id: &'a AccountId,
+    ) -> Option<&'a AccountId> {
+        match self {
+            Command::Deploy { .. } => Some(contract_id),
+            Command::Config(ConfigCommand::Get) | Command::View(_) => None,
+            _ => Some(signer_id),
+        }
+    }
+}
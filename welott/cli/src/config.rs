@@ -0,0 +1,51 @@
+//! Network profiles of `welott.toml`, one per network the contract is deployed on
+use crate::error::{Error, Result};
+use near_sdk::AccountId;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Debug)]
+pub struct Config {
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub network_id: String,
+    pub rpc_url: String,
+    pub contract_id: AccountId,
+    pub signer_id: AccountId,
+    // key file of the signer, ~/.near-credentials/<network_id>/<signer_id>.json by default
+    #[serde(default)]
+    pub credentials: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| Error::UnknownProfile(name.to_string()))
+    }
+}
+
+impl Profile {
+    /// Key file of an account, the one of the config for the signer
+    pub fn credentials_path(&self, account_id: &AccountId) -> PathBuf {
+        match &self.credentials {
+            Some(credentials) if account_id == &self.signer_id => credentials.clone(),
+            _ => PathBuf::from(std::env::var("HOME").unwrap_or_default())
+                .join(".near-credentials")
+                .join(&self.network_id)
+                .join(format!("{}.json", account_id)),
+        }
+    }
+}
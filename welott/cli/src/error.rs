@@ -0,0 +1,57 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Config(toml::de::Error),
+    Client(welott_client::Error),
+    /// Profile missing from the config file
+    UnknownProfile(String),
+    /// Amount of NEAR that is not a decimal number with at most 24 decimals
+    InvalidAmount(String),
+    /// Claim of tickets without any reward
+    NothingToClaim,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Json(err) => write!(f, "json error: {}", err),
+            Error::Config(err) => write!(f, "config error: {}", err),
+            Error::Client(err) => write!(f, "{}", err),
+            Error::UnknownProfile(profile) => write!(f, "unknown profile: {}", profile),
+            Error::InvalidAmount(amount) => write!(f, "invalid amount: {}", amount),
+            Error::NothingToClaim => write!(f, "none of the tickets has a reward"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::Config(err)
+    }
+}
+
+impl From<welott_client::Error> for Error {
+    fn from(err: welott_client::Error) -> Self {
+        Error::Client(err)
+    }
+}
@@ -0,0 +1,12 @@
+//! Operator CLI of the Welott contract, `welott <command>` with the network profiles of `welott.toml`
+mod cli;
+mod config;
+mod error;
+mod operator;
+mod units;
+
+pub use crate::cli::*;
+pub use crate::config::*;
+pub use crate::error::*;
+pub use crate::operator::*;
+pub use crate::units::*;
@@ -0,0 +1,39 @@
+use clap::Parser;
+use std::io;
+use std::process;
+use welott_cli::{Cli, Config, Operator, Result};
+use welott_client::{JsonRpcTransport, Signer, WelottClient};
+
+fn run(cli: &Cli) -> Result<()> {
+    let config = Config::load(&cli.config)?;
+    let profile = config.profile(&cli.profile)?;
+
+    let mut transport = JsonRpcTransport::new(&profile.rpc_url);
+    // the dry runs only send the views, they do not need a key
+    if let Some(signer_id) = cli
+        .command
+        .signer_id(&profile.contract_id, &profile.signer_id)
+        .filter(|_| !cli.dry_run)
+    {
+        let signer = Signer::from_credentials_file(profile.credentials_path(signer_id))?;
+        transport = transport.with_signer(signer);
+    }
+    let client = WelottClient::new(profile.contract_id.clone(), transport);
+
+    let stdout = io::stdout();
+    Operator::new(
+        &client,
+        profile.signer_id.clone(),
+        cli.dry_run,
+        stdout.lock(),
+    )
+    .run(&cli.command)
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(&cli) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,254 @@
+//! Runs the commands against the contract, printing the views and the call outcomes
+use crate::cli::{Command, ConfigCommand, RoundCommand, ViewCommand};
+use crate::error::{Error, Result};
+use crate::units::{format_near, parse_near};
+use near_sdk::serde::de::DeserializeOwned;
+use near_sdk::serde::Serialize;
+use near_sdk::AccountId;
+use std::io::Write;
+use std::path::Path;
+use welott_client::{
+    BracketPosition, Call, ConfigLottery, FunctionCallRequest, InitArgs, LotteryId, TicketId,
+    Transport, WelottClient,
+};
+
+/// near-cli command equivalent to a function call, printed by the dry runs
+pub fn near_call_command(
+    contract_id: &AccountId,
+    signer_id: &AccountId,
+    request: &FunctionCallRequest,
+) -> String {
+    format!(
+        "near call {} {} '{}' --accountId {} --depositYocto {} --gas {}",
+        contract_id,
+        request.method_name,
+        request.args_str(),
+        signer_id,
+        request.deposit,
+        request.gas.0
+    )
+}
+
+pub struct Operator<'a, T, W> {
+    client: &'a WelottClient<T>,
+    signer_id: AccountId,
+    dry_run: bool,
+    out: W,
+}
+
+impl<'a, T: Transport, W: Write> Operator<'a, T, W> {
+    pub fn new(client: &'a WelottClient<T>, signer_id: AccountId, dry_run: bool, out: W) -> Self {
+        Self {
+            client,
+            signer_id,
+            dry_run,
+            out,
+        }
+    }
+
+    pub fn run(&mut self, command: &Command) -> Result<()> {
+        let client = self.client;
+        match command {
+            Command::Deploy { wasm, init } => self.deploy(wasm, init.as_deref()),
+            Command::Config(ConfigCommand::Get) => {
+                self.print(&client.get_config().send()?)?;
+                self.print(&client.view_config_lottery().send()?)
+            }
+            Command::Config(ConfigCommand::Set {
+                max_number_tickets_per_buy,
+                min_discount_divisor,
+            }) => self.send(client.set_config(*max_number_tickets_per_buy, *min_discount_divisor)),
+            Command::Config(ConfigCommand::SetLottery { file }) => {
+                let config_lottery: ConfigLottery =
+                    serde_json::from_str(&std::fs::read_to_string(file)?)?;
+                self.send(client.set_config_lottery(config_lottery))
+            }
+            Command::Config(ConfigCommand::Execute { change_id }) => {
+                self.send(client.execute_change(*change_id))
+            }
+            Command::Round(RoundCommand::Start) => self.send(client.start_lottery()),
+            Command::Round(RoundCommand::Close) => self.send(client.close_lottery()),
+            Command::Round(RoundCommand::Draw {
+                lottery_id,
+                no_auto_injection,
+            }) => {
+                let lottery_id = self.lottery_id_or_latest(*lottery_id)?;
+                self.send(
+                    client.draw_final_number_and_make_lottery_claimable(
+                        lottery_id,
+                        !no_auto_injection,
+                    ),
+                )
+            }
+            Command::Inject {
+                amount,
+                lottery_id,
+                reserve,
+            } => {
+                let amount = parse_near(amount)?;
+                if *reserve {
+                    self.send(client.inject_reserve_funds().deposit(amount))
+                } else {
+                    let lottery_id = self.lottery_id_or_latest(*lottery_id)?;
+                    self.send(client.inject_funds(lottery_id).deposit(amount))
+                }
+            }
+            Command::View(ViewCommand::Lottery { lottery_id }) => {
+                let lottery = match lottery_id {
+                    Some(lottery_id) => client.view_lottery(*lottery_id).send()?,
+                    None => client.view_current_lottery_running().send()?,
+                };
+                self.print(&lottery)
+            }
+            Command::View(ViewCommand::Tickets {
+                account_id,
+                lottery_id,
+            }) => {
+                let account_id = account_id.clone().unwrap_or_else(|| self.signer_id.clone());
+                let lottery_id = self.lottery_id_or_latest(*lottery_id)?;
+                let user_data = client
+                    .view_user_info_for_lottery_id(account_id, lottery_id, None, None)
+                    .send()?;
+                let statuses = client
+                    .view_numbers_and_statuses_for_ticket_ids(
+                        user_data.ticket_ids.clone(),
+                        lottery_id,
+                    )
+                    .send()?;
+                self.print(&user_data)?;
+                self.print(&statuses)
+            }
+            Command::View(ViewCommand::User { account_id }) => {
+                let account_id = account_id.clone().unwrap_or_else(|| self.signer_id.clone());
+                self.print(
+                    &client
+                        .view_all_tickets_by_user_in_lottery_id(account_id.clone(), None, None)
+                        .send()?,
+                )?;
+                self.print(&client.storage_balance_of(account_id).send()?)
+            }
+            Command::Claim {
+                lottery_id,
+                ticket_ids,
+                brackets,
+            } => {
+                let (ticket_ids, brackets) = if brackets.is_empty() {
+                    self.winning_brackets(*lottery_id, ticket_ids)?
+                } else {
+                    (ticket_ids.clone(), brackets.clone())
+                };
+                self.send(client.claim_tickets(*lottery_id, ticket_ids, brackets))
+            }
+        }
+    }
+
+    fn lottery_id_or_latest(&self, lottery_id: Option<LotteryId>) -> Result<LotteryId> {
+        match lottery_id {
+            Some(lottery_id) => Ok(lottery_id),
+            None => Ok(self.client.view_latest_lottery_id().send()?),
+        }
+    }
+
+    /// Highest bracket paying a prize of every ticket, as checked by `claim_tickets`. The tickets
+    /// without a prize in that bracket are left out
+    fn winning_brackets(
+        &mut self,
+        lottery_id: LotteryId,
+        ticket_ids: &[TicketId],
+    ) -> Result<(Vec<TicketId>, Vec<BracketPosition>)> {
+        let lottery = self.client.view_lottery(lottery_id).send()?;
+        let ticket_numbers = self
+            .client
+            .view_numbers_and_statuses_for_ticket_ids(ticket_ids.to_vec(), lottery_id)
+            .send()?
+            .ticket_numbers;
+        let mut winning_ticket_ids = vec![];
+        let mut brackets = vec![];
+        for (&ticket_id, &ticket_number) in ticket_ids.iter().zip(&ticket_numbers) {
+            let winning_bracket = lottery
+                .highest_bracket(ticket_number)
+                .filter(|&bracket| lottery.near_per_bracket[bracket as usize] > 0);
+            match winning_bracket {
+                Some(bracket) => {
+                    winning_ticket_ids.push(ticket_id);
+                    brackets.push(bracket);
+                }
+                None => writeln!(self.out, "ticket {} has no reward, skipped", ticket_id)?,
+            }
+        }
+        if winning_ticket_ids.is_empty() {
+            return Err(Error::NothingToClaim);
+        }
+        Ok((winning_ticket_ids, brackets))
+    }
+
+    fn deploy(&mut self, wasm: &Path, init: Option<&Path>) -> Result<()> {
+        let code = std::fs::read(wasm)?;
+        let code_len = code.len();
+        let deploy = match init {
+            Some(init) => {
+                let args: InitArgs = serde_json::from_str(&std::fs::read_to_string(init)?)?;
+                self.client.deploy_and_init(code, &args)
+            }
+            None => self.client.deploy(code),
+        };
+        let contract_id = self.client.contract_id();
+        if self.dry_run {
+            write!(
+                self.out,
+                "near deploy --accountId {} --wasmFile {}",
+                contract_id,
+                wasm.display()
+            )?;
+            if let Some(request) = deploy.init_request() {
+                write!(
+                    self.out,
+                    " --initFunction {} --initArgs '{}' --initGas {}",
+                    request.method_name,
+                    request.args_str(),
+                    request.gas.0
+                )?;
+            }
+            writeln!(self.out)?;
+            writeln!(self.out, "code: {} bytes", code_len)?;
+            return Ok(());
+        }
+        let result = deploy.send()?;
+        self.print_logs(&result.logs)?;
+        writeln!(self.out, "deployed {} bytes to {}", code_len, contract_id)?;
+        Ok(())
+    }
+
+    /// Send a call, or print it for a dry run
+    fn send<R: DeserializeOwned + Serialize>(&mut self, call: Call<'_, T, R>) -> Result<()> {
+        let request = call.request();
+        if self.dry_run {
+            writeln!(
+                self.out,
+                "{}",
+                near_call_command(self.client.contract_id(), &self.signer_id, request)
+            )?;
+            writeln!(self.out, "deposit: {} NEAR", format_near(request.deposit))?;
+            return Ok(());
+        }
+        let result = call.send()?;
+        self.print_logs(&result.logs)?;
+        let value = serde_json::to_string(&result.value)?;
+        if value != "null" {
+            writeln!(self.out, "{}", value)?;
+        }
+        Ok(())
+    }
+
+    fn print_logs(&mut self, logs: &[String]) -> Result<()> {
+        for log in logs {
+            writeln!(self.out, "log: {}", log)?;
+        }
+        Ok(())
+    }
+
+    fn print<V: Serialize>(&mut self, value: &V) -> Result<()> {
+        writeln!(self.out, "{}", serde_json::to_string_pretty(value)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+//! Amounts of NEAR as typed by the operators, `1.5` for 1.5 NEAR
+use crate::error::{Error, Result};
+use near_sdk::Balance;
+
+const NEAR_DECIMALS: usize = 24;
+
+pub fn parse_near(amount: &str) -> Result<Balance> {
+    let invalid = || Error::InvalidAmount(amount.to_string());
+    let (units, decimals) = match amount.split_once('.') {
+        Some((units, decimals)) => (units, decimals),
+        None => (amount, ""),
+    };
+    if units.is_empty() && decimals.is_empty()
+        || decimals.len() > NEAR_DECIMALS
+        || !units
+            .chars()
+            .chain(decimals.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    format!("{}{:0<width$}", units, decimals, width = NEAR_DECIMALS)
+        .parse()
+        .map_err(|_| invalid())
+}
+
+pub fn format_near(amount: Balance) -> String {
+    let one_near = 10u128.pow(NEAR_DECIMALS as u32);
+    let decimals = format!("{:0>width$}", amount % one_near, width = NEAR_DECIMALS);
+    let decimals = decimals.trim_end_matches('0');
+    if decimals.is_empty() {
+        format!("{}", amount / one_near)
+    } else {
+        format!("{}.{}", amount / one_near, decimals)
+    }
+}
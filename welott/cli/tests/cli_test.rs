@@ -0,0 +1,184 @@
+use clap::Parser;
+use near_sdk::AccountId;
+use welott_cli::*;
+use welott_client::{
+    Lottery, LotteryNumberAndStatusData, MockTransport, RecordedRequest, Status, TicketNumber,
+    TicketStatus, WelottClient,
+};
+
+fn account(account_id: &str) -> AccountId {
+    account_id.parse().unwrap()
+}
+
+/// Run a command line with the mock transport, returning its output
+fn run(transport: &MockTransport, args: &[&str]) -> Result<String> {
+    let cli = Cli::try_parse_from(std::iter::once("welott").chain(args.iter().copied())).unwrap();
+    let client = WelottClient::new(account("welott.testnet"), transport);
+    let mut out = vec![];
+    Operator::new(&client, account("operator.testnet"), cli.dry_run, &mut out).run(&cli.command)?;
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn test_config_profiles() {
+    let config = Config::load(concat!(env!("CARGO_MANIFEST_DIR"), "/welott.example.toml")).unwrap();
+    let testnet = config.profile("testnet").unwrap();
+    assert_eq!(testnet.contract_id.as_str(), "welott52.lamns1.testnet");
+    assert!(testnet
+        .credentials_path(&testnet.signer_id)
+        .ends_with(".near-credentials/testnet/lamns1.testnet.json"));
+    assert!(matches!(
+        config.profile("localnet"),
+        Err(Error::UnknownProfile(_))
+    ));
+
+    let config = Config::parse(
+        r#"
+        [profiles.sandbox]
+        network_id = "sandbox"
+        rpc_url = "http://localhost:3030"
+        contract_id = "welott.test.near"
+        signer_id = "test.near"
+        credentials = "/tmp/validator_key.json"
+        "#,
+    )
+    .unwrap();
+    let sandbox = config.profile("sandbox").unwrap();
+    assert_eq!(
+        sandbox.credentials_path(&account("test.near")).to_str(),
+        Some("/tmp/validator_key.json")
+    );
+    // the key of the contract account, for the deploy
+    assert!(sandbox
+        .credentials_path(&account("welott.test.near"))
+        .ends_with(".near-credentials/sandbox/welott.test.near.json"));
+}
+
+#[test]
+fn test_near_amounts() {
+    assert_eq!(parse_near("1").unwrap(), 10u128.pow(24));
+    assert_eq!(parse_near("0.25").unwrap(), 25 * 10u128.pow(22));
+    assert_eq!(parse_near(".000000000000000000000001").unwrap(), 1);
+    for amount in &["", ".", "1,5", "-1", "0.0000000000000000000000001"] {
+        assert!(parse_near(amount).is_err(), "{}", amount);
+    }
+    assert_eq!(format_near(25 * 10u128.pow(22)), "0.25");
+    assert_eq!(format_near(3 * 10u128.pow(24)), "3");
+    assert_eq!(format_near(1), "0.000000000000000000000001");
+}
+
+#[test]
+fn test_dry_run() {
+    let transport = MockTransport::new();
+    let output = run(&transport, &["--dry-run", "round", "start"]).unwrap();
+    assert_eq!(
+        output,
+        "near call welott.testnet start_lottery '{}' --accountId operator.testnet --depositYocto 1 --gas 100000000000000\n\
+         deposit: 0.000000000000000000000001 NEAR\n"
+    );
+
+    // the lottery id still comes from the contract
+    transport.respond("view_latest_lottery_id", &4);
+    let output = run(&transport, &["--dry-run", "inject", "--amount", "12.5"]).unwrap();
+    assert!(output.starts_with(
+        "near call welott.testnet inject_funds '{\"_lottery_id\":4}' --accountId operator.testnet --depositYocto 12500000000000000000000000 "
+    ));
+    assert!(output.ends_with("deposit: 12.5 NEAR\n"));
+
+    // nothing was sent
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method_name(), "view_latest_lottery_id");
+}
+
+#[test]
+fn test_round_draw() {
+    let transport = MockTransport::new();
+    transport.respond_with_logs(
+        "draw_final_number_and_make_lottery_claimable",
+        &(),
+        vec!["EVENT_JSON:{}".to_string()],
+    );
+    let output = run(&transport, &["round", "draw", "3", "--no-auto-injection"]).unwrap();
+    assert_eq!(output, "log: EVENT_JSON:{}\n");
+
+    match &transport.requests()[0] {
+        RecordedRequest::Call { request, .. } => {
+            assert_eq!(
+                request.args_str(),
+                r#"{"_lottery_id":3,"_auto_injection":false}"#
+            );
+            assert_eq!(request.deposit, 1);
+        }
+        request => panic!("unexpected request {:?}", request),
+    }
+}
+
+fn claimable_lottery() -> Lottery {
+    Lottery {
+        lottery_id: 1,
+        status: Status::Claimable,
+        final_number: 1234567,
+        // bracket 3 pays no prize
+        rewards_breakdown: vec![125, 375, 1250, 0, 3250, 5000],
+        fixed_prize_per_bracket: vec![0; 6],
+        near_per_bracket: vec![0, 0, 1000, 0, 0, 0],
+        ..Default::default()
+    }
+}
+
+fn ticket_numbers(ticket_numbers: Vec<TicketNumber>) -> LotteryNumberAndStatusData {
+    LotteryNumberAndStatusData {
+        ticket_status: ticket_numbers
+            .iter()
+            .map(|_| TicketStatus::Claimable)
+            .collect(),
+        ticket_numbers,
+    }
+}
+
+#[test]
+fn test_claim_highest_brackets() {
+    let transport = MockTransport::new();
+    // ticket 7 wins in bracket 2, ticket 8 in no bracket, ticket 9 in bracket 1 without prize and
+    // ticket 10 is counted in bracket 2 as bracket 3 pays no prize
+    transport.respond("view_lottery", &claimable_lottery());
+    transport.respond(
+        "view_numbers_and_statuses_for_ticket_ids",
+        &ticket_numbers(vec![1000567, 1000000, 1000067, 1004567]),
+    );
+    let output = run(
+        &transport,
+        &["claim", "--lottery-id", "1", "--ticket-ids", "7,8,9,10"],
+    )
+    .unwrap();
+    assert_eq!(
+        output,
+        "ticket 8 has no reward, skipped\nticket 9 has no reward, skipped\n"
+    );
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[1].args_str(),
+        r#"{"_ticket_ids":[7,8,9,10],"_lottery_id":1}"#
+    );
+    assert_eq!(
+        requests[2].args_str(),
+        r#"{"_lottery_id":1,"_ticket_ids":[7,10],"_brackets":[2,2]}"#
+    );
+
+    // no reward at all
+    transport.respond("view_lottery", &claimable_lottery());
+    transport.respond(
+        "view_numbers_and_statuses_for_ticket_ids",
+        &ticket_numbers(vec![1000000]),
+    );
+    assert!(matches!(
+        run(
+            &transport,
+            &["claim", "--lottery-id", "1", "--ticket-ids", "8"]
+        ),
+        Err(Error::NothingToClaim)
+    ));
+}
@@ -0,0 +1,235 @@
+//! A builder for every view and call of the contract, with the argument names of the contract
+use crate::request::{Call, Deploy, View, DEFAULT_GAS, MAX_GAS, ONE_YOCTO};
+use crate::transport::Transport;
+use contract::*;
+use near_contract_standards::storage_management::{StorageBalance, StorageBalanceBounds};
+use near_sdk::json_types::{Base58CryptoHash, U128};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::AccountId;
+
+/// Arguments of the init method `new`
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InitArgs {
+    pub owner_id: AccountId,
+    pub injector_address: AccountId,
+    pub operator_address: AccountId,
+    pub treasury_address: AccountId,
+    pub config_lottery: ConfigLottery,
+}
+
+pub struct WelottClient<T> {
+    contract_id: AccountId,
+    transport: T,
+}
+
+impl<T: Transport> WelottClient<T> {
+    pub fn new(contract_id: AccountId, transport: T) -> Self {
+        Self {
+            contract_id,
+            transport,
+        }
+    }
+
+    pub fn contract_id(&self) -> &AccountId {
+        &self.contract_id
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Deploy the wasm of the contract, signed by the contract account
+    pub fn deploy(&self, code: Vec<u8>) -> Deploy<'_, T> {
+        Deploy::new(self, code)
+    }
+
+    /// Deploy and initialize a new contract
+    pub fn deploy_and_init(&self, code: Vec<u8>, args: &InitArgs) -> Deploy<'_, T> {
+        Deploy::new(self, code).init("new", args)
+    }
+}
+
+/// Declare the views, `fn method(args) -> Response;`
+macro_rules! views {
+    ($($(#[$doc:meta])* fn $method:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty;)*) => {
+        impl<T: Transport> WelottClient<T> {
+            $(
+                $(#[$doc])*
+                #[allow(clippy::too_many_arguments)]
+                pub fn $method(&self, $($arg: $ty),*) -> View<'_, T, $ret> {
+                    #[derive(near_sdk::serde::Serialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                    struct Args {
+                        $($arg: $ty),*
+                    }
+                    View::new(self, stringify!($method), &Args { $($arg),* })
+                }
+            )*
+        }
+    };
+}
+
+/// Declare the calls with their default deposit and gas, `fn method(args) -> Response = (deposit, gas);`
+macro_rules! calls {
+    ($($(#[$doc:meta])* fn $method:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty = ($deposit:expr, $gas:expr);)*) => {
+        impl<T: Transport> WelottClient<T> {
+            $(
+                $(#[$doc])*
+                #[allow(clippy::too_many_arguments)]
+                pub fn $method(&self, $($arg: $ty),*) -> Call<'_, T, $ret> {
+                    #[derive(near_sdk::serde::Serialize)]
+                    #[serde(crate = "near_sdk::serde")]
+                    struct Args {
+                        $($arg: $ty),*
+                    }
+                    Call::new(self, stringify!($method), &Args { $($arg),* }, $deposit, $gas)
+                }
+            )*
+        }
+    };
+}
+
+views! {
+    /// Lottery by id
+    fn view_lottery(_lottery_id: LotteryId) -> Lottery;
+    /// Page of the lotteries
+    fn view_lotteries(_cursor: Option<u64>, _size: Option<u64>) -> Vec<Lottery>;
+    fn view_latest_lottery_id() -> LotteryId;
+    fn view_current_lottery_running() -> Lottery;
+    fn view_number_tickets_per_lottery(_lottery_id: LotteryId) -> u32;
+    fn view_remaining_tickets_per_lottery(_lottery_id: LotteryId) -> u64;
+    fn view_remaining_tickets_per_account(_user: AccountId, _lottery_id: LotteryId) -> u64;
+    fn view_reserve_balance() -> U128;
+    fn view_lottery_dust(_lottery_id: LotteryId) -> U128;
+    /// Price of a number of tickets with the bulk discount
+    fn calculate_total_price_for_bulk_tickets(_lottery_id: LotteryId, _number_tickets: u128) -> u128;
+    fn view_rewards_for_ticket_id(
+        _lottery_id: LotteryId,
+        _ticket_id: TicketId,
+        _bracket: BracketPosition,
+    ) -> u128;
+    /// Tickets of a player in every lottery
+    fn view_all_tickets_by_user_in_lottery_id(
+        _user: AccountId,
+        _cursor: Option<usize>,
+        _size: Option<usize>,
+    ) -> Vec<LotteryUserData>;
+    /// Tickets of a player in a lottery with their statuses
+    fn view_user_info_for_lottery_id(
+        _user: AccountId,
+        _lottery_id: LotteryId,
+        _cursor: Option<usize>,
+        _size: Option<usize>,
+    ) -> LotteryUserData;
+    fn view_numbers_and_statuses_for_ticket_ids(
+        _ticket_ids: Vec<TicketId>,
+        _lottery_id: LotteryId,
+    ) -> LotteryNumberAndStatusData;
+    fn view_random_result() -> u32;
+    fn view_config_lottery() -> ConfigLottery;
+    fn view_accounts(_cursor: usize, _size: usize) -> Vec<AccountId>;
+    fn get_current_timestamp() -> u64;
+    fn get_config() -> ConfigContractData;
+    fn _get_config() -> ConfigContractData;
+    fn get_accounts_paged(from_index: Option<u64>, limit: Option<u64>) -> Vec<AccountSimpleView>;
+    fn get_num_accounts() -> u32;
+    fn get_owner() -> AccountId;
+    fn has_role(role: Role, account_id: AccountId) -> bool;
+    fn view_role_members(role: Role) -> Vec<AccountId>;
+    fn view_account_roles(account_id: AccountId) -> Vec<Role>;
+    fn view_role_proposal(role: AddressRole) -> Option<RoleProposal>;
+    fn view_pause_flags() -> PauseFlags;
+    fn view_emergency() -> Option<EmergencyView>;
+    fn view_emergency_refund(_lottery_id: LotteryId, account_id: AccountId) -> U128;
+    fn view_governance() -> Option<AccountId>;
+    fn view_config_versions(_cursor: Option<u64>, _size: Option<u64>) -> Vec<ConfigVersion>;
+    fn view_lottery_config_version(_lottery_id: LotteryId) -> ConfigVersion;
+    fn view_ledger() -> LedgerView;
+    fn view_prize_liabilities(_lottery_id: LotteryId) -> U128;
+    fn view_solvency() -> SolvencyView;
+    fn view_multisig_signers() -> MultisigSignersView;
+    fn view_multisig_proposal(proposal_id: u64) -> Option<MultisigProposal>;
+    fn view_multisig_proposals(_cursor: Option<u64>, _size: Option<u64>) -> Vec<MultisigProposal>;
+    fn view_pending_changes() -> Vec<PendingChange>;
+    fn view_timelock_delay() -> u64;
+    fn storage_balance_bounds() -> StorageBalanceBounds;
+    fn storage_balance_of(account_id: AccountId) -> Option<StorageBalance>;
+    fn debug_storage_balance_of(account_id: AccountId) -> Option<StorageBalance>;
+}
+
+calls! {
+    // lottery rounds, by the operator or a keeper
+    fn start_lottery() -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn close_lottery() -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn draw_final_number_and_make_lottery_claimable(
+        _lottery_id: LotteryId,
+        _auto_injection: bool,
+    ) -> () = (ONE_YOCTO, MAX_GAS);
+
+    // players, the deposit of `buy_tickets` is the price from `calculate_total_price_for_bulk_tickets`
+    fn buy_tickets(_lottery_id: LotteryId, _ticket_numbers: Vec<TicketNumber>) -> () = (0, DEFAULT_GAS);
+    fn claim_tickets(
+        _lottery_id: LotteryId,
+        _ticket_ids: Vec<TicketId>,
+        _brackets: Vec<BracketPosition>,
+    ) -> () = (ONE_YOCTO, MAX_GAS);
+    fn emergency_withdraw(_lottery_id: LotteryId) -> U128 = (ONE_YOCTO, DEFAULT_GAS);
+
+    // storage, the deposit of `storage_deposit` is at least `storage_balance_bounds().min`
+    fn storage_deposit(
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance = (0, DEFAULT_GAS);
+    fn storage_withdraw(amount: Option<U128>) -> StorageBalance = (ONE_YOCTO, DEFAULT_GAS);
+    fn storage_unregister(force: Option<bool>) -> bool = (ONE_YOCTO, DEFAULT_GAS);
+
+    // funds, the deposit is the amount injected
+    fn inject_funds(_lottery_id: LotteryId) -> () = (0, DEFAULT_GAS);
+    fn inject_reserve_funds() -> () = (0, DEFAULT_GAS);
+    fn withdraw_treasury_payable() -> () = (ONE_YOCTO, DEFAULT_GAS);
+
+    // config, queued behind the timelock
+    fn set_config(_max_number_tickets_per_buy: u64, _min_discount_divisor: u128) -> u64 = (ONE_YOCTO, DEFAULT_GAS);
+    fn set_config_lottery(_config_lottery: ConfigLottery) -> u64 = (0, DEFAULT_GAS);
+    fn set_timelock_delay(_delay: u64) -> u64 = (ONE_YOCTO, DEFAULT_GAS);
+    fn execute_change(_change_id: u64) -> () = (0, DEFAULT_GAS);
+    fn cancel_change(_change_id: u64) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn apply_governance_config(_config_lottery: ConfigLottery) -> u64 = (0, DEFAULT_GAS);
+
+    // roles
+    fn grant_role(role: Role, account_id: AccountId) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn revoke_role(role: Role, account_id: AccountId) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn renounce_role(role: Role) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn propose_role(
+        role: AddressRole,
+        account_id: AccountId,
+        expires_in: Option<u64>,
+    ) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn accept_role(role: AddressRole) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn cancel_role_proposal(role: AddressRole) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn accept_owner() -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn cancel_owner_proposal() -> () = (ONE_YOCTO, DEFAULT_GAS);
+
+    // pausing and emergency
+    fn pause(flag: PauseFlag) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn resume(flag: PauseFlag) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn pause_contract() -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn resume_contract() -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn enter_emergency() -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn emergency_withdraw_surplus() -> U128 = (ONE_YOCTO, DEFAULT_GAS);
+
+    // multisig, the proposals return their id
+    fn multisig_propose(action: MultisigAction) -> u64 = (ONE_YOCTO, MAX_GAS);
+    fn multisig_approve(proposal_id: u64) -> () = (ONE_YOCTO, MAX_GAS);
+    fn multisig_cancel(proposal_id: u64) -> () = (ONE_YOCTO, DEFAULT_GAS);
+    fn set_fee_recipients(_fee_recipients: Vec<FeeRecipient>) -> u64 = (ONE_YOCTO, DEFAULT_GAS);
+    fn set_governance(governance_id: Option<AccountId>) -> u64 = (ONE_YOCTO, DEFAULT_GAS);
+    fn propose_owner(owner_id: AccountId, previous_owner_id: AccountId, expires_in: Option<u64>) -> u64 = (ONE_YOCTO, DEFAULT_GAS);
+    fn propose_upgrade(_code_hash: Base58CryptoHash) -> u64 = (ONE_YOCTO, DEFAULT_GAS);
+    fn recover_wrong_tokens(
+        _token_address: Option<AccountId>,
+        _token_amount: U128,
+        _receiver_id: AccountId,
+    ) -> u64 = (ONE_YOCTO, MAX_GAS);
+}
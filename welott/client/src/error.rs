@@ -0,0 +1,31 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Arguments or response that do not match the contract API
+    Json(serde_json::Error),
+    /// Transport that could not reach the contract
+    Transport(String),
+    /// Call or view that failed in the contract, with the error returned by the node
+    Execution(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Json(err) => write!(f, "json error: {}", err),
+            Error::Transport(err) => write!(f, "transport error: {}", err),
+            Error::Execution(err) => write!(f, "execution error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
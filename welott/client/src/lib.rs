@@ -0,0 +1,25 @@
+//! Typed client of the Welott contract. The request and response types are the ones of the
+//! contract crate, the calls and views are built by `WelottClient` and sent by a `Transport`:
+//! `JsonRpcTransport` for a node, `MockTransport` for the tests.
+mod client;
+mod error;
+mod request;
+#[cfg(feature = "rpc")]
+mod rpc;
+mod transport;
+
+pub use crate::client::*;
+pub use crate::error::*;
+pub use crate::request::*;
+#[cfg(feature = "rpc")]
+pub use crate::rpc::*;
+pub use crate::transport::*;
+
+pub use contract::{
+    AccountSimpleView, AddressRole, BracketPosition, ConfigApproval, ConfigChange,
+    ConfigContractData, ConfigLottery, ConfigVersion, EmergencyView, FeeRecipient, LedgerView,
+    Lottery, LotteryId, LotteryNumberAndStatusData, LotteryUserData, MultisigAction,
+    MultisigProposal, MultisigProposalStatus, MultisigSignersView, OverflowPolicy, PauseFlag,
+    PauseFlags, PendingChange, Role, RoleProposal, SolvencyView, Status, TicketId, TicketNumber,
+    TicketStatus,
+};
@@ -0,0 +1,173 @@
+//! Typed builders of the views and calls, the response type is the one returned by the contract
+use crate::client::WelottClient;
+use crate::error::Result;
+use crate::transport::{FunctionCallRequest, Transport};
+use near_sdk::serde::de::DeserializeOwned;
+use near_sdk::serde::Serialize;
+use near_sdk::{Balance, Gas};
+use std::marker::PhantomData;
+
+/// Gas attached to the calls by default
+pub const DEFAULT_GAS: Gas = Gas(100_000_000_000_000);
+/// Gas for the calls creating promises, like the draw paying the fees
+pub const MAX_GAS: Gas = Gas(300_000_000_000_000);
+/// Deposit required by the privileged and the claiming methods
+pub const ONE_YOCTO: Balance = 1;
+
+fn to_args<A: Serialize>(args: &A) -> Vec<u8> {
+    // the arguments are plain structs, they always serialize
+    serde_json::to_vec(args).expect("Arguments should serialize to JSON")
+}
+
+fn from_value<R: DeserializeOwned>(value: &[u8]) -> Result<R> {
+    // the methods returning nothing have an empty value
+    let value = if value.is_empty() { b"null" } else { value };
+    Ok(serde_json::from_slice(value)?)
+}
+
+pub struct View<'a, T, R> {
+    client: &'a WelottClient<T>,
+    method_name: &'static str,
+    args: Vec<u8>,
+    response: PhantomData<R>,
+}
+
+impl<'a, T: Transport, R: DeserializeOwned> View<'a, T, R> {
+    pub(crate) fn new<A: Serialize>(
+        client: &'a WelottClient<T>,
+        method_name: &'static str,
+        args: &A,
+    ) -> Self {
+        Self {
+            client,
+            method_name,
+            args: to_args(args),
+            response: PhantomData,
+        }
+    }
+
+    pub fn method_name(&self) -> &str {
+        self.method_name
+    }
+
+    pub fn args(&self) -> &[u8] {
+        &self.args
+    }
+
+    pub fn send(self) -> Result<R> {
+        let value = self.client.transport().view(
+            self.client.contract_id(),
+            self.method_name,
+            &self.args,
+        )?;
+        from_value(&value)
+    }
+}
+
+/// Result of a call with the logs of its receipts
+#[derive(Debug, Clone)]
+pub struct CallResult<R> {
+    pub value: R,
+    pub logs: Vec<String>,
+}
+
+pub struct Call<'a, T, R> {
+    client: &'a WelottClient<T>,
+    request: FunctionCallRequest,
+    response: PhantomData<R>,
+}
+
+impl<'a, T: Transport, R: DeserializeOwned> Call<'a, T, R> {
+    pub(crate) fn new<A: Serialize>(
+        client: &'a WelottClient<T>,
+        method_name: &'static str,
+        args: &A,
+        deposit: Balance,
+        gas: Gas,
+    ) -> Self {
+        Self {
+            client,
+            request: FunctionCallRequest {
+                method_name: method_name.to_string(),
+                args: to_args(args),
+                gas,
+                deposit,
+            },
+            response: PhantomData,
+        }
+    }
+
+    pub fn gas(mut self, gas: Gas) -> Self {
+        self.request.gas = gas;
+        self
+    }
+
+    pub fn deposit(mut self, deposit: Balance) -> Self {
+        self.request.deposit = deposit;
+        self
+    }
+
+    /// The function call that `send` would sign, for dry runs
+    pub fn request(&self) -> &FunctionCallRequest {
+        &self.request
+    }
+
+    pub fn send(self) -> Result<CallResult<R>> {
+        let outcome = self
+            .client
+            .transport()
+            .call(self.client.contract_id(), &self.request)?;
+        Ok(CallResult {
+            value: from_value(&outcome.value)?,
+            logs: outcome.logs,
+        })
+    }
+}
+
+pub struct Deploy<'a, T> {
+    client: &'a WelottClient<T>,
+    code: Vec<u8>,
+    init: Option<FunctionCallRequest>,
+}
+
+impl<'a, T: Transport> Deploy<'a, T> {
+    pub(crate) fn new(client: &'a WelottClient<T>, code: Vec<u8>) -> Self {
+        Self {
+            client,
+            code,
+            init: None,
+        }
+    }
+
+    /// Initialize the contract in the same transaction, it can only be done once
+    pub fn init<A: Serialize>(mut self, method_name: &str, args: &A) -> Self {
+        self.init = Some(FunctionCallRequest {
+            method_name: method_name.to_string(),
+            args: to_args(args),
+            gas: DEFAULT_GAS,
+            deposit: 0,
+        });
+        self
+    }
+
+    pub fn code(&self) -> &[u8] {
+        &self.code
+    }
+
+    /// The init call that `send` would sign, for dry runs
+    pub fn init_request(&self) -> Option<&FunctionCallRequest> {
+        self.init.as_ref()
+    }
+
+    pub fn send(self) -> Result<CallResult<()>> {
+        let outcome = self.client.transport().deploy(
+            self.client.contract_id(),
+            &self.code,
+            self.init.as_ref(),
+        )?;
+        Ok(CallResult {
+            value: (),
+            logs: outcome.logs,
+        })
+    }
+}
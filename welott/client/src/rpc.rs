@@ -0,0 +1,247 @@
+//! Transport over the NEAR JSON-RPC, the calls are signed with a full access key of the signer
+use crate::error::{Error, Result};
+use crate::transport::{CallOutcome, FunctionCallRequest, Transport};
+use ed25519_dalek::{Keypair, Signer as _};
+use near_sdk::borsh::BorshSerialize;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::AccountId;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+const ED25519_KEY_TYPE: u8 = 0;
+const DEPLOY_CONTRACT_ACTION: u8 = 1;
+const FUNCTION_CALL_ACTION: u8 = 2;
+
+enum Action<'a> {
+    DeployContract(&'a [u8]),
+    FunctionCall(&'a FunctionCallRequest),
+}
+
+/// Account signing the calls, with its ed25519 key
+pub struct Signer {
+    pub account_id: AccountId,
+    keypair: Keypair,
+}
+
+fn decode_key(key: &str) -> Result<Vec<u8>> {
+    let key = key
+        .strip_prefix("ed25519:")
+        .ok_or_else(|| Error::Transport(format!("only ed25519 keys are supported: {}", key)))?;
+    bs58::decode(key)
+        .into_vec()
+        .map_err(|err| Error::Transport(format!("invalid key: {}", err)))
+}
+
+impl Signer {
+    /// `secret_key` like `ed25519:<base58 of the 64 bytes keypair>`
+    pub fn from_secret_key(account_id: AccountId, secret_key: &str) -> Result<Self> {
+        let keypair = Keypair::from_bytes(&decode_key(secret_key)?)
+            .map_err(|err| Error::Transport(format!("invalid secret key: {}", err)))?;
+        Ok(Self {
+            account_id,
+            keypair,
+        })
+    }
+
+    /// Key file written by near-cli, `~/.near-credentials/<network>/<account_id>.json`
+    pub fn from_credentials_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())
+            .map_err(|err| Error::Transport(format!("{}: {}", path.as_ref().display(), err)))?;
+        let credentials: Value = serde_json::from_str(&content)?;
+        let field = |name: &str| credentials[name].as_str().map(str::to_string);
+        let account_id = field("account_id")
+            .ok_or_else(|| Error::Transport("credentials without account_id".to_string()))?;
+        let secret_key = field("private_key")
+            .or_else(|| field("secret_key"))
+            .ok_or_else(|| Error::Transport("credentials without private_key".to_string()))?;
+        let account_id = account_id
+            .parse()
+            .map_err(|_| Error::Transport(format!("invalid account id: {}", account_id)))?;
+        Self::from_secret_key(account_id, &secret_key)
+    }
+
+    pub fn public_key(&self) -> String {
+        format!(
+            "ed25519:{}",
+            bs58::encode(self.keypair.public.as_bytes()).into_string()
+        )
+    }
+}
+
+/// Borsh encoding of a transaction, as the protocol expects it
+fn encode_transaction(
+    signer: &Signer,
+    nonce: u64,
+    receiver_id: &AccountId,
+    block_hash: &[u8],
+    actions: &[Action],
+) -> Vec<u8> {
+    let mut buf = vec![];
+    // the writes into a vec can not fail
+    signer.account_id.as_str().serialize(&mut buf).unwrap();
+    buf.push(ED25519_KEY_TYPE);
+    buf.extend_from_slice(signer.keypair.public.as_bytes());
+    nonce.serialize(&mut buf).unwrap();
+    receiver_id.as_str().serialize(&mut buf).unwrap();
+    buf.extend_from_slice(block_hash);
+    (actions.len() as u32).serialize(&mut buf).unwrap();
+    for action in actions {
+        match action {
+            Action::DeployContract(code) => {
+                buf.push(DEPLOY_CONTRACT_ACTION);
+                code.serialize(&mut buf).unwrap();
+            }
+            Action::FunctionCall(request) => {
+                buf.push(FUNCTION_CALL_ACTION);
+                request.method_name.serialize(&mut buf).unwrap();
+                request.args.serialize(&mut buf).unwrap();
+                request.gas.0.serialize(&mut buf).unwrap();
+                request.deposit.serialize(&mut buf).unwrap();
+            }
+        }
+    }
+    buf
+}
+
+pub struct JsonRpcTransport {
+    rpc_url: String,
+    signer: Option<Signer>,
+}
+
+impl JsonRpcTransport {
+    /// Transport for the views only
+    pub fn new(rpc_url: &str) -> Self {
+        Self {
+            rpc_url: rpc_url.to_string(),
+            signer: None,
+        }
+    }
+
+    pub fn with_signer(mut self, signer: Signer) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    fn rpc(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": "welott-client",
+            "method": method,
+            "params": params,
+        });
+        let response = ureq::post(&self.rpc_url)
+            .set("Content-Type", "application/json")
+            .send_string(&body.to_string())
+            .map_err(|err| Error::Transport(err.to_string()))?
+            .into_string()
+            .map_err(|err| Error::Transport(err.to_string()))?;
+        let mut response: Value = serde_json::from_str(&response)?;
+        if let Some(error) = response.get("error") {
+            return Err(if error["cause"]["name"] == "CONTRACT_EXECUTION_ERROR" {
+                Error::Execution(error["cause"]["info"].to_string())
+            } else {
+                Error::Transport(error.to_string())
+            });
+        }
+        Ok(response["result"].take())
+    }
+
+    fn send_transaction(&self, receiver_id: &AccountId, actions: &[Action]) -> Result<CallOutcome> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or_else(|| Error::Transport("a signer is required for the calls".to_string()))?;
+        if actions
+            .iter()
+            .any(|action| matches!(action, Action::DeployContract(_)))
+            && &signer.account_id != receiver_id
+        {
+            return Err(Error::Transport(format!(
+                "the code of {} can only be deployed by its own key",
+                receiver_id
+            )));
+        }
+        let access_key = self.rpc(
+            "query",
+            json!({
+                "request_type": "view_access_key",
+                "finality": "final",
+                "account_id": signer.account_id,
+                "public_key": signer.public_key(),
+            }),
+        )?;
+        let nonce = access_key["nonce"]
+            .as_u64()
+            .ok_or_else(|| Error::Transport(format!("invalid access key: {}", access_key)))?;
+        let block_hash = bs58::decode(access_key["block_hash"].as_str().unwrap_or_default())
+            .into_vec()
+            .map_err(|err| Error::Transport(format!("invalid block hash: {}", err)))?;
+
+        let mut signed_transaction =
+            encode_transaction(signer, nonce + 1, receiver_id, &block_hash, actions);
+        let signature = signer.keypair.sign(&Sha256::digest(&signed_transaction));
+        signed_transaction.push(ED25519_KEY_TYPE);
+        signed_transaction.extend_from_slice(&signature.to_bytes());
+
+        let outcome = self.rpc(
+            "broadcast_tx_commit",
+            json!([Base64VecU8(signed_transaction)]),
+        )?;
+        let status = &outcome["status"];
+        if let Some(failure) = status.get("Failure") {
+            return Err(Error::Execution(failure.to_string()));
+        }
+        let value: Base64VecU8 = match status.get("SuccessValue") {
+            Some(value) => serde_json::from_value(value.clone())?,
+            None => Base64VecU8(vec![]),
+        };
+        let logs = outcome["receipts_outcome"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|receipt| receipt["outcome"]["logs"].as_array())
+            .flatten()
+            .filter_map(|log| log.as_str().map(str::to_string))
+            .collect();
+        Ok(CallOutcome {
+            value: value.0,
+            logs,
+        })
+    }
+}
+
+impl Transport for JsonRpcTransport {
+    fn view(&self, contract_id: &AccountId, method_name: &str, args: &[u8]) -> Result<Vec<u8>> {
+        let result = self.rpc(
+            "query",
+            json!({
+                "request_type": "call_function",
+                "finality": "final",
+                "account_id": contract_id,
+                "method_name": method_name,
+                "args_base64": Base64VecU8(args.to_vec()),
+            }),
+        )?;
+        // older nodes return the panics of the views as a result
+        if let Some(error) = result.get("error") {
+            return Err(Error::Execution(error.to_string()));
+        }
+        Ok(serde_json::from_value(result["result"].clone())?)
+    }
+
+    fn call(&self, contract_id: &AccountId, request: &FunctionCallRequest) -> Result<CallOutcome> {
+        self.send_transaction(contract_id, &[Action::FunctionCall(request)])
+    }
+
+    fn deploy(
+        &self,
+        contract_id: &AccountId,
+        code: &[u8],
+        init: Option<&FunctionCallRequest>,
+    ) -> Result<CallOutcome> {
+        let mut actions = vec![Action::DeployContract(code)];
+        actions.extend(init.map(Action::FunctionCall));
+        self.send_transaction(contract_id, &actions)
+    }
+}
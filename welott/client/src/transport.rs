@@ -0,0 +1,232 @@
+//! How the client reaches the contract. A transport is bound to a network and, for the calls, to a signer
+use crate::error::{Error, Result};
+use near_sdk::serde::Serialize;
+use near_sdk::{AccountId, Balance, Gas};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+/// Function call sent to the contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCallRequest {
+    pub method_name: String,
+    // JSON arguments
+    pub args: Vec<u8>,
+    pub gas: Gas,
+    pub deposit: Balance,
+}
+
+impl FunctionCallRequest {
+    pub fn args_str(&self) -> &str {
+        std::str::from_utf8(&self.args).unwrap_or_default()
+    }
+}
+
+/// Result of a function call, the logs of every receipt included
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallOutcome {
+    // JSON return value, empty for the methods returning nothing
+    pub value: Vec<u8>,
+    pub logs: Vec<String>,
+}
+
+pub trait Transport {
+    /// Run a view method, returning its JSON result
+    fn view(&self, contract_id: &AccountId, method_name: &str, args: &[u8]) -> Result<Vec<u8>>;
+
+    /// Sign and send a function call, waiting for its outcome
+    fn call(&self, contract_id: &AccountId, request: &FunctionCallRequest) -> Result<CallOutcome>;
+
+    /// Deploy the code to the contract account, with its init call in the same transaction
+    fn deploy(
+        &self,
+        contract_id: &AccountId,
+        code: &[u8],
+        init: Option<&FunctionCallRequest>,
+    ) -> Result<CallOutcome>;
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+    fn view(&self, contract_id: &AccountId, method_name: &str, args: &[u8]) -> Result<Vec<u8>> {
+        (**self).view(contract_id, method_name, args)
+    }
+
+    fn call(&self, contract_id: &AccountId, request: &FunctionCallRequest) -> Result<CallOutcome> {
+        (**self).call(contract_id, request)
+    }
+
+    fn deploy(
+        &self,
+        contract_id: &AccountId,
+        code: &[u8],
+        init: Option<&FunctionCallRequest>,
+    ) -> Result<CallOutcome> {
+        (**self).deploy(contract_id, code, init)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+    fn view(&self, contract_id: &AccountId, method_name: &str, args: &[u8]) -> Result<Vec<u8>> {
+        (**self).view(contract_id, method_name, args)
+    }
+
+    fn call(&self, contract_id: &AccountId, request: &FunctionCallRequest) -> Result<CallOutcome> {
+        (**self).call(contract_id, request)
+    }
+
+    fn deploy(
+        &self,
+        contract_id: &AccountId,
+        code: &[u8],
+        init: Option<&FunctionCallRequest>,
+    ) -> Result<CallOutcome> {
+        (**self).deploy(contract_id, code, init)
+    }
+}
+
+/// Request received by the mock transport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecordedRequest {
+    View {
+        contract_id: AccountId,
+        method_name: String,
+        args: Vec<u8>,
+    },
+    Call {
+        contract_id: AccountId,
+        request: FunctionCallRequest,
+    },
+    Deploy {
+        contract_id: AccountId,
+        code: Vec<u8>,
+        init: Option<FunctionCallRequest>,
+    },
+}
+
+impl RecordedRequest {
+    pub fn method_name(&self) -> &str {
+        match self {
+            RecordedRequest::View { method_name, .. } => method_name,
+            RecordedRequest::Call { request, .. } => &request.method_name,
+            RecordedRequest::Deploy { init, .. } => init
+                .as_ref()
+                .map(|init| init.method_name.as_str())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn args_str(&self) -> &str {
+        let args = match self {
+            RecordedRequest::View { args, .. } => args,
+            RecordedRequest::Call { request, .. } => &request.args,
+            RecordedRequest::Deploy { init, .. } => match init {
+                Some(init) => &init.args,
+                None => return "",
+            },
+        };
+        std::str::from_utf8(args).unwrap_or_default()
+    }
+}
+
+/// Transport answering with queued responses, in order per method, and recording the requests.
+/// A call or a deploy without a queued response returns nothing, a view without one fails.
+/// The responses of a deploy are queued under the name of its init method
+#[derive(Default)]
+pub struct MockTransport {
+    responses: RefCell<HashMap<String, VecDeque<Result<CallOutcome>>>>,
+    requests: RefCell<Vec<RecordedRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue the JSON result of a method
+    pub fn respond<V: Serialize>(&self, method_name: &str, value: &V) -> &Self {
+        self.respond_with_logs(method_name, value, vec![])
+    }
+
+    /// Queue the JSON result of a call with the logs of its receipts
+    pub fn respond_with_logs<V: Serialize>(
+        &self,
+        method_name: &str,
+        value: &V,
+        logs: Vec<String>,
+    ) -> &Self {
+        let outcome = CallOutcome {
+            value: serde_json::to_vec(value).unwrap(),
+            logs,
+        };
+        self.push(method_name, Ok(outcome))
+    }
+
+    /// Queue a failure of a method, like a panic of the contract
+    pub fn fail(&self, method_name: &str, error: &str) -> &Self {
+        self.push(method_name, Err(Error::Execution(error.to_string())))
+    }
+
+    fn push(&self, method_name: &str, response: Result<CallOutcome>) -> &Self {
+        self.responses
+            .borrow_mut()
+            .entry(method_name.to_string())
+            .or_default()
+            .push_back(response);
+        self
+    }
+
+    fn pop(&self, method_name: &str) -> Option<Result<CallOutcome>> {
+        self.responses
+            .borrow_mut()
+            .get_mut(method_name)
+            .and_then(|responses| responses.pop_front())
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.borrow().clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn view(&self, contract_id: &AccountId, method_name: &str, args: &[u8]) -> Result<Vec<u8>> {
+        self.requests.borrow_mut().push(RecordedRequest::View {
+            contract_id: contract_id.clone(),
+            method_name: method_name.to_string(),
+            args: args.to_vec(),
+        });
+        match self.pop(method_name) {
+            Some(response) => response.map(|outcome| outcome.value),
+            None => Err(Error::Transport(format!(
+                "no mock response for {}",
+                method_name
+            ))),
+        }
+    }
+
+    fn call(&self, contract_id: &AccountId, request: &FunctionCallRequest) -> Result<CallOutcome> {
+        self.requests.borrow_mut().push(RecordedRequest::Call {
+            contract_id: contract_id.clone(),
+            request: request.clone(),
+        });
+        self.pop(&request.method_name)
+            .unwrap_or_else(|| Ok(CallOutcome::default()))
+    }
+
+    fn deploy(
+        &self,
+        contract_id: &AccountId,
+        code: &[u8],
+        init: Option<&FunctionCallRequest>,
+    ) -> Result<CallOutcome> {
+        self.requests.borrow_mut().push(RecordedRequest::Deploy {
+            contract_id: contract_id.clone(),
+            code: code.to_vec(),
+            init: init.cloned(),
+        });
+        match init {
+            Some(init) => self
+                .pop(&init.method_name)
+                .unwrap_or_else(|| Ok(CallOutcome::default())),
+            None => Ok(CallOutcome::default()),
+        }
+    }
+}
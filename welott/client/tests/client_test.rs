@@ -0,0 +1,240 @@
+use near_sdk::json_types::U128;
+use near_sdk::{AccountId, Gas};
+use welott_client::*;
+
+const ONE_NEAR: u128 = 10u128.pow(24);
+
+fn account(account_id: &str) -> AccountId {
+    account_id.parse().unwrap()
+}
+
+fn client(transport: &MockTransport) -> WelottClient<&MockTransport> {
+    WelottClient::new(account("welott.testnet"), transport)
+}
+
+#[test]
+fn test_view_lottery() {
+    // the contract returns the amounts of a lottery as JSON numbers, beyond u64
+    let lottery = Lottery {
+        lottery_id: 3,
+        amount_collected_in_near: 4_999 * ONE_NEAR / 1000,
+        rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
+        ..Default::default()
+    };
+    let transport = MockTransport::new();
+    transport.respond("view_lottery", &lottery);
+
+    let viewed = client(&transport).view_lottery(3).send().unwrap();
+    assert_eq!(viewed.lottery_id, 3);
+    assert_eq!(viewed.status, Status::Open);
+    assert_eq!(viewed.amount_collected_in_near, 4_999 * ONE_NEAR / 1000);
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method_name(), "view_lottery");
+    assert_eq!(requests[0].args_str(), r#"{"_lottery_id":3}"#);
+}
+
+#[test]
+fn test_view_user_tickets() {
+    let transport = MockTransport::new();
+    transport.respond(
+        "view_user_info_for_lottery_id",
+        &LotteryUserData {
+            final_number: 1567891,
+            lottery_id: 1,
+            ticket_numbers: vec![1567891, 1000891],
+            ticket_ids: vec![0, 2],
+            cursor: 2,
+        },
+    );
+    transport.respond(
+        "view_numbers_and_statuses_for_ticket_ids",
+        &LotteryNumberAndStatusData {
+            ticket_numbers: vec![1567891, 1000891],
+            ticket_status: vec![TicketStatus::Claimed, TicketStatus::Claimable],
+        },
+    );
+    let client = client(&transport);
+
+    let user_data = client
+        .view_user_info_for_lottery_id(account("eugene"), 1, None, Some(10))
+        .send()
+        .unwrap();
+    assert_eq!(user_data.ticket_ids, vec![0, 2]);
+    assert_eq!(user_data.final_number, 1567891);
+
+    let statuses = client
+        .view_numbers_and_statuses_for_ticket_ids(user_data.ticket_ids, 1)
+        .send()
+        .unwrap();
+    assert!(matches!(
+        statuses.ticket_status.as_slice(),
+        [TicketStatus::Claimed, TicketStatus::Claimable]
+    ));
+
+    let requests = transport.requests();
+    assert_eq!(
+        requests[0].args_str(),
+        r#"{"_user":"eugene","_lottery_id":1,"_cursor":null,"_size":10}"#
+    );
+    assert_eq!(
+        requests[1].args_str(),
+        r#"{"_ticket_ids":[0,2],"_lottery_id":1}"#
+    );
+}
+
+#[test]
+fn test_call_defaults() {
+    let transport = MockTransport::new();
+    let client = client(&transport);
+
+    let start = client.start_lottery();
+    assert_eq!(start.request().deposit, ONE_YOCTO);
+    assert_eq!(start.request().gas, DEFAULT_GAS);
+
+    let buy = client
+        .buy_tickets(1, vec![1234567, 1000001])
+        .deposit(2 * ONE_NEAR)
+        .gas(Gas(50_000_000_000_000));
+    assert_eq!(
+        buy.request(),
+        &FunctionCallRequest {
+            method_name: "buy_tickets".to_string(),
+            args: br#"{"_lottery_id":1,"_ticket_numbers":[1234567,1000001]}"#.to_vec(),
+            gas: Gas(50_000_000_000_000),
+            deposit: 2 * ONE_NEAR,
+        }
+    );
+    buy.send().unwrap();
+
+    match &transport.requests()[0] {
+        RecordedRequest::Call {
+            contract_id,
+            request,
+        } => {
+            assert_eq!(contract_id.as_str(), "welott.testnet");
+            assert_eq!(request.deposit, 2 * ONE_NEAR);
+        }
+        request => panic!("unexpected request {:?}", request),
+    }
+}
+
+#[test]
+fn test_call_result_and_logs() {
+    let transport = MockTransport::new();
+    transport.respond_with_logs(
+        "set_config",
+        &7u64,
+        vec![r#"EVENT_JSON:{"standard":"welott","version":"1.0.0","event":"queue_change","data":[]}"#
+            .to_string()],
+    );
+    let result = client(&transport)
+        // u128 arguments are sent as JSON numbers, like the contract expects them
+        .set_config(100, u128::MAX)
+        .send()
+        .unwrap();
+    assert_eq!(result.value, 7);
+    assert_eq!(result.logs.len(), 1);
+    assert_eq!(
+        transport.requests()[0].args_str(),
+        format!(
+            r#"{{"_max_number_tickets_per_buy":100,"_min_discount_divisor":{}}}"#,
+            u128::MAX
+        )
+    );
+}
+
+#[test]
+fn test_shared_request_types() {
+    let transport = MockTransport::new();
+    transport.respond("multisig_propose", &4u64);
+    let proposal_id = client(&transport)
+        .multisig_propose(MultisigAction::RecoverWrongTokens {
+            token_address: None,
+            token_amount: U128(ONE_NEAR),
+            receiver_id: account("bob"),
+        })
+        .send()
+        .unwrap()
+        .value;
+    assert_eq!(proposal_id, 4);
+    client(&transport).pause(PauseFlag::Buy).send().unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(
+        requests[0].args_str(),
+        r#"{"action":{"RecoverWrongTokens":{"token_address":null,"token_amount":"1000000000000000000000000","receiver_id":"bob"}}}"#
+    );
+    assert_eq!(requests[1].args_str(), r#"{"flag":"Buy"}"#);
+}
+
+#[test]
+fn test_errors() {
+    let transport = MockTransport::new();
+    transport.fail("claim_tickets", "E35: The operation is paused: claim");
+    let client = client(&transport);
+
+    match client.claim_tickets(1, vec![0], vec![5]).send() {
+        Err(Error::Execution(error)) => assert!(error.contains("E35")),
+        result => panic!("unexpected result {:?}", result.map(|result| result.logs)),
+    }
+    // a view without a response
+    assert!(matches!(
+        client.view_reserve_balance().send(),
+        Err(Error::Transport(_))
+    ));
+    // a response of the wrong type
+    transport.respond("view_timelock_delay", &"soon");
+    assert!(matches!(
+        client.view_timelock_delay().send(),
+        Err(Error::Json(_))
+    ));
+}
+
+#[test]
+fn test_deploy_and_init() {
+    let transport = MockTransport::new();
+    let config_lottery: ConfigLottery = serde_json::from_str(
+        r#"{
+            "time_run_lottery": 36000000000000,
+            "price_ticket_in_near": "250000000000000000000000",
+            "discount_divisor": "0",
+            "rewards_breakdown": [125, 375, 750, 1250, 2500, 5000],
+            "reserve_fee": "2200",
+            "operate_fee": "500",
+            "max_tickets_per_account": 120,
+            "max_tickets_per_lottery": 10000,
+            "min_jackpot": "0",
+            "fixed_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+            "max_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+            "overflow_policy": "RollOver"
+        }"#,
+    )
+    .unwrap();
+    let args = InitArgs {
+        owner_id: account("owner"),
+        injector_address: account("injector"),
+        operator_address: account("operator"),
+        treasury_address: account("treasury"),
+        config_lottery,
+    };
+    let client = client(&transport);
+    let deploy = client.deploy_and_init(b"\0asm".to_vec(), &args);
+    assert_eq!(deploy.init_request().unwrap().method_name, "new");
+    deploy.send().unwrap();
+
+    match &transport.requests()[0] {
+        RecordedRequest::Deploy {
+            code,
+            init: Some(init),
+            ..
+        } => {
+            assert_eq!(code, b"\0asm");
+            assert!(init.args_str().starts_with(
+                r#"{"owner_id":"owner","injector_address":"injector","operator_address":"operator","treasury_address":"treasury","config_lottery":{"time_run_lottery":36000000000000,"#
+            ));
+        }
+        request => panic!("unexpected request {:?}", request),
+    }
+}
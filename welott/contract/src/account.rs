@@ -22,17 +22,39 @@ pub struct Account {
     // keep track of user ticket ids for a given lotteryId
     #[serde(skip_serializing)]
     pub tickets: UnorderedMap<LotteryId, Vec<TicketId>>,
+
+    // keep track of the NEAR paid for the tickets of a given lotteryId
+    #[serde(skip_serializing)]
+    pub amounts_paid: UnorderedMap<LotteryId, Balance>,
+}
+
+/// An account saved before the amounts paid were recorded
+#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AccountV0001 {
+    pub account_id: AccountId,
+    #[serde(skip_serializing)]
+    pub tickets: UnorderedMap<LotteryId, Vec<TicketId>>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize)]
 #[serde(crate = "near_sdk::serde")]
 pub enum VAccount {
+    V0001(AccountV0001),
     Current(Account),
 }
 
 impl From<VAccount> for Account {
     fn from(v: VAccount) -> Self {
         match v {
+            VAccount::V0001(c) => Account {
+                amounts_paid: UnorderedMap::new(StorageKey::AccountAmountsPaid {
+                    account_id: c.account_id.clone(),
+                }),
+                account_id: c.account_id,
+                storage_tracker: Default::default(),
+                tickets: c.tickets,
+            },
             VAccount::Current(c) => c,
         }
     }
@@ -52,6 +74,9 @@ impl Account {
             tickets: UnorderedMap::new(StorageKey::AccountTickets {
                 account_id: account_id.clone(),
             }),
+            amounts_paid: UnorderedMap::new(StorageKey::AccountAmountsPaid {
+                account_id: account_id.clone(),
+            }),
         }
     }
 }
@@ -93,7 +118,7 @@ impl NearLott {
         storage.storage_tracker.start();
         data.accounts.insert(account_id, &account.into());
         storage.storage_tracker.stop();
-        internal_set_storage_data(data, account_id, storage);
+        data.internal_set_storage(account_id, storage);
     }
 }
 
@@ -145,4 +170,18 @@ impl Account {
         ticket_ids.push(ticket_id);
         self.tickets.insert(_lottery_id, &ticket_ids);
     }
+
+    pub fn internal_get_amount_paid_per_lottery(&self, _lottery_id: &LotteryId) -> Balance {
+        self.amounts_paid.get(_lottery_id).unwrap_or(0)
+    }
+
+    pub fn internal_add_amount_paid_per_lottery(
+        &mut self,
+        _lottery_id: &LotteryId,
+        amount: Balance,
+    ) {
+        let amount_paid = self.internal_get_amount_paid_per_lottery(_lottery_id);
+        self.amounts_paid
+            .insert(_lottery_id, &(amount_paid + amount));
+    }
 }
@@ -0,0 +1,171 @@
+use crate::*;
+use near_sdk::collections::UnorderedSet;
+
+/// Roles granted to accounts, each role can have multiple members
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Role {
+    Owner,
+    Operator,
+    Injector,
+    Pauser,
+    ConfigManager,
+    Keeper,
+}
+
+pub const ALL_ROLES: [Role; 6] = [
+    Role::Owner,
+    Role::Operator,
+    Role::Injector,
+    Role::Pauser,
+    Role::ConfigManager,
+    Role::Keeper,
+];
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::Owner => write!(f, "owner"),
+            Role::Operator => write!(f, "operator"),
+            Role::Injector => write!(f, "injector"),
+            Role::Pauser => write!(f, "pauser"),
+            Role::ConfigManager => write!(f, "config manager"),
+            Role::Keeper => write!(f, "keeper"),
+        }
+    }
+}
+
+impl NearLott {
+    pub fn internal_has_role(&self, role: &Role, account_id: &AccountId) -> bool {
+        self.data()
+            .acl
+            .get(role)
+            .map_or(false, |members| members.contains(account_id))
+    }
+
+    /// Returns true if the account was not a member of the role
+    pub fn internal_grant_role(&mut self, role: &Role, account_id: &AccountId) -> bool {
+        let data = self.data_mut();
+        let mut members = data
+            .acl
+            .get(role)
+            .unwrap_or_else(|| UnorderedSet::new(StorageKey::RoleMembers { role: role.clone() }));
+        let granted = members.insert(account_id);
+        data.acl.insert(role, &members);
+
+        if granted {
+            WelottEvent::RoleGranted(vec![RoleData {
+                role: role.clone(),
+                account_id: account_id.clone(),
+                sender: env::predecessor_account_id(),
+            }])
+            .emit();
+        }
+        granted
+    }
+
+    /// Returns true if the account was a member of the role
+    pub fn internal_revoke_role(&mut self, role: &Role, account_id: &AccountId) -> bool {
+        let data = self.data_mut();
+        let mut members = match data.acl.get(role) {
+            Some(members) => members,
+            None => return false,
+        };
+        if *role == Role::Owner {
+            assert!(
+                members.len() > 1 || !members.contains(account_id),
+                "{}",
+                ERR55_LAST_OWNER
+            );
+        }
+        let revoked = members.remove(account_id);
+        data.acl.insert(role, &members);
+
+        if revoked {
+            WelottEvent::RoleRevoked(vec![RoleData {
+                role: role.clone(),
+                account_id: account_id.clone(),
+                sender: env::predecessor_account_id(),
+            }])
+            .emit();
+        }
+        revoked
+    }
+
+    pub fn internal_role_members(&self, role: &Role) -> Vec<AccountId> {
+        self.data()
+            .acl
+            .get(role)
+            .map_or(vec![], |members| members.to_vec())
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Grant a role to an account
+     * @param role: role to grant
+     * @param account_id: account receiving the role
+     * @dev Callable by owner. The owner role is granted by a multisig proposal
+     */
+    #[payable]
+    pub fn grant_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+        assert!(
+            role != Role::Owner,
+            "{}",
+            ERR66_OWNER_CHANGE_REQUIRES_MULTISIG
+        );
+        self.internal_grant_role(&role, &account_id);
+    }
+
+    /**
+     * @notice Revoke a role from an account. The last owner can not be revoked
+     * @param role: role to revoke
+     * @param account_id: account losing the role
+     * @dev Callable by owner. The owner role is revoked by a multisig proposal
+     */
+    #[payable]
+    pub fn revoke_role(&mut self, role: Role, account_id: AccountId) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+        assert!(
+            role != Role::Owner,
+            "{}",
+            ERR66_OWNER_CHANGE_REQUIRES_MULTISIG
+        );
+        self.internal_revoke_role(&role, &account_id);
+    }
+
+    /**
+     * @notice Give up a role of the caller. The last owner can not renounce
+     * @param role: role to give up
+     * @dev Callable by a member of the role
+     */
+    #[payable]
+    pub fn renounce_role(&mut self, role: Role) {
+        self.assert_one_yoctor();
+        self.assert_any_role_calling(&[role.clone()]);
+        self.internal_revoke_role(&role, &env::predecessor_account_id());
+    }
+
+    /// Whether an account is a member of a role
+    pub fn has_role(&self, role: Role, account_id: AccountId) -> bool {
+        self.internal_has_role(&role, &account_id)
+    }
+
+    /// Get the members of a role
+    pub fn view_role_members(&self, role: Role) -> Vec<AccountId> {
+        self.internal_role_members(&role)
+    }
+
+    /// Get the roles of an account
+    pub fn view_account_roles(&self, account_id: AccountId) -> Vec<Role> {
+        ALL_ROLES
+            .iter()
+            .filter(|role| self.internal_has_role(role, &account_id))
+            .cloned()
+            .collect()
+    }
+}
@@ -1,40 +1,25 @@
 use crate::*;
 
 impl NearLott {
-    /// Assert that the method was called by the owner.
-    pub fn assert_owner_calling(&self) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.data().owner_id,
-            "Can only be called by the owner"
-        )
-    }
-
-    /// Assert that the method was called by operator
-    pub fn assert_operator_calling(&self) {
-        assert_eq!(
-            env::predecessor_account_id(),
-            self.data().operator_address,
-            "Can only be called by the operator"
-        )
-    }
-
-    /// only operator if is an owner of an operator
-    pub fn assert_operator_or_owner_calling(&self) {
+    /// Assert that the method was called by a member of one of the roles
+    pub fn assert_any_role_calling(&self, roles: &[Role]) {
+        let account_id = env::predecessor_account_id();
         assert!(
-            env::predecessor_account_id() == self.data().owner_id
-                || env::predecessor_account_id() == self.data().operator_address,
-            "Can only be called by the operator or the owner"
+            roles
+                .iter()
+                .any(|role| self.internal_has_role(role, &account_id)),
+            "Can only be called by {}",
+            roles
+                .iter()
+                .map(|role| format!("the {}", role))
+                .collect::<Vec<String>>()
+                .join(" or ")
         );
     }
 
-    /// only injector or an operator calling
-    pub fn assert_injector_or_owner_calling(&self) {
-        assert!(
-            env::predecessor_account_id() == self.data().owner_id
-                || env::predecessor_account_id() == self.data().injector_address,
-            "Can only be called by the injector or the owner"
-        );
+    /// Assert that the method was called by the owner.
+    pub fn assert_owner_calling(&self) {
+        self.assert_any_role_calling(&[Role::Owner]);
     }
 
     /// Assert that 1 yoctorNEAR was attached
@@ -46,13 +31,18 @@ impl NearLott {
         )
     }
 
-    /// Assert contract running
-    pub fn assert_contract_running(&self) {
-        let data = self.data();
-        match data.state {
-            RunningState::Running => (),
-            _ => env::panic_str(ERR35_CONTRACT_PAUSED),
-        };
+    /// Assert the operation is not paused.
+    /// In the emergency state, only claiming and withdrawing the storage can run
+    pub fn assert_not_paused(&self, flag: PauseFlag) {
+        if flag != PauseFlag::Claim && flag != PauseFlag::StorageWithdraw {
+            self.assert_not_emergency();
+        }
+        assert!(
+            !self.data().pause_flags.is_paused(&flag),
+            "{}: {}",
+            ERR35_CONTRACT_PAUSED,
+            flag
+        );
     }
 
     // Assert a lottery running
@@ -5,3 +5,13 @@ use near_sdk::ext_contract;
 pub trait FungibleTokenCore {
     fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
 }
+
+#[ext_contract(ext_self)]
+pub trait RecoverWrongTokensResolver {
+    fn on_recover_wrong_tokens(
+        &mut self,
+        token_address: Option<AccountId>,
+        token_amount: U128,
+        receiver_id: AccountId,
+    );
+}
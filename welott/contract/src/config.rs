@@ -6,23 +6,38 @@ const MINIMUM_PRICE_PER_TICKET: Balance = ONE_NEAR / 10; // 0.1 NEAR
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ConfigContractData {
-    pub owner_id: AccountId,
-    pub state: RunningState,
+    pub pause_flags: PauseFlags,
     pub current_lottery_id: LotteryId,
     pub current_ticket_id: TicketId,
 
-    pub injector_address: AccountId,
-    pub operator_address: AccountId,
     pub treasury_address: AccountId,
     pub max_number_tickets_per_buy_or_claim: u64,
 
     pub pending_injection_next_lottery: u128,
+    pub reserve_balance: u128,
 
     pub min_discount_divisor: u128,
     pub max_reserve_fee: u128,
+    pub fee_recipients: Vec<FeeRecipient>,
 }
 
-#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize)]
+/// A recipient of the operate fee, with its share in basis points (10,000 = 100%)
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FeeRecipient {
+    pub account_id: AccountId,
+    pub share: u128,
+}
+
+/// Where the excess of a capped bracket goes
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum OverflowPolicy {
+    CascadeToLowerBrackets,
+    RollOver,
+}
+
+#[derive(Serialize, Deserialize, BorshSerialize, BorshDeserialize, Clone, Debug)]
 #[serde(crate = "near_sdk::serde")]
 pub struct ConfigLottery {
     pub time_run_lottery: u64,
@@ -31,59 +46,121 @@ pub struct ConfigLottery {
     pub rewards_breakdown: Vec<u128>,
     pub reserve_fee: U128,
     pub operate_fee: U128,
+    pub max_tickets_per_account: u64,
+    pub max_tickets_per_lottery: u64,
+    pub min_jackpot: U128,
+    // fixed prize per winning ticket for each bracket, 0 if the bracket is paid by percentage
+    pub fixed_prize_per_bracket: Vec<U128>,
+    // maximum prize per winning ticket for each bracket, 0 if the bracket is not capped
+    pub max_prize_per_bracket: Vec<U128>,
+    pub overflow_policy: OverflowPolicy,
+}
+
+impl NearLott {
+    /// The share of the operate fee of an account goes to another one, merged if it is a recipient
+    /// already
+    pub fn internal_replace_fee_recipient(
+        &mut self,
+        previous_account_id: &AccountId,
+        account_id: &AccountId,
+    ) {
+        let data = self.data_mut();
+        let position = match data
+            .fee_recipients
+            .iter()
+            .position(|recipient| &recipient.account_id == previous_account_id)
+        {
+            Some(position) => position,
+            None => return,
+        };
+        let share = data.fee_recipients[position].share;
+        if let Some(recipient) = data
+            .fee_recipients
+            .iter_mut()
+            .find(|recipient| &recipient.account_id == account_id)
+        {
+            recipient.share += share;
+            data.fee_recipients.remove(position);
+        } else {
+            data.fee_recipients[position].account_id = account_id.clone();
+        }
+
+        WelottEvent::SetFeeRecipients(vec![SetFeeRecipientsData {
+            fee_recipients: &data.fee_recipients,
+        }])
+        .emit();
+    }
+
+    /// The operate and reserve fees are changed by a multisig proposal only
+    pub fn assert_same_fees(&self, config_lottery: &ConfigLottery) {
+        let current_config_lottery = &self.data().config_lottery;
+        assert!(
+            config_lottery.operate_fee == current_config_lottery.operate_fee
+                && config_lottery.reserve_fee == current_config_lottery.reserve_fee,
+            "{}",
+            ERR65_FEES_CHANGE_REQUIRES_MULTISIG
+        );
+    }
 }
 
 #[near_bindgen]
 impl NearLott {
     /**
-     * @notice Set operator, treasury, and injector addresses
-     * @dev Only callable by owner
-     * @param _operator_address: address of the operator
-     * @param _treasury_address: address of the treasury
-     * @param _injector_address: address of the injector
+     * @notice Queue new limits of buying tickets, applied by `execute_change` after the timelock delay
+     * @dev Only callable by config manager or owner. The treasury address is changed by `propose_role`
      * @param _max_number_tickets_per_buy: maximum number tickets per buy
-     * @param _min_discount_divisor: minimum number tickets per buy
+     * @param _min_discount_divisor: minimum discount divisor of the config lottery, at least the
+     * maximum number tickets per buy
+     * @return id of the queued change
      */
+    #[payable]
     pub fn set_config(
         &mut self,
-        _operator_address: AccountId,
-        _treasury_address: AccountId,
-        _injector_address: AccountId,
         _max_number_tickets_per_buy: u64,
         _min_discount_divisor: u128,
-    ) {
-        self.assert_owner_calling();
-        let mut data = self.data_mut();
-        data.operator_address = _operator_address;
-        data.treasury_address = _treasury_address;
-        data.injector_address = _injector_address;
-        data.max_number_tickets_per_buy_or_claim = _max_number_tickets_per_buy;
-        data.min_discount_divisor = _min_discount_divisor;
+    ) -> u64 {
+        self.assert_one_yoctor();
+        self.assert_any_role_calling(&[Role::ConfigManager, Role::Owner]);
+        self.assert_not_paused(PauseFlag::AdminConfig);
+        assert_valid_config(_max_number_tickets_per_buy, _min_discount_divisor);
+        self.internal_queue_change(ConfigChange::Config {
+            max_number_tickets_per_buy: _max_number_tickets_per_buy,
+            min_discount_divisor: U128(_min_discount_divisor),
+        })
     }
 
     /**
-     * @notice Set config for run a lottery
-     * @dev Only callable by owner
+     * @notice Propose the recipients of the operate fee, set once approved by the multisig
+     * @dev Only callable by a multisig signer
+     * @param _fee_recipients: distinct accounts with their shares, summing to 10,000 basis points
+     * @return id of the multisig proposal
      */
-    pub fn set_config_lottery(&mut self, _config_lottery: ConfigLottery) {
-        // only owner can call
-        self.assert_owner_calling();
-        assert!(
-            _config_lottery.time_run_lottery >= LIMIT_TIME_IN_LOTTERY,
-            "{}",
-            ERR45_MINIMUM_TIME_FOR_RUN_LOTTERY
-        );
-        assert!(
-            _config_lottery.price_ticket_in_near.0 >= MINIMUM_PRICE_PER_TICKET,
-            "{}",
-            ERR45_MINIMUM_TIME_FOR_RUN_LOTTERY
-        );
+    #[payable]
+    pub fn set_fee_recipients(&mut self, _fee_recipients: Vec<FeeRecipient>) -> u64 {
+        self.assert_one_yoctor();
+        assert_valid_fee_recipients(&_fee_recipients);
+        self.internal_multisig_propose(MultisigAction::SetFeeRecipients {
+            fee_recipients: _fee_recipients,
+        })
+    }
 
-        // get latest lotteryid
-        let data = self.data_mut();
+    /**
+     * @notice Queue a config for run a lottery, applied by `execute_change` after the timelock delay
+     * @dev Only callable by config manager or owner. A config changing the operate or reserve fee
+     * is proposed to the multisig with `multisig_propose`
+     * @return id of the queued change
+     */
+    pub fn set_config_lottery(&mut self, _config_lottery: ConfigLottery) -> u64 {
+        // only config manager or owner can call
+        self.assert_any_role_calling(&[Role::ConfigManager, Role::Owner]);
+        self.assert_not_paused(PauseFlag::AdminConfig);
+        self.assert_valid_config_lottery(&_config_lottery);
+        self.assert_same_fees(&_config_lottery);
 
-        // update
-        data.config_lottery = _config_lottery;
+        self.internal_queue_change(ConfigChange::ConfigLottery {
+            config_lottery: _config_lottery,
+            approval: ConfigApproval::ConfigManager,
+        })
     }
 
     /**
@@ -92,17 +169,105 @@ impl NearLott {
     pub fn _get_config(&self) -> ConfigContractData {
         let data = self.data();
         ConfigContractData {
-            owner_id: data.owner_id.clone(),
-            injector_address: data.injector_address.clone(),
-            operator_address: data.operator_address.clone(),
             treasury_address: data.treasury_address.clone(),
-            state: data.state.clone(),
+            pause_flags: data.pause_flags.clone(),
             current_lottery_id: data.current_lottery_id,
             current_ticket_id: data.current_ticket_id,
             max_number_tickets_per_buy_or_claim: data.max_number_tickets_per_buy_or_claim,
             pending_injection_next_lottery: data.pending_injection_next_lottery,
+            reserve_balance: data.reserve_balance,
             min_discount_divisor: data.min_discount_divisor,
             max_reserve_fee: data.max_reserve_fee,
+            fee_recipients: data.fee_recipients.clone(),
         }
     }
 }
+
+pub fn assert_valid_fee_recipients(fee_recipients: &[FeeRecipient]) {
+    let total_shares: u128 = fee_recipients.iter().map(|r| r.share).sum();
+    let mut accounts: Vec<&AccountId> = fee_recipients.iter().map(|r| &r.account_id).collect();
+    accounts.sort();
+    accounts.dedup();
+    assert!(
+        total_shares == 10000
+            && accounts.len() == fee_recipients.len()
+            && fee_recipients.iter().all(|r| r.share > 0),
+        "{}",
+        ERR51_INVALID_FEE_RECIPIENTS
+    );
+}
+
+pub fn assert_valid_config(max_number_tickets_per_buy: u64, min_discount_divisor: u128) {
+    assert!(
+        max_number_tickets_per_buy > 0,
+        "{}",
+        ERR74_INVALID_MAX_TICKETS_PER_BUY
+    );
+    // the bulk discount of a buy is only defined up to the divisor
+    assert!(
+        min_discount_divisor >= max_number_tickets_per_buy as u128,
+        "{}",
+        ERR75_MIN_DISCOUNT_DIVISOR_TOO_LOW
+    );
+}
+
+/// The first rule a config lottery breaks, none if it can run a lottery
+pub fn check_config_lottery(
+    config_lottery: &ConfigLottery,
+    max_reserve_fee: u128,
+    min_discount_divisor: u128,
+) -> Result<(), &'static str> {
+    if config_lottery.time_run_lottery < LIMIT_TIME_IN_LOTTERY {
+        return Err(ERR45_MINIMUM_TIME_FOR_RUN_LOTTERY);
+    }
+    if config_lottery.price_ticket_in_near.0 < MINIMUM_PRICE_PER_TICKET {
+        return Err(ERR71_PRICE_TICKET_TOO_LOW);
+    }
+    // a divisor of 0 gives no discount
+    if config_lottery.discount_divisor.0 != 0
+        && config_lottery.discount_divisor.0 < min_discount_divisor
+    {
+        return Err(ERR13_LOTTERY_DISCOUNT_DIVISOR_TOO_LOW);
+    }
+    if config_lottery.reserve_fee.0 > max_reserve_fee {
+        return Err(ERR15_LOTTERY_OVER_TREASURY_FEE);
+    }
+    let rewards_breakdown = &config_lottery.rewards_breakdown;
+    if rewards_breakdown.len() != 6 || rewards_breakdown.iter().sum::<u128>() != 10000 {
+        return Err(ERR14_LOTTERY_OVER_RANGE_REWARDS);
+    }
+    // a bracket is paid either by a fixed amount or by a percentage
+    let fixed_prize_per_bracket = &config_lottery.fixed_prize_per_bracket;
+    if fixed_prize_per_bracket.len() != 6
+        || (0..6).any(|j| fixed_prize_per_bracket[j].0 != 0 && rewards_breakdown[j] != 0)
+    {
+        return Err(ERR48_INVALID_FIXED_PRIZES);
+    }
+    // the guaranteed minimum jackpot must fit under the cap of the top bracket
+    let max_prize_per_bracket = &config_lottery.max_prize_per_bracket;
+    if max_prize_per_bracket.len() != 6
+        || max_prize_per_bracket[5].0 != 0
+            && config_lottery.min_jackpot.0 > max_prize_per_bracket[5].0
+    {
+        return Err(ERR49_INVALID_PRIZE_CAPS);
+    }
+    if config_lottery.max_tickets_per_account == 0
+        || config_lottery.max_tickets_per_account > config_lottery.max_tickets_per_lottery
+    {
+        return Err(ERR47_INVALID_TICKETS_CAP);
+    }
+    Ok(())
+}
+
+impl NearLott {
+    /// A config lottery is checked when it is queued, applied and used to start a lottery
+    pub fn assert_valid_config_lottery(&self, config_lottery: &ConfigLottery) {
+        let data = self.data();
+        check_config_lottery(
+            config_lottery,
+            data.max_reserve_fee,
+            data.min_discount_divisor,
+        )
+        .unwrap_or_else(|err| panic!("{}", err));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::*;
+use near_sdk::collections::LookupSet;
+
+/// Pot of a lottery frozen by the emergency shutdown, each player refunded pro-rata to the amount paid
+/// for the tickets
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FrozenPot {
+    pub lottery_id: LotteryId,
+    // the pot of the lottery with its share of the reserve and of the pending injection
+    pub pot: Balance,
+    pub amount_sold: Balance,
+    pub tickets_sold: u64,
+    pub refunded: Balance,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Emergency {
+    pub started_at: Timestamp,
+    pub frozen_pots: UnorderedMap<LotteryId, FrozenPot>,
+    // players refunded for a lottery
+    pub refunds: LookupSet<(LotteryId, AccountId)>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EmergencyView {
+    pub started_at: Timestamp,
+    pub frozen_pots: Vec<FrozenPot>,
+}
+
+impl NearLott {
+    /// Assert the emergency shutdown has not started
+    pub fn assert_not_emergency(&self) {
+        assert!(
+            self.data().emergency.is_none(),
+            "{}",
+            ERR68_EMERGENCY_SHUTDOWN
+        );
+    }
+
+    fn internal_unwrap_emergency(&self) -> &Emergency {
+        self.data()
+            .emergency
+            .as_ref()
+            .expect(ERR69_NOT_IN_EMERGENCY)
+    }
+
+    /// Refund of a player for a frozen lottery, 0 if already refunded
+    pub fn internal_emergency_refund(
+        &self,
+        lottery_id: &LotteryId,
+        account_id: &AccountId,
+    ) -> Balance {
+        let emergency = self.internal_unwrap_emergency();
+        let frozen_pot = match emergency.frozen_pots.get(lottery_id) {
+            Some(frozen_pot) => frozen_pot,
+            None => return 0,
+        };
+        if emergency
+            .refunds
+            .contains(&(*lottery_id, account_id.clone()))
+        {
+            return 0;
+        }
+        let amount_paid = self.internal_get_account(account_id).map_or(0, |account| {
+            account.internal_get_amount_paid_per_lottery(lottery_id)
+        });
+        (U256::from(amount_paid) * U256::from(frozen_pot.pot) / U256::from(frozen_pot.amount_sold))
+            .as_u128()
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Enter the terminal emergency state. The open and closed lotteries are frozen,
+     * their players are refunded the pots pro-rata to what they paid by `emergency_withdraw`.
+     * The reserve and the pending injection are shared between the pots pro-rata to their sales.
+     * The claimable prizes can still be claimed
+     * @dev Callable by owner
+     */
+    #[payable]
+    pub fn enter_emergency(&mut self) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+        self.assert_not_emergency();
+
+        let data = self.data_mut();
+        // every lottery not drawn yet, an older one may have been left behind
+        let lotteries: Vec<Lottery> = data
+            ._lotteries
+            .values()
+            .filter(|lottery| lottery.status == Status::Open || lottery.status == Status::Close)
+            .collect();
+
+        // the reserve and the pending injection are refunded with the pots, pro-rata to the sales
+        let released = data.reserve_balance + data.pending_injection_next_lottery;
+        data.reserve_balance = 0;
+        data.pending_injection_next_lottery = 0;
+        let total_sold: Balance = lotteries.iter().map(|l| l.amount_sold_in_near).sum();
+        let mut allocated = 0;
+        let mut amount_to_treasury = 0;
+
+        let mut frozen_pots = UnorderedMap::new(StorageKey::FrozenPots);
+        for lottery in lotteries {
+            // a pot without players goes to the treasury
+            if lottery.amount_sold_in_near == 0 {
+                data.ledger.open_pots -= lottery.amount_collected_in_near;
+                data.ledger.treasury_payable += lottery.amount_collected_in_near;
+                amount_to_treasury += lottery.amount_collected_in_near;
+                continue;
+            }
+            let share = (U256::from(released) * U256::from(lottery.amount_sold_in_near)
+                / U256::from(total_sold))
+            .as_u128();
+            data.ledger.open_pots += share;
+            allocated += share;
+            frozen_pots.insert(
+                &lottery.lottery_id,
+                &FrozenPot {
+                    lottery_id: lottery.lottery_id,
+                    pot: lottery.amount_collected_in_near + share,
+                    amount_sold: lottery.amount_sold_in_near,
+                    tickets_sold: (lottery.first_ticket_id_next_lottery - lottery.first_ticket_id)
+                        as u64,
+                    refunded: 0,
+                },
+            );
+        }
+        // the rounding, or all of it if nothing is sold
+        data.ledger.treasury_payable += released - allocated;
+        amount_to_treasury += released - allocated;
+
+        data.emergency = Some(Emergency {
+            started_at: env::block_timestamp(),
+            frozen_pots,
+            refunds: LookupSet::new(StorageKey::EmergencyRefunds),
+        });
+
+        WelottEvent::EnterEmergency(vec![EnterEmergencyData {
+            sender: env::predecessor_account_id(),
+            frozen_lottery_ids: data
+                .emergency
+                .as_ref()
+                .unwrap()
+                .frozen_pots
+                .keys_as_vector()
+                .to_vec(),
+            amount_to_treasury: U128(amount_to_treasury),
+            reserve_balance: U128(data.reserve_balance),
+        }])
+        .emit();
+    }
+
+    /**
+     * @notice Withdraw the share of a frozen pot, pro-rata to the amount paid for the tickets
+     * @param _lottery_id: lottery id
+     * @dev Callable by the players of the lottery, once
+     */
+    #[payable]
+    pub fn emergency_withdraw(&mut self, _lottery_id: LotteryId) -> U128 {
+        self.assert_one_yoctor();
+        let account_id = env::predecessor_account_id();
+        let amount = self.internal_emergency_refund(&_lottery_id, &account_id);
+        assert!(amount > 0, "{}", ERR50_NOTHING_TO_WITHDRAW);
+
+        let data = self.data_mut();
+        let emergency = data.emergency.as_mut().unwrap();
+        let mut frozen_pot = emergency.frozen_pots.get(&_lottery_id).unwrap();
+        frozen_pot.refunded += amount;
+        emergency.frozen_pots.insert(&_lottery_id, &frozen_pot);
+        emergency.refunds.insert(&(_lottery_id, account_id.clone()));
+        data.ledger.open_pots -= amount;
+        Promise::new(account_id.clone()).transfer(amount);
+
+        WelottEvent::EmergencyWithdraw(vec![WithdrawData {
+            account_id,
+            lottery_id: Some(_lottery_id),
+            amount: U128(amount),
+        }])
+        .emit();
+        U128(amount)
+    }
+
+    /**
+     * @notice Withdraw what remains after the refunds, the prizes, the storage deposits and the treasury payable
+     * @dev Callable by owner, after entering the emergency state
+     */
+    #[payable]
+    pub fn emergency_withdraw_surplus(&mut self) -> U128 {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+        self.internal_unwrap_emergency();
+
+        let amount = self.view_solvency().surplus.0;
+        assert!(amount > 0, "{}", ERR50_NOTHING_TO_WITHDRAW);
+        Promise::new(env::predecessor_account_id()).transfer(amount);
+
+        WelottEvent::EmergencyWithdrawSurplus(vec![WithdrawData {
+            account_id: env::predecessor_account_id(),
+            lottery_id: None,
+            amount: U128(amount),
+        }])
+        .emit();
+        U128(amount)
+    }
+
+    /// Get the emergency state with the frozen pots, if entered
+    pub fn view_emergency(&self) -> Option<EmergencyView> {
+        self.data()
+            .emergency
+            .as_ref()
+            .map(|emergency| EmergencyView {
+                started_at: emergency.started_at,
+                frozen_pots: emergency.frozen_pots.values().collect(),
+            })
+    }
+
+    /// Get the amount a player can withdraw from a frozen lottery
+    pub fn view_emergency_refund(&self, _lottery_id: LotteryId, account_id: AccountId) -> U128 {
+        U128(self.internal_emergency_refund(&_lottery_id, &account_id))
+    }
+}
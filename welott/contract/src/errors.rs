@@ -38,7 +38,7 @@ pub const ERR31_TICKET_NUMBER_RANGE: &str =
 pub const ERR32_INSUFFICIENT_STORAGE: &str = "E32: insufficient $NEAR storage deposit";
 pub const ERR33_INSUFFICIENT_MINIMUM_REQUIRES: &str = "E33: Requires minimum deposit";
 pub const ERR34_RANDOM_NUMBER_INVALID: &str = "E34: Invalid random number";
-pub const ERR35_CONTRACT_PAUSED: &str = "E35: contract paused";
+pub const ERR35_CONTRACT_PAUSED: &str = "E35: The operation is paused";
 pub const ERR36_STRING_NUMBER_INVALID: &str = "E36: Invalid string number";
 pub const ERR37_NOT_ENOUGH_RANDOM_NUMBERS: &str = "E37: Not enough random positions numbers";
 pub const ERR38_DISALLOW_UPDATE: &str =
@@ -48,8 +48,53 @@ pub const ERR40_CURRENTY_TIME_LESS_END_TIME: &str = "E40: Current time less than
 pub const ERR41_ALREADY_CLAIMED: &str = "E41: You're already claimed alll";
 pub const ERR42_ACCOUNT_NO_EXISTING: &str = "E42: Account is not existing in Welott";
 pub const ERR43_ACCOUNT_MAX_TICKETS_PER_A_LOTTERY: &str =
-    "E43: Maximum tickets of user per a lottery is 120";
+    "E43: Maximum tickets of user per a lottery reached";
 pub const ERR44_LIMIT_ELEMENT_PER_A_VIEW: &str =
     "E44: Maximum limit element for per a view is 50";
 pub const ERR45_MINIMUM_TIME_FOR_RUN_LOTTERY: &str =
     "E44: Minimum time for run lottery is 10 hours";
+pub const ERR46_LOTTERY_SOLD_OUT: &str = "E46: Lottery sold out";
+pub const ERR47_INVALID_TICKETS_CAP: &str =
+    "E47: Tickets cap per account must be > 0 and <= tickets cap per lottery";
+pub const ERR48_INVALID_FIXED_PRIZES: &str =
+    "E48: Fixed prizes must have 6 brackets, each without a rewards breakdown";
+pub const ERR49_INVALID_PRIZE_CAPS: &str =
+    "E49: Prize caps must have 6 brackets, and the top cap must cover the minimum jackpot";
+pub const ERR50_NOTHING_TO_WITHDRAW: &str = "E50: Nothing to withdraw";
+pub const ERR51_INVALID_FEE_RECIPIENTS: &str =
+    "E51: Fee recipients must be distinct with positive shares summing to 10000";
+pub const ERR52_NO_ROLE_PROPOSAL: &str = "E52: No pending proposal for this role";
+pub const ERR53_NOT_PROPOSED_ACCOUNT: &str = "E53: Only the proposed account can accept";
+pub const ERR54_ROLE_PROPOSAL_EXPIRED: &str = "E54: The proposal has expired";
+pub const ERR55_LAST_OWNER: &str = "E55: The last owner can not be removed";
+pub const ERR56_NOT_EXISTING_CHANGE: &str = "E56: The change does not exist";
+pub const ERR57_CHANGE_NOT_EFFECTIVE: &str = "E57: The change is not effective until";
+pub const ERR58_UPGRADE_NOT_QUEUED: &str =
+    "E58: The code is not queued for upgrade or its timelock delay has not passed";
+pub const ERR59_UPGRADE_CHANGE: &str =
+    "E59: An upgrade is executed by calling upgrade with the code";
+pub const ERR60_NOT_MULTISIG_SIGNER: &str = "E60: Can only be called by a multisig signer";
+pub const ERR61_NOT_EXISTING_MULTISIG_PROPOSAL: &str = "E61: The multisig proposal does not exist";
+pub const ERR62_INVALID_MULTISIG_SIGNERS: &str =
+    "E62: Multisig signers must be distinct, with a threshold > 0 and <= the number of signers";
+pub const ERR63_MULTISIG_PROPOSAL_NOT_PENDING: &str = "E63: The multisig proposal is not pending";
+pub const ERR64_MULTISIG_ALREADY_APPROVED: &str =
+    "E64: The multisig proposal is already approved by this signer";
+pub const ERR65_FEES_CHANGE_REQUIRES_MULTISIG: &str =
+    "E65: Changing the operate or reserve fee requires a multisig proposal";
+pub const ERR66_OWNER_CHANGE_REQUIRES_MULTISIG: &str =
+    "E66: Changing the owners requires a multisig proposal";
+pub const ERR67_NOT_GOVERNANCE: &str = "E67: Can only be called by the governance account";
+pub const ERR68_EMERGENCY_SHUTDOWN: &str = "E68: The contract is in emergency shutdown";
+pub const ERR69_NOT_IN_EMERGENCY: &str = "E69: The contract is not in emergency shutdown";
+pub const ERR70_RECOVER_MORE_THAN_EXCESS: &str =
+    "E70: Only the NEAR in excess of the ledger can be recovered";
+pub const ERR71_PRICE_TICKET_TOO_LOW: &str = "E71: Price ticket in near must be >= 0.1 NEAR";
+pub const ERR72_TIMELOCK_DELAY_TOO_SHORT: &str = "E72: Timelock delay must be >= 1 hour";
+pub const ERR73_NOT_AN_OWNER: &str = "E73: The outgoing account is not an owner";
+pub const ERR74_INVALID_MAX_TICKETS_PER_BUY: &str = "E74: Max number tickets per buy must be > 0";
+pub const ERR75_MIN_DISCOUNT_DIVISOR_TOO_LOW: &str =
+    "E75: Min discount divisor must be >= max number tickets per buy";
+pub const ERR76_MIGRATE_DURING_LOTTERY: &str =
+    "E76: The state can only be migrated between lotteries";
+pub const ERR77_STATE_NOT_MIGRATED: &str = "E77: The state is not migrated to the deployed code";
@@ -0,0 +1,338 @@
+//! Events following NEP-297, logged as `EVENT_JSON:{"standard":"welott","version":"1.0.0","event":...,"data":[...]}`
+use crate::*;
+use near_sdk::BlockHeight;
+
+pub const EVENT_STANDARD: &str = "welott";
+pub const EVENT_STANDARD_VERSION: &str = "1.0.0";
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StartLotteryData {
+    pub lottery_id: LotteryId,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub price_ticket_in_near: U128,
+    pub discount_divisor: U128,
+    pub rewards_breakdown: Vec<U128>,
+    pub reserve_fee: U128,
+    pub operate_fee: U128,
+    pub first_ticket_id: TicketId,
+    pub amount_collected_in_near: U128,
+    pub max_tickets_per_account: u64,
+    pub max_tickets_per_lottery: u64,
+    pub min_jackpot: U128,
+    pub fixed_prize_per_bracket: Vec<U128>,
+    pub max_prize_per_bracket: Vec<U128>,
+    pub overflow_policy: OverflowPolicy,
+    pub config_version: u64,
+    pub reserve_balance: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BuyTicketsData {
+    pub buyer: AccountId,
+    pub lottery_id: LotteryId,
+    pub ticket_numbers: Vec<TicketNumber>,
+    pub ticket_ids: Vec<TicketId>,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CloseLotteryData {
+    pub lottery_id: LotteryId,
+    pub first_ticket_id_next_lottery: TicketId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DrawLotteryData {
+    pub lottery_id: LotteryId,
+    pub final_number: u32,
+    pub amount_collected_in_near: U128,
+    pub rewards_breakdown: Vec<U128>,
+    pub near_per_bracket: Vec<U128>,
+    pub count_winners_per_bracket: Vec<U128>,
+    pub operate_fee: U128,
+    pub fee_recipients: Vec<AccountId>,
+    pub fee_shares: Vec<U128>,
+    pub reserve_fee: U128,
+    pub amount_to_share_to_winners: U128,
+    // owed to the treasury for the brackets without winners, 0 with the auto injection
+    pub amount_to_treasury: U128,
+    pub jackpot_top_up: U128,
+    pub fixed_prize_per_bracket: Vec<U128>,
+    pub fixed_prizes_from_pot: U128,
+    pub fixed_prizes_from_reserve: U128,
+    pub fixed_prizes_scale: U128,
+    pub max_prize_per_bracket: Vec<U128>,
+    pub overflow_policy: OverflowPolicy,
+    pub overflow_per_bracket: Vec<U128>,
+    pub overflow_received_per_bracket: Vec<U128>,
+    pub overflow_rolled_over: U128,
+    pub dust: U128,
+    pub reserve_balance: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ClaimTicketsData {
+    pub claimer: AccountId,
+    pub lottery_id: LotteryId,
+    pub ticket_ids: Vec<TicketId>,
+    pub brackets: Vec<BracketPosition>,
+    pub rewards: Vec<U128>,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct InjectFundsData {
+    pub injector: AccountId,
+    // none for the reserve
+    pub lottery_id: Option<LotteryId>,
+    pub amount: U128,
+    pub reserve_balance: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageData {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub storage_balance: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QueueChangeData<'a> {
+    pub change_id: u64,
+    pub change: &'a ConfigChange,
+    pub proposed_by: AccountId,
+    pub effective_at: Timestamp,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ChangeData {
+    pub change_id: u64,
+    pub sender: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ApplyConfigLotteryData {
+    pub version: u64,
+    pub applied_by: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SetFeeRecipientsData<'a> {
+    pub fee_recipients: &'a [FeeRecipient],
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PauseData {
+    pub sender: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleData {
+    pub role: Role,
+    pub account_id: AccountId,
+    pub sender: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleProposalData {
+    pub role: AddressRole,
+    pub account_id: AccountId,
+    pub expires_at: Option<Timestamp>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AcceptRoleData {
+    pub role: AddressRole,
+    // the owner or treasury address replaced, none for the operator and injector
+    pub previous_account_id: Option<AccountId>,
+    pub account_id: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawData {
+    pub account_id: AccountId,
+    // none outside of the frozen lotteries
+    pub lottery_id: Option<LotteryId>,
+    pub amount: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigProposeData<'a> {
+    pub proposal_id: u64,
+    pub action: &'a MultisigAction,
+    pub proposer: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigApproveData {
+    pub proposal_id: u64,
+    pub signer: AccountId,
+    pub approvals: u64,
+    pub threshold: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigProposalData {
+    pub proposal_id: u64,
+    pub sender: AccountId,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EnterEmergencyData {
+    pub sender: AccountId,
+    pub frozen_lottery_ids: Vec<LotteryId>,
+    // the pots without players, and the reserve and the pending injection if no ticket is sold
+    pub amount_to_treasury: U128,
+    pub reserve_balance: U128,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DrawFinalNumberData {
+    pub source: &'static str,
+    pub block_height: BlockHeight,
+    pub final_number: TicketNumber,
+    // the VRF: the random seed of the block and the digits selected from it
+    pub random_seed: Option<Vec<u8>>,
+    pub selected_numbers: Option<Vec<u8>>,
+    // the queue: the final numbers left once drawn
+    pub queued_numbers_left: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SetSignersData<'a> {
+    pub signers: &'a [AccountId],
+    pub threshold: u64,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SetGovernanceData {
+    // none once removed
+    pub governance_id: Option<AccountId>,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RecoverWrongTokensData {
+    // none for NEAR
+    pub token_address: Option<AccountId>,
+    pub token_amount: U128,
+    pub receiver_id: AccountId,
+    pub success: bool,
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum WelottEvent<'a> {
+    StartLottery(Vec<StartLotteryData>),
+    BuyTickets(Vec<BuyTicketsData>),
+    CloseLottery(Vec<CloseLotteryData>),
+    DrawLottery(Vec<DrawLotteryData>),
+    ClaimTickets(Vec<ClaimTicketsData>),
+    InjectFunds(Vec<InjectFundsData>),
+    InjectReserveFunds(Vec<InjectFundsData>),
+    StorageDeposit(Vec<StorageData>),
+    StorageWithdraw(Vec<StorageData>),
+    QueueChange(Vec<QueueChangeData<'a>>),
+    ExecuteChange(Vec<ChangeData>),
+    CancelChange(Vec<ChangeData>),
+    ApplyConfigLottery(Vec<ApplyConfigLotteryData>),
+    SetFeeRecipients(Vec<SetFeeRecipientsData<'a>>),
+    BuyPaused(Vec<PauseData>),
+    BuyResumed(Vec<PauseData>),
+    DrawPaused(Vec<PauseData>),
+    DrawResumed(Vec<PauseData>),
+    ClaimPaused(Vec<PauseData>),
+    ClaimResumed(Vec<PauseData>),
+    StorageWithdrawPaused(Vec<PauseData>),
+    StorageWithdrawResumed(Vec<PauseData>),
+    AdminConfigPaused(Vec<PauseData>),
+    AdminConfigResumed(Vec<PauseData>),
+    RoleGranted(Vec<RoleData>),
+    RoleRevoked(Vec<RoleData>),
+    ProposeRole(Vec<RoleProposalData>),
+    AcceptRole(Vec<AcceptRoleData>),
+    CancelRoleProposal(Vec<RoleProposalData>),
+    WithdrawTreasuryPayable(Vec<WithdrawData>),
+    MultisigPropose(Vec<MultisigProposeData<'a>>),
+    MultisigApprove(Vec<MultisigApproveData>),
+    MultisigExecute(Vec<MultisigProposalData>),
+    MultisigCancel(Vec<MultisigProposalData>),
+    EnterEmergency(Vec<EnterEmergencyData>),
+    EmergencyWithdraw(Vec<WithdrawData>),
+    EmergencyWithdrawSurplus(Vec<WithdrawData>),
+    RecoverWrongTokens(Vec<RecoverWrongTokensData>),
+    DrawFinalNumber(Vec<DrawFinalNumberData>),
+    SetSigners(Vec<SetSignersData<'a>>),
+    SetGovernance(Vec<SetGovernanceData>),
+}
+
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct EventLog<'a> {
+    standard: &'static str,
+    version: &'static str,
+    #[serde(flatten)]
+    event: &'a WelottEvent<'a>,
+}
+
+impl WelottEvent<'_> {
+    /// Event of pausing or resuming an operation, each flag has its own event
+    pub fn pause(flag: &PauseFlag, paused: bool, data: PauseData) -> WelottEvent<'static> {
+        let data = vec![data];
+        match (flag, paused) {
+            (PauseFlag::Buy, true) => WelottEvent::BuyPaused(data),
+            (PauseFlag::Buy, false) => WelottEvent::BuyResumed(data),
+            (PauseFlag::Draw, true) => WelottEvent::DrawPaused(data),
+            (PauseFlag::Draw, false) => WelottEvent::DrawResumed(data),
+            (PauseFlag::Claim, true) => WelottEvent::ClaimPaused(data),
+            (PauseFlag::Claim, false) => WelottEvent::ClaimResumed(data),
+            (PauseFlag::StorageWithdraw, true) => WelottEvent::StorageWithdrawPaused(data),
+            (PauseFlag::StorageWithdraw, false) => WelottEvent::StorageWithdrawResumed(data),
+            (PauseFlag::AdminConfig, true) => WelottEvent::AdminConfigPaused(data),
+            (PauseFlag::AdminConfig, false) => WelottEvent::AdminConfigResumed(data),
+        }
+    }
+
+    pub fn emit(&self) {
+        let log = EventLog {
+            standard: EVENT_STANDARD,
+            version: EVENT_STANDARD_VERSION,
+            event: self,
+        };
+        env::log_str(&format!(
+            "EVENT_JSON:{}",
+            near_sdk::serde_json::to_string(&log).unwrap()
+        ));
+    }
+}
+
+/// Amounts as U128 strings
+pub fn to_u128_vec(amounts: &[u128]) -> Vec<U128> {
+    amounts.iter().map(|&amount| U128(amount)).collect()
+}
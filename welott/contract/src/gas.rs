@@ -2,3 +2,6 @@ use near_sdk::Gas;
 
 /// Amount of gas for fungible token transfers, increased to 20T to support AS token contracts.
 pub const GAS_FOR_FT_TRANSFER: Gas = Gas(20_000_000_000_000);
+
+/// Amount of gas for the callback logging the result of a token recovery.
+pub const GAS_FOR_RESOLVE_RECOVER: Gas = Gas(10_000_000_000_000);
@@ -0,0 +1,105 @@
+use crate::*;
+
+/// A config lottery applied to this contract, the lotteries record the version they started with
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ConfigVersion {
+    pub version: u64,
+    pub config_lottery: ConfigLottery,
+    pub applied_by: AccountId,
+    pub applied_at: Timestamp,
+}
+
+impl NearLott {
+    /// Apply a config lottery and record it as a new version of the account deciding it
+    pub fn internal_apply_config_lottery(
+        &mut self,
+        config_lottery: ConfigLottery,
+        applied_by: AccountId,
+    ) -> u64 {
+        let data = self.data_mut();
+        let version = data.config_versions.len();
+        data.config_versions.push(&ConfigVersion {
+            version,
+            config_lottery: config_lottery.clone(),
+            applied_by: applied_by.clone(),
+            applied_at: env::block_timestamp(),
+        });
+        data.config_lottery = config_lottery;
+
+        WelottEvent::ApplyConfigLottery(vec![ApplyConfigLotteryData {
+            version,
+            applied_by,
+        }])
+        .emit();
+        version
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Propose the governance account to the multisig, set once approved
+     * @param governance_id: the DAO applying the config lottery, none to remove it
+     * @return id of the multisig proposal
+     * @dev Callable by a multisig signer
+     */
+    #[payable]
+    pub fn set_governance(&mut self, governance_id: Option<AccountId>) -> u64 {
+        self.assert_one_yoctor();
+        self.internal_multisig_propose(MultisigAction::SetGovernance { governance_id })
+    }
+
+    /**
+     * @notice Queue a config lottery voted by the DAO, applied by `execute_change` after the timelock delay
+     * @param _config_lottery: config for run a lottery
+     * @return id of the queued change
+     * @dev Only callable by the governance account, between lotteries. The change is executed
+     * between lotteries too
+     */
+    pub fn apply_governance_config(&mut self, _config_lottery: ConfigLottery) -> u64 {
+        assert_eq!(
+            Some(env::predecessor_account_id()),
+            self.data().governance_id,
+            "{}",
+            ERR67_NOT_GOVERNANCE
+        );
+        self.assert_not_paused(PauseFlag::AdminConfig);
+        self.assert_lottery_running();
+        self.assert_valid_config_lottery(&_config_lottery);
+
+        self.internal_queue_change(ConfigChange::ConfigLottery {
+            config_lottery: _config_lottery,
+            approval: ConfigApproval::Governance,
+        })
+    }
+
+    /// Get the governance account, if any
+    pub fn view_governance(&self) -> Option<AccountId> {
+        self.data().governance_id.clone()
+    }
+
+    /// Get the history of the config lottery
+    pub fn view_config_versions(
+        &self,
+        _cursor: Option<u64>,
+        _size: Option<u64>,
+    ) -> Vec<ConfigVersion> {
+        let config_versions = &self.data().config_versions;
+        config_versions
+            .iter()
+            .skip(_cursor.unwrap_or(0) as usize)
+            .take(_size.unwrap_or_else(|| config_versions.len()) as usize)
+            .collect()
+    }
+
+    /// Get the config a lottery started with
+    pub fn view_lottery_config_version(&self, _lottery_id: LotteryId) -> ConfigVersion {
+        let data = self.data();
+        let lottery = data
+            ._lotteries
+            .get(&_lottery_id)
+            .expect(ERR1_NOT_EXISTING_LOTTERY);
+        data.config_versions.get(lottery.config_version).unwrap()
+    }
+}
@@ -0,0 +1,160 @@
+use crate::*;
+use near_sdk::StorageUsage;
+
+/// Named buckets of the NEAR held by this contract.
+/// The reserve and the pending injection are kept in `ContractData`, the ledger view includes them.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Ledger {
+    /// NEAR collected by the lotteries which are not drawn yet
+    pub open_pots: Balance,
+
+    /// prizes drawn and not claimed yet
+    pub prize_liabilities: Balance,
+    pub prize_liabilities_per_lottery: LookupMap<LotteryId, Balance>,
+
+    /// NEAR deposited by the accounts to pay for their storage
+    pub storage_deposits: Balance,
+    /// bytes used by the accounts, paid by their storage deposits
+    pub storage_used_bytes: StorageUsage,
+
+    /// NEAR owed to the treasury address and not transferred yet
+    pub treasury_payable: Balance,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LedgerView {
+    pub open_pots: U128,
+    pub prize_liabilities: U128,
+    pub reserve: U128,
+    pub pending_injection: U128,
+    pub storage_deposits: U128,
+    pub treasury_payable: U128,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SolvencyView {
+    pub account_balance: U128,
+    // the storage not paid by the storage deposits
+    pub locked_storage: U128,
+    pub total_buckets: U128,
+    pub surplus: U128,
+    pub deficit: U128,
+    pub is_solvent: bool,
+}
+
+impl Default for Ledger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self {
+            open_pots: 0,
+            prize_liabilities: 0,
+            prize_liabilities_per_lottery: LookupMap::new(StorageKey::PrizeLiabilities),
+            storage_deposits: 0,
+            storage_used_bytes: 0,
+            treasury_payable: 0,
+        }
+    }
+
+    pub fn internal_add_prize_liabilities(&mut self, lottery_id: &LotteryId, amount: Balance) {
+        if amount == 0 {
+            return;
+        }
+        let liabilities = self
+            .prize_liabilities_per_lottery
+            .get(lottery_id)
+            .unwrap_or(0);
+        self.prize_liabilities_per_lottery
+            .insert(lottery_id, &(liabilities + amount));
+        self.prize_liabilities += amount;
+    }
+
+    pub fn internal_release_prize_liabilities(&mut self, lottery_id: &LotteryId, amount: Balance) {
+        let liabilities = self
+            .prize_liabilities_per_lottery
+            .get(lottery_id)
+            .unwrap_or(0);
+        assert!(liabilities >= amount, "Internal prize accounting bug");
+        if liabilities == amount {
+            self.prize_liabilities_per_lottery.remove(lottery_id);
+        } else {
+            self.prize_liabilities_per_lottery
+                .insert(lottery_id, &(liabilities - amount));
+        }
+        self.prize_liabilities -= amount;
+    }
+}
+
+impl NearLott {
+    pub fn internal_ledger_view(&self) -> LedgerView {
+        let data = self.data();
+        LedgerView {
+            open_pots: U128(data.ledger.open_pots),
+            prize_liabilities: U128(data.ledger.prize_liabilities),
+            reserve: U128(data.reserve_balance),
+            pending_injection: U128(data.pending_injection_next_lottery),
+            storage_deposits: U128(data.ledger.storage_deposits),
+            treasury_payable: U128(data.ledger.treasury_payable),
+        }
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice View the buckets of the NEAR held by this contract
+     */
+    pub fn view_ledger(&self) -> LedgerView {
+        self.internal_ledger_view()
+    }
+
+    /**
+     * @notice View the prizes of a lottery which are not claimed yet
+     * @param _lottery_id: lottery id
+     */
+    pub fn view_prize_liabilities(&self, _lottery_id: LotteryId) -> U128 {
+        U128(
+            self.data()
+                .ledger
+                .prize_liabilities_per_lottery
+                .get(&_lottery_id)
+                .unwrap_or(0),
+        )
+    }
+
+    /**
+     * @notice Compare the sum of the buckets against the account balance minus the locked storage
+     * @dev The storage of the accounts is paid by the storage deposits bucket, only the storage of the
+     * contract itself is locked
+     */
+    pub fn view_solvency(&self) -> SolvencyView {
+        let ledger = self.internal_ledger_view();
+        let total_buckets = ledger.open_pots.0
+            + ledger.prize_liabilities.0
+            + ledger.reserve.0
+            + ledger.pending_injection.0
+            + ledger.storage_deposits.0
+            + ledger.treasury_payable.0;
+
+        let account_balance = env::account_balance();
+        let data = self.data();
+        let contract_bytes = env::storage_usage().saturating_sub(data.ledger.storage_used_bytes);
+        let locked_storage = Balance::from(contract_bytes) * env::storage_byte_cost();
+        let available = account_balance.saturating_sub(locked_storage);
+
+        SolvencyView {
+            account_balance: U128(account_balance),
+            locked_storage: U128(locked_storage),
+            total_buckets: U128(total_buckets),
+            surplus: U128(available.saturating_sub(total_buckets)),
+            deficit: U128(total_buckets.saturating_sub(available)),
+            is_solvent: available >= total_buckets,
+        }
+    }
+}
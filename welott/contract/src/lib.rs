@@ -1,42 +1,62 @@
 use crate::info::DEFAULT_AUDITOR_ACCOUNT_ID;
 use crate::info::DEFAULT_WEB_APP_URL;
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LookupMap, UnorderedMap};
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet, Vector};
 use near_sdk::json_types::U128;
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    env, near_bindgen, serde_json::json, AccountId, Balance, BorshStorageKey, PanicOnDefault,
-    Promise, Timestamp, ONE_NEAR,
+    env, near_bindgen, AccountId, Balance, BorshStorageKey, PanicOnDefault, Promise, Timestamp,
+    ONE_NEAR,
 };
 use std::collections::HashMap;
 use std::fmt;
 
 pub use crate::account::*;
 pub use crate::account_btn_counting::*;
+pub use crate::acl::*;
 pub use crate::assert::*;
 pub use crate::callback::*;
 pub use crate::config::*;
+pub use crate::emergency::*;
 pub use crate::errors::*;
+pub use crate::events::*;
 pub use crate::gas::*;
+pub use crate::governance::*;
+pub use crate::ledger::*;
 pub use crate::logic::*;
+pub use crate::migration::*;
+pub use crate::multisig::*;
 pub use crate::owner::*;
+pub use crate::prize::*;
+pub use crate::randomness::*;
 pub use crate::storage::*;
 pub use crate::storage_tracker::*;
+pub use crate::timelock::*;
 pub use crate::utils::*;
 pub use crate::views::*;
 
 mod account;
 mod account_btn_counting;
+mod acl;
 mod assert;
 mod callback;
 mod config;
+mod emergency;
 mod errors;
+mod events;
 mod gas;
+mod governance;
 mod info;
+mod ledger;
 mod logic;
+mod migration;
+mod multisig;
 mod owner;
+mod prize;
+mod randomness;
 mod storage;
 mod storage_tracker;
+mod timelock;
 mod utils;
 mod views;
 
@@ -83,8 +103,23 @@ pub struct Lottery {
     pub first_ticket_id_next_lottery: u32,
     pub amount_collected_in_near: u128,
     pub last_pot_size: u128,
+    pub amount_sold_in_near: u128,
     pub final_number: u32,
     pub operate_fee: u128,
+    pub max_tickets_per_account: u64,
+    pub max_tickets_per_lottery: u64,
+    pub min_jackpot: u128,
+    pub jackpot_top_up: u128,
+    pub fixed_prize_per_bracket: Vec<u128>,
+    pub fixed_prizes_scale: u128,
+    pub fixed_prizes_from_reserve: u128,
+    pub max_prize_per_bracket: Vec<u128>,
+    pub overflow_policy: OverflowPolicy,
+    pub overflow_per_bracket: Vec<u128>,
+    pub overflow_received_per_bracket: Vec<u128>,
+    pub overflow_rolled_over: u128,
+    pub dust: u128,
+    pub config_version: u64,
 }
 
 impl Default for Lottery {
@@ -104,12 +139,40 @@ impl Default for Lottery {
             first_ticket_id_next_lottery: 0,
             amount_collected_in_near: 0,
             last_pot_size: 0,
+            amount_sold_in_near: 0,
             final_number: 0,
             operate_fee: 0,
+            max_tickets_per_account: 0,
+            max_tickets_per_lottery: 0,
+            min_jackpot: 0,
+            jackpot_top_up: 0,
+            fixed_prize_per_bracket: vec![],
+            fixed_prizes_scale: 10000,
+            fixed_prizes_from_reserve: 0,
+            max_prize_per_bracket: vec![],
+            overflow_policy: OverflowPolicy::RollOver,
+            overflow_per_bracket: vec![],
+            overflow_received_per_bracket: vec![],
+            overflow_rolled_over: 0,
+            dust: 0,
+            config_version: 0,
         }
     }
 }
 
+impl Lottery {
+    /// Highest bracket paying a prize that the ticket number matches, the bracket a winning
+    /// ticket is counted and claimed in
+    pub fn highest_bracket(&self, ticket_number: TicketNumber) -> Option<BracketPosition> {
+        (0..6).rev().find(|&bracket| {
+            (self.rewards_breakdown[bracket as usize] != 0
+                || self.fixed_prize_per_bracket[bracket as usize] != 0)
+                && ticket_number % 10u32.pow(bracket + 1)
+                    == self.final_number % 10u32.pow(bracket + 1)
+        })
+    }
+}
+
 #[derive(BorshStorageKey, BorshSerialize)]
 pub(crate) enum StorageKey {
     Accounts,
@@ -119,14 +182,92 @@ pub(crate) enum StorageKey {
     Storage,
     BracketTicketNumbers { lottery_id: LotteryId },
     AccountTickets { account_id: AccountId },
+    PrizeLiabilities,
+    RoleProposals,
+    Acl,
+    RoleMembers { role: Role },
+    PendingChanges,
+    MultisigProposals,
+    ConfigVersions,
+    FrozenPots,
+    EmergencyRefunds,
+    QueuedFinalNumbers,
+    AccountAmountsPaid { account_id: AccountId },
 }
 
+/// Operations which can be paused independently
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone)]
 #[serde(crate = "near_sdk::serde")]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
-pub enum RunningState {
-    Running,
-    Paused,
+pub enum PauseFlag {
+    Buy,
+    Draw,
+    Claim,
+    StorageWithdraw,
+    AdminConfig,
+}
+
+pub const ALL_PAUSE_FLAGS: [PauseFlag; 5] = [
+    PauseFlag::Buy,
+    PauseFlag::Draw,
+    PauseFlag::Claim,
+    PauseFlag::StorageWithdraw,
+    PauseFlag::AdminConfig,
+];
+
+impl fmt::Display for PauseFlag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PauseFlag::Buy => write!(f, "buy"),
+            PauseFlag::Draw => write!(f, "draw"),
+            PauseFlag::Claim => write!(f, "claim"),
+            PauseFlag::StorageWithdraw => write!(f, "storage_withdraw"),
+            PauseFlag::AdminConfig => write!(f, "admin_config"),
+        }
+    }
+}
+
+#[derive(
+    BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone, Default,
+)]
+#[serde(crate = "near_sdk::serde")]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub struct PauseFlags {
+    // buying tickets
+    pub buy: bool,
+    // starting, closing and drawing the lotteries
+    pub draw: bool,
+    // claiming the prizes
+    pub claim: bool,
+    pub storage_withdraw: bool,
+    // applying configuration changes
+    pub admin_config: bool,
+}
+
+impl PauseFlags {
+    pub fn is_paused(&self, flag: &PauseFlag) -> bool {
+        match flag {
+            PauseFlag::Buy => self.buy,
+            PauseFlag::Draw => self.draw,
+            PauseFlag::Claim => self.claim,
+            PauseFlag::StorageWithdraw => self.storage_withdraw,
+            PauseFlag::AdminConfig => self.admin_config,
+        }
+    }
+
+    /// Returns true if the flag changed
+    pub fn set(&mut self, flag: &PauseFlag, paused: bool) -> bool {
+        let value = match flag {
+            PauseFlag::Buy => &mut self.buy,
+            PauseFlag::Draw => &mut self.draw,
+            PauseFlag::Claim => &mut self.claim,
+            PauseFlag::StorageWithdraw => &mut self.storage_withdraw,
+            PauseFlag::AdminConfig => &mut self.admin_config,
+        };
+        let changed = *value != paused;
+        *value = paused;
+        changed
+    }
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Eq, PartialEq, Clone)]
@@ -139,16 +280,35 @@ pub enum PermissionUpdateState {
 
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct ContractData {
-    pub owner_id: AccountId,
-    pub state: RunningState,
+    pub pause_flags: PauseFlags,
     pub current_lottery_id: LotteryId,
     pub current_ticket_id: TicketId,
 
-    pub injector_address: AccountId,
-    pub operator_address: AccountId,
     pub treasury_address: AccountId,
     pub max_number_tickets_per_buy_or_claim: u64,
     pub pending_injection_next_lottery: u128,
+    // reserve kept aside to guarantee the minimum jackpot
+    pub reserve_balance: u128,
+    // buckets of the NEAR held by this contract
+    pub ledger: Ledger,
+    // recipients of the operate fee
+    pub fee_recipients: Vec<FeeRecipient>,
+    // pending proposals to change the owner, operator, injector or treasury address
+    pub role_proposals: LookupMap<AddressRole, RoleProposal>,
+    // members of each role
+    pub acl: LookupMap<Role, UnorderedSet<AccountId>>,
+    // configuration changes waiting for the timelock delay
+    pub pending_changes: UnorderedMap<u64, PendingChange>,
+    pub next_change_id: u64,
+    pub timelock_delay: u64,
+    // signers approving the sensitive actions
+    pub multisig: Multisig,
+    // the DAO applying the config lottery between lotteries
+    pub governance_id: Option<AccountId>,
+    // every config lottery applied, indexed by version
+    pub config_versions: Vector<ConfigVersion>,
+    // terminal state entered after a critical bug, if any
+    pub emergency: Option<Emergency>,
 
     pub min_discount_divisor: u128,
     pub max_reserve_fee: u128,
@@ -175,9 +335,11 @@ pub struct ContractData {
     pub storage: LookupMap<AccountId, VStorage>,
 }
 
+/// State of the contract, the V0001 state is rewritten by `migrate_state` once the code is deployed
 #[derive(BorshSerialize, BorshDeserialize)]
 pub enum VersionedContractData {
-    V0001(ContractData),
+    V0001(ContractDataV0001),
+    V0002(ContractData),
 }
 impl VersionedContractData {}
 
@@ -229,20 +391,36 @@ impl NearLott {
         brackets.insert(&4, &11111);
         brackets.insert(&5, &111111);
 
-        Self {
-            data: VersionedContractData::V0001(ContractData {
-                owner_id,
-                injector_address,
-                operator_address,
+        // the whole operate fee goes to the treasury until the recipients are set
+        let fee_recipients = vec![FeeRecipient {
+            account_id: treasury_address.clone(),
+            share: 10000,
+        }];
+
+        let mut contract = Self {
+            data: VersionedContractData::V0002(ContractData {
                 treasury_address,
-                state: RunningState::Running,
+                pause_flags: PauseFlags::default(),
                 current_lottery_id: 0,
                 current_ticket_id: 0,
                 max_number_tickets_per_buy_or_claim: 12,
                 pending_injection_next_lottery: 0,
+                reserve_balance: 0,
+                ledger: Ledger::new(),
+                fee_recipients,
+                role_proposals: LookupMap::new(StorageKey::RoleProposals),
+                acl: LookupMap::new(StorageKey::Acl),
+                pending_changes: UnorderedMap::new(StorageKey::PendingChanges),
+                next_change_id: 0,
+                timelock_delay: DEFAULT_TIMELOCK_DELAY,
+                // the owner approves alone until the signers are set
+                multisig: Multisig::new(vec![owner_id.clone()], 1),
+                governance_id: None,
+                config_versions: Vector::new(StorageKey::ConfigVersions),
+                emergency: None,
                 min_discount_divisor: 0,
                 max_reserve_fee: 3000, // 30%
-                config_lottery,
+                config_lottery: config_lottery.clone(),
                 _lotteries: UnorderedMap::new(StorageKey::Lotteries),
                 _tickets: UnorderedMap::new(StorageKey::Tickets),
                 _bracket_calculator: brackets,
@@ -258,20 +436,29 @@ impl NearLott {
             auditor_account_id: Some(AccountId::new_unchecked(String::from(
                 DEFAULT_AUDITOR_ACCOUNT_ID,
             ))),
-        }
+        };
+
+        // grant the initial roles
+        contract.internal_grant_role(&Role::Owner, &owner_id);
+        contract.internal_grant_role(&Role::Operator, &operator_address);
+        contract.internal_grant_role(&Role::Injector, &injector_address);
+        contract.internal_apply_config_lottery(config_lottery, env::predecessor_account_id());
+        contract
     }
 }
 
 impl NearLott {
     fn data(&self) -> &ContractData {
         match &self.data {
-            VersionedContractData::V0001(data) => data,
+            VersionedContractData::V0002(data) => data,
+            VersionedContractData::V0001(_) => env::panic_str(ERR77_STATE_NOT_MIGRATED),
         }
     }
 
     fn data_mut(&mut self) -> &mut ContractData {
         match &mut self.data {
-            VersionedContractData::V0001(data) => data,
+            VersionedContractData::V0002(data) => data,
+            VersionedContractData::V0001(_) => env::panic_str(ERR77_STATE_NOT_MIGRATED),
         }
     }
 }
@@ -282,8 +469,10 @@ mod tests {
     use crate::info::CONTRACT_NAME;
     use crate::info::CONTRACT_VERSION;
     use crate::info::DEVELOPERS_ACCOUNT_ID;
+    use near_sdk::json_types::Base58CryptoHash;
     use near_sdk::test_utils::{accounts, VMContextBuilder};
     use near_sdk::testing_env;
+    use near_sdk::{PromiseResult, RuntimeFeesConfig, VMConfig};
 
     fn get_context(predecessor_account_id: AccountId) -> VMContextBuilder {
         let mut builder = VMContextBuilder::new();
@@ -297,21 +486,33 @@ mod tests {
     fn set_config_lottery(is_default: bool) -> ConfigLottery {
         if is_default {
             ConfigLottery {
-                time_run_lottery: 12345678 as u64 + 1,
+                time_run_lottery: 36_000_000_000_000,
                 price_ticket_in_near: U128::from(0),
                 discount_divisor: U128::from(0),
                 rewards_breakdown: vec![],
                 reserve_fee: U128::from(0),
                 operate_fee: U128::from(0),
+                max_tickets_per_account: 120,
+                max_tickets_per_lottery: 10000,
+                min_jackpot: U128::from(0),
+                fixed_prize_per_bracket: vec![U128::from(0); 6],
+                max_prize_per_bracket: vec![U128::from(0); 6],
+                overflow_policy: OverflowPolicy::RollOver,
             }
         } else {
             ConfigLottery {
-                time_run_lottery: 12345678 as u64 + 1,
+                time_run_lottery: 36_000_000_000_000,
                 price_ticket_in_near: U128(1000000000000000000000000),
                 discount_divisor: U128(2000),
                 rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
                 reserve_fee: U128(2000),
                 operate_fee: U128(500),
+                max_tickets_per_account: 120,
+                max_tickets_per_lottery: 10000,
+                min_jackpot: U128::from(0),
+                fixed_prize_per_bracket: vec![U128::from(0); 6],
+                max_prize_per_bracket: vec![U128::from(0); 6],
+                overflow_policy: OverflowPolicy::RollOver,
             }
         }
     }
@@ -330,73 +531,988 @@ mod tests {
     }
 
     #[test]
-    fn test_new() {
-        let mut context = get_context(accounts(0));
-        testing_env!(context.build());
+    fn test_new() {
+        let mut context = get_context(accounts(0));
+        testing_env!(context.build());
+
+        let contract = NearLott::new(
+            accounts(0),
+            accounts(1),
+            accounts(2),
+            accounts(3),
+            set_config_lottery(true),
+        );
+        testing_env!(context.is_view(true).build());
+
+        let config = contract.get_config();
+        assert_eq!(contract.get_owner(), accounts(0));
+        assert!(contract.has_role(Role::Injector, accounts(1)));
+        assert!(contract.has_role(Role::Operator, accounts(2)));
+        assert_eq!(config.treasury_address, accounts(3));
+
+        assert_eq!(config.pause_flags, PauseFlags::default());
+        assert_eq!(config.current_lottery_id, 0);
+        assert_eq!(config.current_ticket_id, 0);
+        assert_eq!(config.max_number_tickets_per_buy_or_claim, 12);
+
+        assert_eq!(config.pending_injection_next_lottery, 0);
+        assert_eq!(config.reserve_balance, 0);
+        assert_eq!(config.min_discount_divisor, 0);
+        assert_eq!(config.max_reserve_fee, 3000);
+
+        let data = contract.data();
+        assert_eq!(data._lotteries.len(), 0);
+        assert_eq!(data._tickets.len(), 0);
+        assert_eq!(data.random_result, 0);
+        assert_eq!(data._bracket_tickets_number.len(), 0);
+
+        assert_eq!(data._bracket_calculator.get(&0), Some(1));
+        assert_eq!(data._bracket_calculator.get(&1), Some(11));
+        assert_eq!(data._bracket_calculator.get(&2), Some(111));
+        assert_eq!(data._bracket_calculator.get(&3), Some(1111));
+        assert_eq!(data._bracket_calculator.get(&4), Some(11111));
+        assert_eq!(data._bracket_calculator.get(&5), Some(111111));
+    }
+
+    #[test]
+    #[should_panic(expected = "E60: Can only be called by a multisig signer")]
+    fn test_propose_owner_invalid() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+
+        contract.propose_owner(accounts(1), accounts(0), None);
+    }
+
+    #[test]
+    fn test_propose_and_accept_owner() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+
+        contract.propose_owner(accounts(1), accounts(0), None);
+        // the owner does not change until the proposal is accepted
+        assert_eq!(contract.view_role_members(Role::Owner), vec![accounts(0)]);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.accept_owner();
+        assert_eq!(contract.view_role_members(Role::Owner), vec![accounts(1)]);
+        assert!(contract.view_role_proposal(AddressRole::Owner).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "E60: Can only be called by a multisig signer")]
+    fn test_accept_owner_replaces_signer() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.multisig_propose(MultisigAction::SetSigners {
+            signers: vec![accounts(0), accounts(4)],
+            threshold: 2,
+        });
+        let proposal_id = contract.propose_owner(accounts(1), accounts(0), None);
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        contract.multisig_approve(proposal_id);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.accept_owner();
+        let multisig = contract.view_multisig_signers();
+        assert_eq!(multisig.signers, vec![accounts(1), accounts(4)]);
+        assert_eq!(multisig.threshold, 2);
+
+        // the new owner proposes, the other signer approves and the action is executed
+        let fee_recipients = vec![FeeRecipient {
+            account_id: accounts(5),
+            share: 10000,
+        }];
+        let proposal_id = contract.set_fee_recipients(fee_recipients);
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        contract.multisig_approve(proposal_id);
+        assert_eq!(contract.data().fee_recipients[0].account_id, accounts(5));
+
+        // the previous owner can no longer sign
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.propose_owner(accounts(0), accounts(0), None);
+    }
+
+    #[test]
+    fn test_accept_owner_already_signer() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.multisig_propose(MultisigAction::SetSigners {
+            signers: vec![accounts(0), accounts(1)],
+            threshold: 2,
+        });
+        let proposal_id = contract.propose_owner(accounts(1), accounts(0), None);
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.multisig_approve(proposal_id);
+        contract.accept_owner();
+
+        // a single signer is left, the threshold follows
+        let multisig = contract.view_multisig_signers();
+        assert_eq!(multisig.signers, vec![accounts(1)]);
+        assert_eq!(multisig.threshold, 1);
+    }
+
+    #[test]
+    fn test_accept_owner_proposed_by_other_signer() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.multisig_propose(MultisigAction::SetSigners {
+            signers: vec![accounts(0), accounts(4)],
+            threshold: 2,
+        });
+
+        // a signer which is not an owner proposes to replace the owner
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        let proposal_id = contract.propose_owner(accounts(1), accounts(0), None);
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.multisig_approve(proposal_id);
+        assert_eq!(
+            contract
+                .view_role_proposal(AddressRole::Owner)
+                .unwrap()
+                .previous_account_id,
+            Some(accounts(0))
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.accept_owner();
+        assert_eq!(contract.view_role_members(Role::Owner), vec![accounts(1)]);
+        assert!(!contract.has_role(Role::Owner, accounts(4)));
+        let multisig = contract.view_multisig_signers();
+        assert_eq!(multisig.signers, vec![accounts(1), accounts(4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "E73: The outgoing account is not an owner")]
+    fn test_propose_owner_previous_not_owner() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.propose_owner(accounts(1), accounts(4), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "E53: Only the proposed account can accept")]
+    fn test_accept_owner_not_proposed() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.propose_owner(accounts(1), accounts(0), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.accept_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "E54: The proposal has expired")]
+    fn test_accept_owner_expired() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .block_timestamp(1000)
+            .build());
+        contract.propose_owner(accounts(1), accounts(0), Some(500));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .block_timestamp(1501)
+            .build());
+        contract.accept_owner();
+    }
+
+    #[test]
+    #[should_panic(expected = "E52: No pending proposal for this role")]
+    fn test_cancel_owner_proposal() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.propose_owner(accounts(1), accounts(0), None);
+        contract.cancel_owner_proposal();
+
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(1)
+            .build());
+        contract.accept_owner();
+    }
+
+    #[test]
+    fn test_propose_and_accept_treasury() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.propose_role(AddressRole::Treasury, accounts(4), None);
+        assert_eq!(
+            contract
+                .view_role_proposal(AddressRole::Treasury)
+                .unwrap()
+                .account_id,
+            accounts(4)
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        contract.accept_role(AddressRole::Treasury);
+        assert_eq!(contract.data().treasury_address, accounts(4));
+        // the roles are unchanged
+        assert!(contract.has_role(Role::Operator, accounts(2)));
+        assert!(contract.has_role(Role::Injector, accounts(1)));
+        // the operate fee goes to the new treasury
+        let fee_recipients = &contract.data().fee_recipients;
+        assert_eq!(fee_recipients.len(), 1);
+        assert_eq!(fee_recipients[0].account_id, accounts(4));
+        assert_eq!(fee_recipients[0].share, 10000);
+    }
+
+    #[test]
+    fn test_accept_treasury_already_fee_recipient() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.set_fee_recipients(vec![
+            FeeRecipient {
+                account_id: accounts(3),
+                share: 6000,
+            },
+            FeeRecipient {
+                account_id: accounts(4),
+                share: 3000,
+            },
+            FeeRecipient {
+                account_id: accounts(5),
+                share: 1000,
+            },
+        ]);
+        contract.propose_role(AddressRole::Treasury, accounts(4), None);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        contract.accept_role(AddressRole::Treasury);
+        // the share of the outgoing treasury is merged into the incoming one
+        let fee_recipients: Vec<(AccountId, u128)> = contract
+            .data()
+            .fee_recipients
+            .iter()
+            .map(|recipient| (recipient.account_id.clone(), recipient.share))
+            .collect();
+        assert_eq!(
+            fee_recipients,
+            vec![(accounts(4), 9000), (accounts(5), 1000)]
+        );
+    }
+
+    #[test]
+    fn test_grant_and_revoke_role() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.grant_role(Role::Keeper, accounts(4));
+        contract.grant_role(Role::Keeper, accounts(5));
+        assert_eq!(
+            contract.view_role_members(Role::Keeper),
+            vec![accounts(4), accounts(5)]
+        );
+        assert_eq!(
+            contract.view_account_roles(accounts(2)),
+            vec![Role::Operator]
+        );
+
+        contract.revoke_role(Role::Keeper, accounts(4));
+        assert!(!contract.has_role(Role::Keeper, accounts(4)));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(1)
+            .build());
+        contract.renounce_role(Role::Keeper);
+        assert!(contract.view_role_members(Role::Keeper).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Can only be called by the pauser or the owner")]
+    fn test_pause_contract_without_role() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.pause_contract();
+    }
+
+    #[test]
+    fn test_pause_flags() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.pause_contract();
+        contract.resume(PauseFlag::Claim);
+        assert!(near_sdk::test_utils::get_logs()
+            .iter()
+            .any(|log| log.contains(r#""event":"claim_resumed""#)));
+
+        // winners can still claim while the rest is paused
+        let pause_flags = contract.view_pause_flags();
+        assert!(pause_flags.buy && pause_flags.draw && pause_flags.storage_withdraw);
+        assert!(pause_flags.admin_config && !pause_flags.claim);
+        contract.assert_not_paused(PauseFlag::Claim);
+    }
+
+    #[test]
+    fn test_recover_wrong_tokens() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        let proposal_id = contract.recover_wrong_tokens(Some(accounts(5)), U128(100), accounts(4));
+        assert_eq!(
+            contract.view_multisig_proposal(proposal_id).unwrap().status,
+            MultisigProposalStatus::Executed
+        );
+
+        // the callback logs the result of the transfer
+        testing_env!(
+            context
+                .predecessor_account_id(accounts(0))
+                .current_account_id(accounts(0))
+                .attached_deposit(0)
+                .build(),
+            VMConfig::test(),
+            RuntimeFeesConfig::test(),
+            HashMap::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+        assert!(contract.on_recover_wrong_tokens(Some(accounts(5)), U128(100), accounts(4)));
+        assert!(near_sdk::test_utils::get_logs()[0].contains(r#""success":true"#));
+    }
+
+    #[test]
+    #[should_panic(expected = "E70: Only the NEAR in excess of the ledger can be recovered")]
+    fn test_recover_near_beyond_excess() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        buy_a_ticket(&mut context, &mut contract, accounts(2), 1, vec![1000000]);
+
+        // the pot is owed to the lottery
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .account_balance(10u128.pow(24))
+            .attached_deposit(1)
+            .build());
+        contract.recover_wrong_tokens(None, U128(10u128.pow(24)), accounts(4));
+    }
+
+    #[test]
+    fn test_emergency_withdraw_pro_rata() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        deposit_for_account(&mut context, &mut contract, accounts(4));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        buy_a_ticket(&mut context, &mut contract, accounts(2), 1, vec![1000000]);
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(4),
+            1,
+            vec![1000001, 1000002, 1000003],
+        );
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(ONE_NEAR)
+            .build());
+        contract.inject_funds(1);
+        contract.data_mut().reserve_balance = ONE_NEAR;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.enter_emergency();
+        let emergency = contract.view_emergency().unwrap();
+        assert_eq!(emergency.frozen_pots[0].tickets_sold, 4);
+
+        // the injection and the reserve are refunded with the sales, the bulk discount is kept
+        let paid = _calculate_total_price_for_bulk_tickets(2000, ONE_NEAR, 3);
+        assert!(paid < 3 * ONE_NEAR);
+        let sold = ONE_NEAR + paid;
+        let pot = sold + 2 * ONE_NEAR;
+        assert_eq!(emergency.frozen_pots[0].amount_sold, sold);
+        assert_eq!(emergency.frozen_pots[0].pot, pot);
+        let ledger = contract.view_ledger();
+        assert_eq!(ledger.treasury_payable.0, 0);
+        assert_eq!(ledger.reserve.0, 0);
+        assert_eq!(ledger.open_pots.0, pot);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        let pro_rata = |amount_paid: u128| {
+            (U256::from(amount_paid) * U256::from(pot) / U256::from(sold)).as_u128()
+        };
+        let refund = contract.emergency_withdraw(1);
+        assert_eq!(refund.0, pro_rata(paid));
+        assert_eq!(contract.view_emergency_refund(1, accounts(4)).0, 0);
+        assert_eq!(
+            contract.view_emergency_refund(1, accounts(2)).0,
+            pro_rata(ONE_NEAR)
+        );
+        assert_eq!(contract.view_ledger().open_pots.0, pot - refund.0);
+    }
+
+    #[test]
+    fn test_emergency_freezes_undrawn_lotteries() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        buy_a_ticket(&mut context, &mut contract, accounts(2), 1, vec![1000000]);
+
+        // an older lottery left open is frozen as well as the last one, not the one drawn between
+        for (lottery_id, status) in vec![(2, Status::Claimable), (3, Status::Close)] {
+            let mut lottery = contract.data()._lotteries.get(&1).unwrap();
+            lottery.lottery_id = lottery_id;
+            lottery.status = status;
+            contract.data_mut()._lotteries.insert(&lottery_id, &lottery);
+        }
+        contract.data_mut().current_lottery_id = 3;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.enter_emergency();
+        let mut frozen_lottery_ids: Vec<LotteryId> = contract
+            .view_emergency()
+            .unwrap()
+            .frozen_pots
+            .iter()
+            .map(|frozen_pot| frozen_pot.lottery_id)
+            .collect();
+        frozen_lottery_ids.sort_unstable();
+        assert_eq!(frozen_lottery_ids, vec![1, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "E68: The contract is in emergency shutdown")]
+    fn test_buy_tickets_in_emergency() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.enter_emergency();
+        buy_a_ticket(&mut context, &mut contract, accounts(2), 1, vec![1000000]);
+    }
+
+    #[test]
+    #[should_panic(expected = "E35: The operation is paused: buy")]
+    fn test_buy_tickets_paused() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.pause(PauseFlag::Buy);
+        buy_a_ticket(&mut context, &mut contract, accounts(2), 1, vec![1000000]);
+    }
+
+    #[test]
+    fn test_buy_tickets_event() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            1,
+            vec![1302877, 1292877],
+        );
+
+        let logs = near_sdk::test_utils::get_logs();
+        let buy_log = logs.last().unwrap();
+        assert!(buy_log.starts_with(
+            r#"EVENT_JSON:{"standard":"welott","version":"1.0.0","event":"buy_tickets","data":[{"#
+        ));
+        assert!(buy_log.contains(r#""ticket_numbers":[1302877,1292877]"#));
+        assert!(buy_log.contains(r#""ticket_ids":[0,1]"#));
+    }
+
+    #[test]
+    fn test_multisig_config_events() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.multisig_propose(MultisigAction::SetSigners {
+            signers: vec![accounts(0), accounts(4)],
+            threshold: 1,
+        });
+        contract.set_governance(Some(accounts(5)));
+
+        let logs = near_sdk::test_utils::get_logs();
+        let set_signers =
+            r#""event":"set_signers","data":[{"signers":["alice","eugene"],"threshold":1}]"#;
+        assert!(logs.iter().any(|log| log.contains(set_signers)));
+        let set_governance = r#""event":"set_governance","data":[{"governance_id":"fargo"}]"#;
+        assert!(logs.iter().any(|log| log.contains(set_governance)));
+    }
+
+    #[test]
+    #[should_panic(expected = "E55: The last owner can not be removed")]
+    fn test_renounce_last_owner() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.renounce_role(Role::Owner);
+    }
+
+    #[test]
+    fn test_timelocked_config_lottery() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .block_timestamp(1000)
+            .build());
+
+        // the owner is the only signer, the fee change is queued at once
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.operate_fee = U128(800);
+        contract.multisig_propose(MultisigAction::SetConfigLottery(config_lottery));
+
+        // the fee change is visible but not applied yet
+        let pending_changes = contract.view_pending_changes();
+        assert_eq!(pending_changes.len(), 1);
+        let change_id = pending_changes[0].change_id;
+        assert_eq!(
+            pending_changes[0].effective_at,
+            1000 + DEFAULT_TIMELOCK_DELAY
+        );
+        assert_eq!(contract.view_config_lottery().operate_fee, U128(500));
+
+        // anyone can execute the change after the delay
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .block_timestamp(1000 + DEFAULT_TIMELOCK_DELAY)
+            .build());
+        contract.execute_change(change_id);
+        assert_eq!(contract.view_config_lottery().operate_fee, U128(800));
+        assert!(contract.view_pending_changes().is_empty());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "E65: Changing the operate or reserve fee requires a multisig proposal"
+    )]
+    fn test_execute_config_lottery_reverting_fee() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .block_timestamp(1000)
+            .build());
+
+        // the config manager queues a config with the current fees
+        let manager_change_id = contract.set_config_lottery(set_config_lottery(false));
+        // the multisig changes the operate fee behind it
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.operate_fee = U128(800);
+        contract.multisig_propose(MultisigAction::SetConfigLottery(config_lottery));
+        let multisig_change_id = manager_change_id + 1;
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp(1000 + DEFAULT_TIMELOCK_DELAY)
+            .build());
+        contract.execute_change(multisig_change_id);
+        assert_eq!(contract.view_config_lottery().operate_fee, U128(800));
+        // the config of the manager would revert the fee
+        contract.execute_change(manager_change_id);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "E65: Changing the operate or reserve fee requires a multisig proposal"
+    )]
+    fn test_set_config_lottery_changing_fee() {
+        let (_context, mut contract) = setup_contract(set_config_lottery(false));
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.reserve_fee = U128(1000);
+        contract.set_config_lottery(config_lottery);
+    }
+
+    #[test]
+    #[should_panic(expected = "E71: Price ticket in near must be >= 0.1 NEAR")]
+    fn test_set_config_lottery_price_too_low() {
+        let (_context, mut contract) = setup_contract(set_config_lottery(false));
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.price_ticket_in_near = U128(ONE_NEAR / 100);
+        contract.set_config_lottery(config_lottery);
+    }
+
+    #[test]
+    #[should_panic(expected = "E14: Rewards must equal 10000")]
+    fn test_start_lottery_invalid_config() {
+        // the config of the deployment is only checked when a lottery starts
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.rewards_breakdown[5] = 4000;
+        let (mut context, mut contract) = setup_contract(config_lottery);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.start_lottery();
+    }
+
+    #[test]
+    #[should_panic(expected = "E57: The change is not effective until")]
+    fn test_execute_change_before_delay() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        let change_id = contract.set_config(100, 300);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .block_timestamp(DEFAULT_TIMELOCK_DELAY - 1)
+            .build());
+        contract.execute_change(change_id);
+    }
+
+    #[test]
+    fn test_set_config() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        let change_id = contract.set_config(100, 300);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp(DEFAULT_TIMELOCK_DELAY)
+            .build());
+        contract.execute_change(change_id);
+        let config = contract._get_config();
+        assert_eq!(config.max_number_tickets_per_buy_or_claim, 100);
+        assert_eq!(config.min_discount_divisor, 300);
+
+        // the divisor of a config lottery is at least the minimum, or 0 for no discount
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.discount_divisor = U128(200);
+        assert_eq!(
+            check_config_lottery(&config_lottery, config.max_reserve_fee, 300),
+            Err(ERR13_LOTTERY_DISCOUNT_DIVISOR_TOO_LOW)
+        );
+        config_lottery.discount_divisor = U128(0);
+        assert_eq!(
+            check_config_lottery(&config_lottery, config.max_reserve_fee, 300),
+            Ok(())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Requires attached deposit of exactly 1 yoctorNEAR")]
+    fn test_set_config_without_deposit() {
+        let (_, mut contract) = setup_contract(set_config_lottery(false));
+        contract.set_config(100, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "E74: Max number tickets per buy must be > 0")]
+    fn test_set_config_no_ticket_per_buy() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_config(0, 300);
+    }
+
+    #[test]
+    #[should_panic(expected = "E75: Min discount divisor must be >= max number tickets per buy")]
+    fn test_set_config_min_discount_divisor_too_low() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_config(100, 0);
+    }
+
+    #[test]
+    fn test_cancel_change() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        let change_id = contract.set_config(100, 300);
+        contract.cancel_change(change_id);
+        assert!(contract.view_pending_changes().is_empty());
+    }
+
+    #[test]
+    fn test_set_timelock_delay() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).block_timestamp(1000).build());
 
-        let contract = NearLott::new(
-            accounts(0),
-            accounts(1),
-            accounts(2),
-            accounts(3),
-            set_config_lottery(true),
+        // the owner is the only signer, the delay change is queued behind the current delay
+        let proposal_id = contract.set_timelock_delay(MIN_TIMELOCK_DELAY);
+        assert_eq!(
+            contract.view_multisig_proposal(proposal_id).unwrap().status,
+            MultisigProposalStatus::Executed
         );
-        testing_env!(context.is_view(true).build());
+        let pending_changes = contract.view_pending_changes();
+        assert_eq!(pending_changes.len(), 1);
+        assert_eq!(
+            pending_changes[0].effective_at,
+            1000 + DEFAULT_TIMELOCK_DELAY
+        );
+        assert_eq!(contract.view_timelock_delay(), DEFAULT_TIMELOCK_DELAY);
 
-        let config = contract.get_config();
-        assert_eq!(contract.get_owner(), accounts(0));
-        assert_eq!(config.injector_address, accounts(1));
-        assert_eq!(config.operator_address, accounts(2));
-        assert_eq!(config.treasury_address, accounts(3));
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp(1000 + DEFAULT_TIMELOCK_DELAY)
+            .build());
+        contract.execute_change(pending_changes[0].change_id);
+        assert_eq!(contract.view_timelock_delay(), MIN_TIMELOCK_DELAY);
+    }
 
-        assert_eq!(config.state, RunningState::Running);
-        assert_eq!(config.current_lottery_id, 0);
-        assert_eq!(config.current_ticket_id, 0);
-        assert_eq!(config.max_number_tickets_per_buy_or_claim, 12);
+    #[test]
+    fn test_upgrade_removed_from_queue_after_deploy() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        let code_hash = env::sha256(b"code");
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&code_hash);
+        contract.propose_upgrade(Base58CryptoHash::from(hash));
 
-        assert_eq!(config.pending_injection_next_lottery, 0);
-        assert_eq!(config.min_discount_divisor, 0);
-        assert_eq!(config.max_reserve_fee, 3000);
+        // the upgrade stays queued until the deploy succeeded
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp(DEFAULT_TIMELOCK_DELAY)
+            .build());
+        let change_id = contract.internal_find_upgrade(&code_hash);
+        assert_eq!(contract.view_pending_changes().len(), 1);
 
-        let data = contract.data();
-        assert_eq!(data._lotteries.len(), 0);
-        assert_eq!(data._tickets.len(), 0);
-        assert_eq!(data.random_result, 0);
-        assert_eq!(data._bracket_tickets_number.len(), 0);
+        testing_env!(context.predecessor_account_id(accounts(0)).build());
+        contract.on_upgrade(change_id, accounts(4));
+        assert!(contract.view_pending_changes().is_empty());
+    }
 
-        assert_eq!(data._bracket_calculator.get(&0), Some(1));
-        assert_eq!(data._bracket_calculator.get(&1), Some(11));
-        assert_eq!(data._bracket_calculator.get(&2), Some(111));
-        assert_eq!(data._bracket_calculator.get(&3), Some(1111));
-        assert_eq!(data._bracket_calculator.get(&4), Some(11111));
-        assert_eq!(data._bracket_calculator.get(&5), Some(111111));
+    #[test]
+    #[should_panic(
+        expected = "E58: The code is not queued for upgrade or its timelock delay has not passed"
+    )]
+    fn test_upgrade_cancelled_before_deploy() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.propose_upgrade(Base58CryptoHash::from([0u8; 32]));
+        let change_id = contract.view_pending_changes()[0].change_id;
+        contract.cancel_change(change_id);
+        contract.on_upgrade(change_id, accounts(0));
     }
 
     #[test]
-    #[should_panic(expected = "Can only be called by the owner")]
-    fn test_set_owner_invalid() {
-        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+    #[should_panic(expected = "E72: Timelock delay must be >= 1 hour")]
+    fn test_set_timelock_delay_too_short() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_timelock_delay(MIN_TIMELOCK_DELAY - 1);
+    }
 
+    #[test]
+    #[should_panic(expected = "E60")]
+    fn test_set_timelock_delay_not_signer() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
         testing_env!(context
-            .predecessor_account_id(accounts(1))
+            .predecessor_account_id(accounts(4))
             .attached_deposit(1)
             .build());
+        contract.set_timelock_delay(MIN_TIMELOCK_DELAY);
+    }
+
+    #[test]
+    fn test_multisig_two_of_three() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.multisig_propose(MultisigAction::SetSigners {
+            signers: vec![accounts(0), accounts(4), accounts(5)],
+            threshold: 2,
+        });
+        assert_eq!(contract.view_multisig_signers().threshold, 2);
+
+        // the proposal waits for a second signer
+        let fee_recipients = vec![FeeRecipient {
+            account_id: accounts(4),
+            share: 10000,
+        }];
+        let proposal_id = contract.set_fee_recipients(fee_recipients);
+        assert_eq!(contract.data().fee_recipients[0].account_id, accounts(3));
 
-        contract.set_owner(accounts(1));
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(1)
+            .block_timestamp(1000)
+            .build());
+        contract.multisig_approve(proposal_id);
+        assert_eq!(contract.data().fee_recipients[0].account_id, accounts(4));
+
+        // the approvals are kept in the history
+        let proposals = contract.view_multisig_proposals(None, None);
+        assert_eq!(proposals.len(), 2);
+        assert_eq!(proposals[1].status, MultisigProposalStatus::Executed);
+        assert_eq!(proposals[1].approvals, vec![accounts(0), accounts(5)]);
+        assert_eq!(proposals[1].executed_at, Some(1000));
     }
 
     #[test]
-    fn test_set_owner() {
-        let (mut context, mut contract) = setup_contract(set_config_lottery(true));
+    #[should_panic(expected = "E63: The multisig proposal is not pending")]
+    fn test_multisig_approve_cancelled() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.multisig_propose(MultisigAction::SetSigners {
+            signers: vec![accounts(0), accounts(4)],
+            threshold: 2,
+        });
+        let proposal_id = contract.propose_owner(accounts(5), accounts(0), None);
+        contract.multisig_cancel(proposal_id);
+        assert!(contract.view_role_proposal(AddressRole::Owner).is_none());
 
         testing_env!(context
-            .predecessor_account_id(accounts(0))
+            .predecessor_account_id(accounts(4))
             .attached_deposit(1)
             .build());
+        contract.multisig_approve(proposal_id);
+    }
 
-        contract.set_owner(accounts(1));
-        let data = contract.data();
-        assert_eq!(data.owner_id, accounts(1));
+    #[test]
+    fn test_governance_config_versions() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_governance(Some(accounts(4)));
+        assert_eq!(contract.view_governance(), Some(accounts(4)));
+
+        // the fee change of the DAO waits for the timelock delay, like the one of the multisig
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.operate_fee = U128(800);
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .block_timestamp(1000)
+            .build());
+        let change_id = contract.apply_governance_config(config_lottery);
+        assert_eq!(contract.view_config_lottery().operate_fee, U128(500));
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .block_timestamp(1000 + DEFAULT_TIMELOCK_DELAY)
+            .build());
+        contract.execute_change(change_id);
+        assert_eq!(contract.view_config_lottery().operate_fee, U128(800));
+
+        // the lottery records the config it started with
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        let config_version = contract.view_lottery_config_version(1);
+        assert_eq!(config_version.version, 1);
+        assert_eq!(config_version.applied_by, accounts(4));
+        assert_eq!(contract.view_config_versions(None, None).len(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "E38: The lottery is running")]
+    fn test_governance_config_during_lottery() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_governance(Some(accounts(4)));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .build());
+        let config_lottery = set_config_lottery(false);
+        contract.apply_governance_config(config_lottery);
+    }
+
+    #[test]
+    #[should_panic(expected = "E38: The lottery is running")]
+    fn test_governance_config_executed_during_lottery() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_governance(Some(accounts(4)));
+
+        // queued between lotteries, the config is not applied to the lottery started since
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(0)
+            .build());
+        let change_id = contract.apply_governance_config(set_config_lottery(false));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        testing_env!(context
+            .predecessor_account_id(accounts(5))
+            .attached_deposit(0)
+            .block_timestamp(env::block_timestamp() + DEFAULT_TIMELOCK_DELAY)
+            .build());
+        contract.execute_change(change_id);
     }
 
     #[test]
@@ -413,14 +1529,14 @@ mod tests {
             .predecessor_account_id(accounts(1))
             .attached_deposit(2)
             .build());
-        contract.assert_injector_or_owner_calling();
+        contract.assert_any_role_calling(&[Role::Injector, Role::Owner]);
 
         testing_env!(context
             .predecessor_account_id(accounts(2))
             .attached_deposit(1)
             .build());
-        contract.assert_operator_calling();
-        contract.assert_operator_or_owner_calling();
+        contract.assert_any_role_calling(&[Role::Operator, Role::Keeper]);
+        contract.assert_any_role_calling(&[Role::Operator, Role::Owner]);
 
         testing_env!(context
             .predecessor_account_id(accounts(1))
@@ -428,7 +1544,7 @@ mod tests {
             .build());
         contract.assert_one_yoctor();
 
-        contract.assert_contract_running();
+        contract.assert_not_paused(PauseFlag::Claim);
     }
 
     #[test]
@@ -543,6 +1659,237 @@ mod tests {
         contract.buy_tickets(current_lottery_id, ticket_number);
     }
 
+    #[test]
+    fn test_calculate_draw_fees() {
+        let lottery = Lottery {
+            amount_collected_in_near: 100 * ONE_NEAR,
+            last_pot_size: 20 * ONE_NEAR,
+            operate_fee: 500,
+            reserve_fee: 2000,
+            ..Default::default()
+        };
+        // the operate fee is not taken from the pot injected before the lottery
+        let (operate_fee, reserve_fee, amount_to_share_to_winners) = _calculate_draw_fees(&lottery);
+        assert_eq!(operate_fee, 4 * ONE_NEAR);
+        assert_eq!(reserve_fee, 96 * ONE_NEAR / 5);
+        assert_eq!(
+            amount_to_share_to_winners,
+            100 * ONE_NEAR - operate_fee - reserve_fee
+        );
+    }
+
+    #[test]
+    fn test_calculate_count_winners_per_bracket() {
+        let mut lottery = Lottery {
+            rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
+            fixed_prize_per_bracket: vec![0; 6],
+            ..Default::default()
+        };
+        // the tickets matching at least the digits of each bracket
+        let number_of_tickets = |bracket: BracketPosition| [10, 6, 3, 1, 0, 0][bracket as usize];
+        assert_eq!(
+            _calculate_count_winners_per_bracket(&lottery, number_of_tickets),
+            vec![4, 3, 2, 1, 0, 0]
+        );
+
+        // the winners of a bracket paying nothing still count in the bracket below
+        lottery.rewards_breakdown = vec![125, 375, 0, 2000, 2500, 5000];
+        assert_eq!(
+            _calculate_count_winners_per_bracket(&lottery, number_of_tickets),
+            vec![4, 5, 2, 1, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_calculate_prizes() {
+        let mut lottery = Lottery {
+            first_ticket_id_next_lottery: 10,
+            rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
+            near_per_bracket: vec![0; 6],
+            count_winners_per_bracket: vec![4, 3, 2, 1, 0, 0],
+            fixed_prize_per_bracket: vec![0; 6],
+            max_prize_per_bracket: vec![0; 6],
+            overflow_per_bracket: vec![0; 6],
+            overflow_received_per_bracket: vec![0; 6],
+            ..Default::default()
+        };
+        let draw_prizes = _calculate_prizes(&mut lottery, 10_000_000, 0);
+        assert_eq!(
+            lottery.near_per_bracket,
+            vec![31_250, 125_000, 375_000, 1_250_000, 0, 0]
+        );
+        // the brackets without winners go to the next lottery
+        assert_eq!(draw_prizes.amount_to_withdraw_to_next_lottery, 7_500_000);
+        assert_eq!(draw_prizes.jackpot_top_up, 0);
+        assert_eq!(lottery.dust, 0);
+    }
+
+    fn state_v0001(status: Status) -> NearLott {
+        let mut _lotteries = UnorderedMap::new(StorageKey::Lotteries);
+        _lotteries.insert(
+            &1,
+            &LotteryV0001 {
+                lottery_id: 1,
+                status,
+                start_time: 0,
+                end_time: 0,
+                price_ticket_in_near: ONE_NEAR,
+                discount_divisor: 0,
+                rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
+                reserve_fee: 2000,
+                near_per_bracket: vec![0, 0, 0, 0, 0, ONE_NEAR],
+                count_winners_per_bracket: vec![0, 0, 0, 0, 0, 2],
+                first_ticket_id: 0,
+                first_ticket_id_next_lottery: 4,
+                amount_collected_in_near: 5 * ONE_NEAR,
+                last_pot_size: ONE_NEAR,
+                final_number: 1123456,
+                operate_fee: 500,
+            },
+        );
+        let mut _accounts = UnorderedMap::new(StorageKey::Accounts);
+        _accounts.insert(
+            &accounts(1),
+            &VAccount::V0001(AccountV0001 {
+                account_id: accounts(1),
+                tickets: UnorderedMap::new(StorageKey::AccountTickets {
+                    account_id: accounts(1),
+                }),
+            }),
+        );
+        let mut storage = LookupMap::new(StorageKey::Storage);
+        storage.insert(
+            &accounts(1),
+            &VStorage::Current(Storage {
+                storage_balance: ONE_NEAR,
+                used_bytes: 100,
+                storage_tracker: Default::default(),
+            }),
+        );
+
+        NearLott {
+            data: VersionedContractData::V0001(ContractDataV0001 {
+                owner_id: accounts(0),
+                state: RunningState::Running,
+                current_lottery_id: 1,
+                current_ticket_id: 4,
+                injector_address: accounts(2),
+                operator_address: accounts(3),
+                treasury_address: accounts(4),
+                max_number_tickets_per_buy_or_claim: 12,
+                pending_injection_next_lottery: ONE_NEAR,
+                min_discount_divisor: 0,
+                max_reserve_fee: 3000,
+                config_lottery: ConfigLotteryV0001 {
+                    time_run_lottery: 36_000_000_000_000,
+                    price_ticket_in_near: U128(ONE_NEAR),
+                    discount_divisor: U128(0),
+                    rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
+                    reserve_fee: U128(2000),
+                    operate_fee: U128(500),
+                },
+                _lotteries,
+                _tickets: UnorderedMap::new(StorageKey::Tickets),
+                _bracket_tickets_number: UnorderedMap::new(StorageKey::BracketTicketNumbers {
+                    lottery_id: 0,
+                }),
+                _bracket_calculator: LookupMap::new(StorageKey::BracketCalculator),
+                random_result: 1123456,
+                permission_update: PermissionUpdateState::Allow,
+                accounts: _accounts,
+                storage,
+            }),
+            web_app_url: None,
+            auditor_account_id: None,
+        }
+    }
+
+    #[test]
+    fn test_migrate_state_v0001() {
+        testing_env!(get_context(accounts(0)).build());
+        env::state_write(&state_v0001(Status::Claimable));
+        let contract = NearLott::migrate_state();
+
+        assert!(contract.has_role(Role::Owner, accounts(0)));
+        assert!(contract.has_role(Role::Injector, accounts(2)));
+        assert!(contract.has_role(Role::Operator, accounts(3)));
+        assert_eq!(contract.data().treasury_address, accounts(4));
+        assert_eq!(contract.view_pause_flags(), PauseFlags::default());
+
+        let lottery = contract.data()._lotteries.get(&1).unwrap();
+        assert_eq!(lottery.final_number, 1123456);
+        assert_eq!(lottery.amount_sold_in_near, 4 * ONE_NEAR);
+        assert_eq!(lottery.max_tickets_per_account, 120);
+        assert_eq!(lottery.config_version, 0);
+        assert_eq!(contract.data()._lotteries.len(), 1);
+
+        let config_lottery = contract.view_config_lottery();
+        assert_eq!(config_lottery.price_ticket_in_near.0, ONE_NEAR);
+        assert_eq!(config_lottery.max_tickets_per_account, 120);
+
+        // the prizes drawn are liabilities, the storage deposits are tracked
+        assert_eq!(contract.view_prize_liabilities(1).0, 2 * ONE_NEAR);
+        let ledger = contract.view_ledger();
+        assert_eq!(ledger.storage_deposits.0, ONE_NEAR);
+        assert_eq!(ledger.pending_injection.0, ONE_NEAR);
+        assert_eq!(ledger.open_pots.0, 0);
+
+        // the accounts are read in the current layout
+        let account = contract.internal_unwrap_account(&accounts(1));
+        assert_eq!(account.internal_get_amount_paid_per_lottery(&1), 0);
+
+        // migrating again keeps the state
+        env::state_write(&contract);
+        let contract = NearLott::migrate_state();
+        assert_eq!(contract.view_config_versions(None, None).len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "E76: The state can only be migrated between lotteries")]
+    fn test_migrate_state_v0001_during_lottery() {
+        testing_env!(get_context(accounts(0)).build());
+        env::state_write(&state_v0001(Status::Open));
+        NearLott::migrate_state();
+    }
+
+    #[test]
+    fn test_highest_bracket() {
+        let lottery = Lottery {
+            final_number: 1123456,
+            rewards_breakdown: vec![0, 5000, 0, 0, 0, 5000],
+            fixed_prize_per_bracket: vec![0, 0, 0, ONE_NEAR, 0, 0],
+            ..Default::default()
+        };
+        // the brackets paying nothing are skipped
+        assert_eq!(lottery.highest_bracket(1123456), Some(5));
+        assert_eq!(lottery.highest_bracket(1923456), Some(3));
+        assert_eq!(lottery.highest_bracket(1999456), Some(1));
+        assert_eq!(lottery.highest_bracket(1999996), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "E29: Bracket must be higher")]
+    fn test_claim_below_a_bracket_paying_nothing() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        buy_a_ticket(&mut context, &mut contract, accounts(2), 1, vec![1123456]);
+
+        // the ticket is counted in the top bracket, the one above its claim pays nothing
+        let mut lottery = contract.data()._lotteries.get(&1).unwrap();
+        lottery.status = Status::Claimable;
+        lottery.final_number = 1123456;
+        lottery.rewards_breakdown = vec![0, 5000, 0, 0, 0, 5000];
+        lottery.near_per_bracket = vec![0, ONE_NEAR, 0, 0, 0, ONE_NEAR];
+        contract.data_mut()._lotteries.insert(&1, &lottery);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.claim_tickets(1, vec![0], vec![1]);
+    }
+
     #[test]
     fn test_desposit_buy_tickets() {
         let (mut context, mut contract) = setup_contract(set_config_lottery(false));
@@ -725,7 +2072,7 @@ mod tests {
             available_storage.available.0, available_storage.total.0
         );
         assert_eq!(available_storage.total.0, 100000000000000000000000);
-        assert_eq!(available_storage.available.0, 96720000000000000000000);
+        assert_eq!(available_storage.available.0, 96050000000000000000000);
     }
 
     #[test]
@@ -754,39 +2101,171 @@ mod tests {
         let lottery2 = data2._lotteries.get(&current_lottery_id).unwrap();
         assert_eq!(lottery2.amount_collected_in_near, 1000000000000000000000000);
 
-        // buy the second time
+        // buy the second time
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![1292877, 1292837],
+        );
+
+        let data3 = contract.data();
+        let lottery3 = data3._lotteries.get(&current_lottery_id).unwrap();
+        assert_eq!(lottery3.amount_collected_in_near, 2999000000000000000000000);
+
+        assert_eq!(
+            data3
+                ._bracket_tickets_number
+                .get(&current_lottery_id)
+                .unwrap()
+                .keys_as_vector()
+                .len(),
+            13
+        );
+
+        // number of ticket should be 3
+        let data_mut = contract.data_mut();
+        let buyer = internal_get_account_unwrap_by_contract_data(data_mut, &accounts(2));
+        let tickets = buyer
+            .internal_get_ticket_ids_per_lottery(&current_lottery_id)
+            .unwrap_or(vec![]);
+        assert_eq!(tickets.len(), 3);
+
+        // check current tickets  assert_eq!(
+        assert_eq!(data_mut.current_ticket_id, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "E43: Maximum tickets of user per a lottery reached: 2")]
+    fn test_buy_tickets_over_account_cap() {
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.max_tickets_per_account = 2;
+        let (mut context, mut contract) = setup_contract(config_lottery);
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![1039219, 1106409, 1192039],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "E46: Lottery sold out: 1 tickets left")]
+    fn test_buy_tickets_sold_out() {
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.max_tickets_per_account = 2;
+        config_lottery.max_tickets_per_lottery = 3;
+        let (mut context, mut contract) = setup_contract(config_lottery);
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        deposit_for_account(&mut context, &mut contract, accounts(3));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![1039219, 1106409],
+        );
+        assert_eq!(
+            contract.view_remaining_tickets_per_account(accounts(2), current_lottery_id),
+            0
+        );
+        assert_eq!(
+            contract.view_remaining_tickets_per_account(accounts(3), current_lottery_id),
+            1
+        );
+        assert_eq!(
+            contract.view_remaining_tickets_per_lottery(current_lottery_id),
+            1
+        );
+
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(3),
+            current_lottery_id,
+            vec![1192039, 1000699],
+        );
+    }
+
+    // the final number which will be drawn right after `close_lottery`
+    fn next_final_number(context: &mut VMContextBuilder) -> TicketNumber {
+        testing_env!(context
+            .random_seed([
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 1, 2, 4, 5, 6, 7, 8, 9, 1, 2, 3, 3, 4, 5, 6, 6, 7, 8, 9,
+                1, 2, 4, 5
+            ])
+            .build());
+        get_random_number()
+    }
+
+    // a ticket matching only the last digit of the final number
+    fn ticket_number_in_first_bracket(final_number: TicketNumber) -> TicketNumber {
+        1000000 + final_number % 10 + 10 * ((final_number / 10 % 10 + 1) % 10)
+    }
+
+    #[test]
+    fn test_reserve_without_auto_injection() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![1039219],
+        );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, false);
+
+        // 1 NEAR - 5% operate fee, then 20% reserve fee
+        assert_eq!(contract.data().pending_injection_next_lottery, 0);
+        assert_eq!(contract.view_reserve_balance().0, 190000000000000000000000);
+    }
+
+    #[test]
+    fn test_guaranteed_min_jackpot() {
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.min_jackpot = U128(10 * ONE_NEAR);
+        let (mut context, mut contract) = setup_contract(config_lottery);
+        contract.data_mut().reserve_balance = 20 * ONE_NEAR;
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let current_lottery_id = contract.data().current_lottery_id;
+        let final_number = next_final_number(&mut context);
         buy_a_ticket(
             &mut context,
             &mut contract,
             accounts(2),
             current_lottery_id,
-            vec![1292877, 1292837],
+            vec![final_number],
         );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, true);
 
-        let data3 = contract.data();
-        let lottery3 = data3._lotteries.get(&current_lottery_id).unwrap();
-        assert_eq!(lottery3.amount_collected_in_near, 2999000000000000000000000);
-
+        // 5000 / 10000 of (1 NEAR - 5% operate fee - 20% reserve fee)
+        let top_bracket_pot = 380000000000000000000000;
+        let lottery = contract.view_lottery(current_lottery_id);
+        assert_eq!(lottery.final_number, final_number);
+        assert_eq!(lottery.count_winners_per_bracket[5], 1);
+        assert_eq!(lottery.near_per_bracket[5], 10 * ONE_NEAR);
+        assert_eq!(lottery.jackpot_top_up, 10 * ONE_NEAR - top_bracket_pot);
         assert_eq!(
-            data3
-                ._bracket_tickets_number
-                .get(&current_lottery_id)
-                .unwrap()
-                .keys_as_vector()
-                .len(),
-            13
+            contract.view_reserve_balance().0,
+            10 * ONE_NEAR + top_bracket_pot
         );
-
-        // number of ticket should be 3
-        let data_mut = contract.data_mut();
-        let buyer = internal_get_account_unwrap_by_contract_data(data_mut, &accounts(2));
-        let tickets = buyer
-            .internal_get_ticket_ids_per_lottery(&current_lottery_id)
-            .unwrap_or(vec![]);
-        assert_eq!(tickets.len(), 3);
-
-        // check current tickets  assert_eq!(
-        assert_eq!(data_mut.current_ticket_id, 3);
     }
 
     #[test]
@@ -974,6 +2453,334 @@ mod tests {
         assert_eq!(final_price2, 11946000000000000000); //~11.946 NEAR, 0.45% Bulk discount
     }
 
+    #[test]
+    fn test_calculate_fixed_prizes() {
+        let fixed_prizes = vec![ONE_NEAR, 2 * ONE_NEAR, 0, 0, 0, 0];
+        let winners = vec![3, 1, 0, 0, 0, 1];
+
+        // enough to pay in full
+        let (prizes, scale) = _calculate_fixed_prizes(&fixed_prizes, &winners, 10 * ONE_NEAR);
+        assert_eq!(prizes, fixed_prizes);
+        assert_eq!(scale, 10000);
+
+        // 5 NEAR are owed, only 4 NEAR available
+        let (prizes, scale) = _calculate_fixed_prizes(&fixed_prizes, &winners, 4 * ONE_NEAR);
+        assert_eq!(
+            prizes,
+            vec![ONE_NEAR * 4 / 5, 2 * ONE_NEAR * 4 / 5, 0, 0, 0, 0]
+        );
+        assert_eq!(scale, 8000);
+    }
+
+    #[test]
+    fn test_calculate_fee_shares() {
+        let fee_recipients = vec![
+            FeeRecipient {
+                account_id: accounts(3),
+                share: 5000,
+            },
+            FeeRecipient {
+                account_id: accounts(4),
+                share: 3333,
+            },
+            FeeRecipient {
+                account_id: accounts(5),
+                share: 1667,
+            },
+        ];
+
+        let shares = _calculate_fee_shares(1000, &fee_recipients);
+        // the rounding remainder goes to the first recipient
+        assert_eq!(shares, vec![501, 333, 166]);
+        assert_eq!(shares.iter().sum::<u128>(), 1000);
+    }
+
+    #[test]
+    #[should_panic(expected = "E51: Fee recipients must be distinct")]
+    fn test_set_fee_recipients_not_summing_to_10000() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_fee_recipients(vec![
+            FeeRecipient {
+                account_id: accounts(3),
+                share: 5000,
+            },
+            FeeRecipient {
+                account_id: accounts(4),
+                share: 4000,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_split_operate_fee() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        assert_eq!(contract.data().fee_recipients[0].account_id, accounts(3));
+        testing_env!(context.attached_deposit(1).build());
+        contract.set_fee_recipients(vec![
+            FeeRecipient {
+                account_id: accounts(3),
+                share: 7000,
+            },
+            FeeRecipient {
+                account_id: accounts(4),
+                share: 3000,
+            },
+        ]);
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![1039219],
+        );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, true);
+
+        // 5% of 1 NEAR
+        let logs = near_sdk::test_utils::get_logs();
+        let draw_log = logs.last().unwrap();
+        assert!(draw_log.contains(r#""fee_recipients":["danny","eugene"]"#));
+        assert!(draw_log
+            .contains(r#""fee_shares":["35000000000000000000000","15000000000000000000000"]"#));
+    }
+
+    #[test]
+    fn test_fixed_prizes_from_reserve() {
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.rewards_breakdown = vec![0, 500, 750, 1250, 2500, 5000];
+        config_lottery.fixed_prize_per_bracket[0] = U128(10 * ONE_NEAR);
+        let (mut context, mut contract) = setup_contract(config_lottery);
+        contract.data_mut().reserve_balance = ONE_NEAR;
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let final_number = next_final_number(&mut context);
+        let ticket_number = ticket_number_in_first_bracket(final_number);
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![ticket_number],
+        );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, true);
+
+        // the whole pot of 0.76 NEAR and the reserve are not enough to pay 10 NEAR
+        let amount_to_share = 760000000000000000000000;
+        let lottery = contract.view_lottery(current_lottery_id);
+        assert_eq!(lottery.count_winners_per_bracket[0], 1);
+        assert_eq!(lottery.near_per_bracket[0], amount_to_share + ONE_NEAR);
+        assert_eq!(lottery.fixed_prizes_from_reserve, ONE_NEAR);
+        assert_eq!(lottery.fixed_prizes_scale, 1760);
+        assert_eq!(contract.view_reserve_balance().0, 0);
+        // nothing is left for the percentage brackets
+        assert_eq!(
+            contract.data().pending_injection_next_lottery,
+            190000000000000000000000
+        );
+    }
+
+    fn draw_with_capped_top_bracket(overflow_policy: OverflowPolicy) -> Lottery {
+        let mut config_lottery = set_config_lottery(false);
+        config_lottery.max_prize_per_bracket[5] = U128(ONE_NEAR / 10);
+        config_lottery.overflow_policy = overflow_policy;
+        let (mut context, mut contract) = setup_contract(config_lottery);
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let final_number = next_final_number(&mut context);
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![final_number, ticket_number_in_first_bracket(final_number)],
+        );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, true);
+        contract.view_lottery(current_lottery_id)
+    }
+
+    #[test]
+    fn test_prize_cap_cascade_to_lower_brackets() {
+        let lottery = draw_with_capped_top_bracket(OverflowPolicy::CascadeToLowerBrackets);
+
+        // 1.999 NEAR - 5% operate fee, then 20% reserve fee
+        let amount_to_share: u128 = 1519240000000000000000000;
+        let overflow = amount_to_share * 5000 / 10000 - ONE_NEAR / 10;
+        assert_eq!(lottery.near_per_bracket[5], ONE_NEAR / 10);
+        assert_eq!(lottery.overflow_per_bracket[5], overflow);
+        // brackets 4 to 1 have no winners, the first bracket receives the whole overflow
+        assert_eq!(lottery.overflow_received_per_bracket[0], overflow);
+        assert_eq!(
+            lottery.near_per_bracket[0],
+            amount_to_share * 125 / 10000 + overflow
+        );
+        assert_eq!(lottery.overflow_rolled_over, 0);
+    }
+
+    #[test]
+    fn test_prize_cap_roll_over() {
+        let lottery = draw_with_capped_top_bracket(OverflowPolicy::RollOver);
+
+        let amount_to_share: u128 = 1519240000000000000000000;
+        let overflow = amount_to_share * 5000 / 10000 - ONE_NEAR / 10;
+        assert_eq!(lottery.near_per_bracket[5], ONE_NEAR / 10);
+        assert_eq!(lottery.overflow_per_bracket[5], overflow);
+        assert_eq!(lottery.overflow_received_per_bracket[0], 0);
+        assert_eq!(lottery.near_per_bracket[0], amount_to_share * 125 / 10000);
+        assert_eq!(lottery.overflow_rolled_over, overflow);
+    }
+
+    #[test]
+    fn test_dust_rolls_over_to_next_lottery() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let final_number = next_final_number(&mut context);
+        let ticket_number = ticket_number_in_first_bracket(final_number);
+        let losing_ticket_number = 1000000 + (final_number + 1) % 10;
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![
+                ticket_number,
+                ticket_number,
+                ticket_number,
+                losing_ticket_number,
+                losing_ticket_number,
+                losing_ticket_number,
+                losing_ticket_number,
+            ],
+        );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, true);
+
+        let lottery = contract.view_lottery(current_lottery_id);
+        let operate_fee = lottery.amount_collected_in_near * lottery.operate_fee / 10000;
+        let reserve_fee =
+            (lottery.amount_collected_in_near - operate_fee) * lottery.reserve_fee / 10000;
+        let amount_to_share = lottery.amount_collected_in_near - operate_fee - reserve_fee;
+        // the three winners of the first bracket share its part of the pot, the other brackets roll over
+        let first_bracket = amount_to_share * 125 / 10000;
+        let no_winners = amount_to_share - first_bracket;
+        assert_eq!(lottery.count_winners_per_bracket[0], 3);
+        assert_eq!(lottery.near_per_bracket[0], first_bracket / 3);
+        assert_eq!(lottery.dust, first_bracket % 3);
+        assert!(lottery.dust > 0);
+        assert_eq!(
+            contract.view_lottery_dust(current_lottery_id).0,
+            lottery.dust
+        );
+        assert_eq!(
+            contract.data().pending_injection_next_lottery,
+            no_winners + reserve_fee + lottery.dust
+        );
+    }
+
+    #[test]
+    fn test_ledger_buckets() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        assert_eq!(
+            contract.view_ledger().storage_deposits,
+            contract.storage_balance_bounds().min
+        );
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+
+        let final_number = next_final_number(&mut context);
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![ticket_number_in_first_bracket(final_number)],
+        );
+        let amount_collected = contract
+            .view_lottery(current_lottery_id)
+            .amount_collected_in_near;
+        assert_eq!(contract.view_ledger().open_pots.0, amount_collected);
+        // the bytes of the tickets bought are paid by the storage deposit
+        assert_eq!(
+            contract.data().ledger.storage_used_bytes,
+            contract.internal_unwrap_storage(&accounts(2)).used_bytes
+        );
+
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, false);
+
+        // the pot is split between the prize, the reserve, the treasury and the operate fee already transferred
+        let lottery = contract.view_lottery(current_lottery_id);
+        let operate_fee = amount_collected * lottery.operate_fee / 10000;
+        let ledger = contract.view_ledger();
+        assert_eq!(ledger.open_pots.0, 0);
+        assert_eq!(ledger.prize_liabilities.0, lottery.near_per_bracket[0]);
+        assert_eq!(
+            contract.view_prize_liabilities(current_lottery_id).0,
+            lottery.near_per_bracket[0]
+        );
+        assert_eq!(
+            ledger.prize_liabilities.0
+                + ledger.reserve.0
+                + ledger.pending_injection.0
+                + ledger.treasury_payable.0,
+            amount_collected - operate_fee
+        );
+
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(1)
+            .build());
+        contract.claim_tickets(current_lottery_id, vec![lottery.first_ticket_id], vec![0]);
+        assert_eq!(contract.view_ledger().prize_liabilities.0, 0);
+        assert_eq!(contract.view_prize_liabilities(current_lottery_id).0, 0);
+
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.withdraw_treasury_payable();
+        assert_eq!(contract.view_ledger().treasury_payable.0, 0);
+
+        // the account balance covers exactly the locked storage and the buckets, the storage
+        // of the account is paid by its deposit
+        testing_env!(context.attached_deposit(0).build());
+        let solvency = contract.view_solvency();
+        let used_bytes = contract.internal_unwrap_storage(&accounts(2)).used_bytes;
+        assert!(used_bytes > 0);
+        assert_eq!(contract.data().ledger.storage_used_bytes, used_bytes);
+        assert_eq!(
+            solvency.locked_storage.0,
+            Balance::from(env::storage_usage() - used_bytes) * env::storage_byte_cost()
+        );
+        testing_env!(context
+            .account_balance(solvency.locked_storage.0 + solvency.total_buckets.0)
+            .build());
+        let solvency = contract.view_solvency();
+        assert!(solvency.is_solvent);
+        assert_eq!(solvency.surplus.0, 0);
+
+        testing_env!(context
+            .account_balance(solvency.locked_storage.0 + solvency.total_buckets.0 - 1)
+            .build());
+        let solvency = contract.view_solvency();
+        assert!(!solvency.is_solvent);
+        assert_eq!(solvency.deficit.0, 1);
+    }
+
     #[test]
     fn test_calculate_rewards_for_ticket_id() {
         let (mut context, mut contract) = setup_contract(set_config_lottery(false));
@@ -1054,6 +2861,63 @@ mod tests {
         println!("random_number: {:?}", result);
     }
 
+    #[test]
+    fn test_randomness_source() {
+        let (_, contract) = setup_contract(set_config_lottery(false));
+        let expected_source = if cfg!(feature = "test-randomness") {
+            "queue"
+        } else {
+            "vrf"
+        };
+        assert_eq!(contract.view_randomness_source(), expected_source);
+    }
+
+    #[cfg(feature = "test-randomness")]
+    #[test]
+    fn test_queue_final_numbers() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.queue_final_numbers(vec![1327419]);
+        assert_eq!(contract.view_queued_final_numbers(), vec![1327419]);
+
+        deposit_for_account(&mut context, &mut contract, accounts(2));
+        start_a_lottery(&mut context, &mut contract, accounts(2));
+        let current_lottery_id = contract.data().current_lottery_id;
+        buy_a_ticket(
+            &mut context,
+            &mut contract,
+            accounts(2),
+            current_lottery_id,
+            vec![1327419],
+        );
+        close_lottery(&mut context, &mut contract);
+        contract.draw_final_number_and_make_lottery_claimable(current_lottery_id, true);
+
+        let lottery = contract.view_lottery(current_lottery_id);
+        assert_eq!(lottery.final_number, 1327419);
+        assert_eq!(lottery.count_winners_per_bracket[5], 1);
+        assert!(contract.view_queued_final_numbers().is_empty());
+
+        // the empty queue falls back to the VRF
+        let vrf_number = VrfSource.final_number();
+        assert_eq!(get_random_number(), vrf_number);
+    }
+
+    #[cfg(feature = "test-randomness")]
+    #[test]
+    #[should_panic(expected = "E31: The ticket number should be in a range 1000000 - 1999999")]
+    fn test_queue_final_numbers_out_of_range() {
+        let (mut context, mut contract) = setup_contract(set_config_lottery(false));
+        testing_env!(context
+            .predecessor_account_id(accounts(0))
+            .attached_deposit(1)
+            .build());
+        contract.queue_final_numbers(vec![1327419, 2000000]);
+    }
+
     #[test]
     fn test_get_current_timestamp() {
         let (mut context, contract) = setup_contract(set_config_lottery(false));
@@ -1247,4 +3111,369 @@ mod tests {
         assert_eq!(1000000000000000000000000, lottery.amount_collected_in_near);
         assert_eq!(0, lottery.final_number);
     }
+
+    mod prize_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        const BUYERS: [usize; 3] = [3, 4, 5];
+
+        #[derive(Debug, Clone)]
+        struct Round {
+            config_lottery: ConfigLottery,
+            reserve_balance: u128,
+            // (buyer, last six digits of the ticket number)
+            tickets: Vec<(usize, [u32; 6])>,
+            final_digits: [u32; 6],
+            auto_injection: bool,
+        }
+
+        fn to_number(digits: &[u32; 6]) -> TicketNumber {
+            1000000 + (0..6).map(|i| digits[i] * 10u32.pow(i as u32)).sum::<u32>()
+        }
+
+        // weights normalized to a breakdown of 10,000, a zero weight is a bracket without percentage
+        fn to_rewards_breakdown(weights: [u128; 6]) -> Vec<u128> {
+            let mut weights = weights.to_vec();
+            if weights.iter().all(|&weight| weight == 0) {
+                weights[5] = 1;
+            }
+            let total: u128 = weights.iter().sum();
+            let mut breakdown: Vec<u128> = weights.iter().map(|w| w * 10000 / total).collect();
+            let highest = (0..6).rev().find(|&j| breakdown[j] != 0).unwrap();
+            breakdown[highest] += 10000 - breakdown.iter().sum::<u128>();
+            breakdown
+        }
+
+        fn round_strategy() -> impl Strategy<Value = Round> {
+            let fees = (
+                100_000_000_000_000_000_000_000u128..2 * ONE_NEAR,
+                prop_oneof![Just(0u128), 500u128..3000],
+                0u128..=3000,
+                0u128..=1000,
+            );
+            let prizes = (
+                prop::array::uniform6(0u128..4),
+                prop::array::uniform6(prop::option::of(1u128..ONE_NEAR)),
+                prop::array::uniform6(prop::option::of(ONE_NEAR / 100..3 * ONE_NEAR)),
+                0u128..2 * ONE_NEAR,
+                any::<bool>(),
+            );
+            // few digits per position, to have winners in every bracket
+            let draw = (
+                prop::collection::vec((0usize..3, prop::array::uniform6(0u32..3)), 0..36),
+                prop::array::uniform6(0u32..3),
+                0u128..3 * ONE_NEAR,
+                any::<bool>(),
+            );
+            (fees, prizes, draw).prop_map(
+                |(
+                    (price, discount_divisor, reserve_fee, operate_fee),
+                    (weights, fixed_prizes, max_prizes, min_jackpot, cascade),
+                    (tickets, final_digits, reserve_balance, auto_injection),
+                )| {
+                    let rewards_breakdown = to_rewards_breakdown(weights);
+                    // a bracket is paid either by a fixed amount or by a percentage
+                    let fixed_prize_per_bracket = (0..6)
+                        .map(|j| match fixed_prizes[j] {
+                            Some(prize) if rewards_breakdown[j] == 0 => U128(prize),
+                            _ => U128(0),
+                        })
+                        .collect();
+                    let max_prize_per_bracket: Vec<U128> = max_prizes
+                        .iter()
+                        .map(|max_prize| U128(max_prize.unwrap_or(0)))
+                        .collect();
+                    // the minimum jackpot fits under the cap of the top bracket
+                    let min_jackpot = match max_prize_per_bracket[5].0 {
+                        0 => min_jackpot,
+                        max_prize => std::cmp::min(min_jackpot, max_prize),
+                    };
+                    Round {
+                        config_lottery: ConfigLottery {
+                            time_run_lottery: 36_000_000_000_000,
+                            price_ticket_in_near: U128(price),
+                            discount_divisor: U128(discount_divisor),
+                            rewards_breakdown,
+                            reserve_fee: U128(reserve_fee),
+                            operate_fee: U128(operate_fee),
+                            max_tickets_per_account: 120,
+                            max_tickets_per_lottery: 10000,
+                            min_jackpot: U128(min_jackpot),
+                            fixed_prize_per_bracket,
+                            max_prize_per_bracket,
+                            overflow_policy: if cascade {
+                                OverflowPolicy::CascadeToLowerBrackets
+                            } else {
+                                OverflowPolicy::RollOver
+                            },
+                        },
+                        reserve_balance,
+                        tickets: tickets
+                            .into_iter()
+                            .map(|(buyer, digits)| (BUYERS[buyer], digits))
+                            .collect(),
+                        final_digits,
+                        auto_injection,
+                    }
+                },
+            )
+        }
+
+        fn total_buckets(ledger: &LedgerView) -> u128 {
+            ledger.open_pots.0
+                + ledger.prize_liabilities.0
+                + ledger.reserve.0
+                + ledger.pending_injection.0
+                + ledger.treasury_payable.0
+        }
+
+        // buy the tickets of the round and close the lottery
+        fn play_round(round: &Round) -> (VMContextBuilder, NearLott, LotteryId) {
+            let (mut context, mut contract) = setup_contract(round.config_lottery.clone());
+            contract.data_mut().reserve_balance = round.reserve_balance;
+            for &buyer in BUYERS.iter() {
+                deposit_for_account(&mut context, &mut contract, accounts(buyer));
+            }
+            start_a_lottery(&mut context, &mut contract, accounts(2));
+            let lottery_id = contract.data().current_lottery_id;
+
+            for &buyer in BUYERS.iter() {
+                let ticket_numbers: Vec<TicketNumber> = round
+                    .tickets
+                    .iter()
+                    .filter(|(owner, _)| *owner == buyer)
+                    .map(|(_, digits)| to_number(digits))
+                    .collect();
+                for chunk in ticket_numbers.chunks(12) {
+                    testing_env!(context
+                        .predecessor_account_id(accounts(buyer))
+                        .attached_deposit(_calculate_total_price_for_bulk_tickets(
+                            round.config_lottery.discount_divisor.0,
+                            round.config_lottery.price_ticket_in_near.0,
+                            chunk.len() as u128,
+                        ))
+                        .build());
+                    contract.buy_tickets(lottery_id, chunk.to_vec());
+                }
+            }
+            close_lottery(&mut context, &mut contract);
+            (context, contract, lottery_id)
+        }
+
+        fn draw_round(
+            context: &mut VMContextBuilder,
+            contract: &mut NearLott,
+            lottery_id: LotteryId,
+            round: &Round,
+        ) -> Lottery {
+            // the last six digits of the final number are the 5th to 10th bytes of the seed
+            let mut random_seed = [0u8; 32];
+            for i in 0..6 {
+                random_seed[4 + i] = round.final_digits[5 - i] as u8;
+            }
+            testing_env!(context
+                .predecessor_account_id(accounts(2))
+                .attached_deposit(1)
+                .random_seed(random_seed)
+                .build());
+            contract.draw_final_number_and_make_lottery_claimable(lottery_id, round.auto_injection);
+            let lottery = contract.view_lottery(lottery_id);
+            assert_eq!(lottery.final_number, to_number(&round.final_digits));
+            lottery
+        }
+
+        fn total_prizes(lottery: &Lottery) -> u128 {
+            (0..6)
+                .map(|j| lottery.near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+                .sum()
+        }
+
+        // the operate fee, the reserve fee and the pot shared by the brackets
+        fn split_collected(lottery: &Lottery) -> (u128, u128, u128) {
+            let operate_fee = (lottery.amount_collected_in_near - lottery.last_pot_size)
+                * lottery.operate_fee
+                / 10000;
+            let reserve_fee =
+                (lottery.amount_collected_in_near - operate_fee) * lottery.reserve_fee / 10000;
+            (
+                operate_fee,
+                reserve_fee,
+                lottery.amount_collected_in_near - operate_fee - reserve_fee,
+            )
+        }
+
+        // the mocked blockchain keeps its storage within a thread, each replay runs on its own
+        fn on_fresh_blockchain<F, R>(replay: F) -> R
+        where
+            F: FnOnce() -> R + Send + 'static,
+            R: Send + 'static,
+        {
+            std::thread::spawn(replay)
+                .join()
+                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+        }
+
+        // claim every ticket of the round at a bracket, with the rewards of the accepted claims
+        fn claim_at_bracket(round: &Round, bracket: BracketPosition) -> Vec<(TicketId, u128)> {
+            let (mut context, mut contract, lottery_id) = play_round(round);
+            let lottery = draw_round(&mut context, &mut contract, lottery_id, round);
+            let mut accepted = vec![];
+            for ticket_id in lottery.first_ticket_id..lottery.first_ticket_id_next_lottery {
+                let owner = contract.data()._tickets.get(&ticket_id).unwrap().owner;
+                testing_env!(context
+                    .predecessor_account_id(owner)
+                    .attached_deposit(1)
+                    .build());
+                let liabilities = contract.view_prize_liabilities(lottery_id).0;
+                let claim = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    contract.claim_tickets(lottery_id, vec![ticket_id], vec![bracket])
+                }));
+                if claim.is_ok() {
+                    accepted.push((
+                        ticket_id,
+                        liabilities - contract.view_prize_liabilities(lottery_id).0,
+                    ));
+                }
+            }
+            accepted
+        }
+
+        fn draw_splits_the_whole_pot(round: Round) -> Result<(), TestCaseError> {
+            let (mut context, mut contract, lottery_id) = play_round(&round);
+            let ledger_before = contract.view_ledger();
+            let lottery = draw_round(&mut context, &mut contract, lottery_id, &round);
+            let ledger_after = contract.view_ledger();
+
+            // the prizes, reserve fee, rollover and dust stay in the ledger, the operate fee leaves it
+            let (operate_fee, reserve_fee, amount_to_share) = split_collected(&lottery);
+            prop_assert_eq!(
+                total_buckets(&ledger_before) - total_buckets(&ledger_after),
+                operate_fee
+            );
+            prop_assert_eq!(ledger_after.prize_liabilities.0, total_prizes(&lottery));
+
+            // the reserve funds the fixed prizes and the top up, it keeps its fee without auto injection
+            let reserve_fee_kept = if round.auto_injection { 0 } else { reserve_fee };
+            let from_reserve = lottery.fixed_prizes_from_reserve + lottery.jackpot_top_up;
+            prop_assert_eq!(
+                ledger_after.reserve.0 + from_reserve,
+                ledger_before.reserve.0 + reserve_fee_kept
+            );
+
+            // the pot and what the reserve added are paid out, rolled over or left as dust
+            let rolled_over = ledger_after.pending_injection.0 + ledger_after.treasury_payable.0
+                - ledger_before.treasury_payable.0
+                - (reserve_fee - reserve_fee_kept);
+            prop_assert_eq!(
+                lottery.amount_collected_in_near + from_reserve,
+                operate_fee + reserve_fee + total_prizes(&lottery) + rolled_over
+            );
+            prop_assert!(rolled_over >= lottery.dust + lottery.overflow_rolled_over);
+            prop_assert!(amount_to_share + from_reserve >= total_prizes(&lottery));
+
+            // the dust is only what the integer divisions left over
+            prop_assert!(lottery.dust <= 3 * (round.tickets.len() as u128 + 6));
+            Ok(())
+        }
+
+        fn brackets_without_winners_roll_over(round: Round) -> Result<(), TestCaseError> {
+            let (mut context, mut contract, lottery_id) = play_round(&round);
+            let ledger_before = contract.view_ledger();
+            let lottery = draw_round(&mut context, &mut contract, lottery_id, &round);
+            let ledger_after = contract.view_ledger();
+
+            let (_, reserve_fee, amount_to_share) = split_collected(&lottery);
+            let fixed_prizes_paid: u128 = (0..6)
+                .filter(|&j| lottery.fixed_prize_per_bracket[j] != 0)
+                .map(|j| lottery.near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+                .sum();
+            let amount_to_share_per_percentage =
+                amount_to_share - (fixed_prizes_paid - lottery.fixed_prizes_from_reserve);
+            let mut expected_roll_over = 0;
+            for j in 0..6 {
+                if lottery.count_winners_per_bracket[j] == 0
+                    && lottery.fixed_prize_per_bracket[j] == 0
+                {
+                    prop_assert_eq!(lottery.near_per_bracket[j], 0);
+                    expected_roll_over +=
+                        lottery.rewards_breakdown[j] * amount_to_share_per_percentage / 10000;
+                }
+            }
+            if round.tickets.is_empty() {
+                expected_roll_over = amount_to_share;
+            }
+
+            // auto injected into the next lottery, or owed to the treasury address
+            if round.auto_injection {
+                prop_assert_eq!(
+                    ledger_after.pending_injection.0,
+                    expected_roll_over + reserve_fee + lottery.overflow_rolled_over + lottery.dust
+                );
+            } else {
+                prop_assert_eq!(
+                    ledger_after.treasury_payable.0 - ledger_before.treasury_payable.0,
+                    expected_roll_over
+                );
+            }
+            Ok(())
+        }
+
+        fn ticket_claims_at_most_one_bracket(round: Round) -> Result<(), TestCaseError> {
+            // a claim gives the ticket away, each bracket is tried on its own replay of the round
+            let claims: Vec<Vec<(TicketId, u128)>> = (0..6)
+                .map(|bracket| {
+                    let round = round.clone();
+                    on_fresh_blockchain(move || claim_at_bracket(&round, bracket))
+                })
+                .collect();
+            let total_prizes = on_fresh_blockchain(move || {
+                let (mut context, mut contract, lottery_id) = play_round(&round);
+                total_prizes(&draw_round(&mut context, &mut contract, lottery_id, &round))
+            });
+
+            let mut claimed_brackets: HashMap<TicketId, Vec<usize>> = HashMap::new();
+            for (bracket, accepted) in claims.iter().enumerate() {
+                for (ticket_id, _) in accepted {
+                    claimed_brackets
+                        .entry(*ticket_id)
+                        .or_default()
+                        .push(bracket);
+                }
+            }
+            for (ticket_id, brackets) in &claimed_brackets {
+                prop_assert_eq!(
+                    brackets.len(),
+                    1,
+                    "ticket {} claims {:?}",
+                    ticket_id,
+                    brackets
+                );
+            }
+
+            // the winning tickets claim exactly the prizes of the draw
+            let claimed: u128 = claims.iter().flatten().map(|(_, reward)| reward).sum();
+            prop_assert_eq!(claimed, total_prizes);
+            Ok(())
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+
+            #[test]
+            fn test_draw_splits_the_whole_pot(round in round_strategy()) {
+                on_fresh_blockchain(move || draw_splits_the_whole_pot(round))?;
+            }
+
+            #[test]
+            fn test_brackets_without_winners_roll_over(round in round_strategy()) {
+                on_fresh_blockchain(move || brackets_without_winners_roll_over(round))?;
+            }
+
+            #[test]
+            fn test_ticket_claims_at_most_one_bracket(round in round_strategy()) {
+                ticket_claims_at_most_one_bracket(round)?;
+            }
+        }
+    }
 }
@@ -6,7 +6,7 @@ pub const ZERO_ADDRESS_WALLET: &str = "no_account.near";
 impl NearLott {
     /**
      * @notice Start the lottery
-     * @dev Callable by operator
+     * @dev Callable by operator or keeper
      * @param _end_time: endTime of the lottery
      * @param _price_ticket_in_near: price of a ticket in NEAR
      * @param _discount_divisor: the divisor to calculate the discount magnitude for bulks
@@ -16,10 +16,11 @@ impl NearLott {
     #[payable]
     pub fn start_lottery(&mut self) {
         self.assert_one_yoctor();
-        self.assert_operator_calling();
-        self.assert_contract_running();
+        self.assert_any_role_calling(&[Role::Operator, Role::Keeper]);
+        self.assert_not_paused(PauseFlag::Draw);
         self.assert_lottery_running();
-        
+        self.assert_valid_config_lottery(&self.data().config_lottery);
+
         // after 4 hours - 5 minutes since now to  4 days + 5 minutes
         let mut data = self.data_mut();
         // extract data
@@ -30,21 +31,22 @@ impl NearLott {
         let reserve_fee = data.config_lottery.reserve_fee.0;
         let operate_fee = data.config_lottery.operate_fee.0;
         let rewards_breakdown = data.config_lottery.rewards_breakdown.clone();
-
-        assert!(
-            discount_divisor >= data.min_discount_divisor,
-            "{}",
-            ERR13_LOTTERY_DISCOUNT_DIVISOR_TOO_LOW
-        );
-
-        assert!(
-            reserve_fee <= data.max_reserve_fee,
-            "{}",
-            ERR15_LOTTERY_OVER_TREASURY_FEE
-        );
-
-        let sum_rewards: u128 = data.config_lottery.rewards_breakdown.iter().sum();
-        assert_eq!(sum_rewards, 10000, "{}", ERR14_LOTTERY_OVER_RANGE_REWARDS);
+        let max_tickets_per_account = data.config_lottery.max_tickets_per_account;
+        let max_tickets_per_lottery = data.config_lottery.max_tickets_per_lottery;
+        let min_jackpot = data.config_lottery.min_jackpot.0;
+        let fixed_prize_per_bracket: Vec<u128> = data
+            .config_lottery
+            .fixed_prize_per_bracket
+            .iter()
+            .map(|prize| prize.0)
+            .collect();
+        let max_prize_per_bracket: Vec<u128> = data
+            .config_lottery
+            .max_prize_per_bracket
+            .iter()
+            .map(|prize| prize.0)
+            .collect();
+        let overflow_policy = data.config_lottery.overflow_policy.clone();
 
         let next_lottery_id = data.current_lottery_id + 1;
         data.current_lottery_id = next_lottery_id;
@@ -66,37 +68,57 @@ impl NearLott {
                 first_ticket_id_next_lottery: data.current_ticket_id,
                 amount_collected_in_near: data.pending_injection_next_lottery,
                 last_pot_size: data.pending_injection_next_lottery,
+                amount_sold_in_near: 0,
                 final_number: 0,
                 operate_fee,
+                max_tickets_per_account,
+                max_tickets_per_lottery,
+                min_jackpot,
+                jackpot_top_up: 0,
+                fixed_prize_per_bracket: fixed_prize_per_bracket.clone(),
+                fixed_prizes_scale: 10000,
+                fixed_prizes_from_reserve: 0,
+                max_prize_per_bracket: max_prize_per_bracket.clone(),
+                overflow_policy: overflow_policy.clone(),
+                overflow_per_bracket: vec![0, 0, 0, 0, 0, 0],
+                overflow_received_per_bracket: vec![0, 0, 0, 0, 0, 0],
+                overflow_rolled_over: 0,
+                dust: 0,
+                config_version: data.config_versions.len() - 1,
             },
         );
 
-        env::log_str(
-            &json!({
-                "type": "start_lottery",
-                "params": {
-                    "current_lottery_id": next_lottery_id,
-                    "start_time":  start_time,
-                    "end_time": end_time,
-                    "price_ticket_in_near": U128(price_ticket_in_near),
-                    "first_ticket_id": data.current_ticket_id,
-                    "first_ticket_id_next_lottery": data.current_ticket_id,
-                    "pending_injection_next_lottery": U128(data.pending_injection_next_lottery),
-                    "_discount_divisor": U128(discount_divisor),
-                    "reserve_fee": U128(reserve_fee),
-                    "operate_fee": U128(operate_fee),
-                }
-            })
-            .to_string(),
-        );
-
+        WelottEvent::StartLottery(vec![StartLotteryData {
+            lottery_id: next_lottery_id,
+            start_time,
+            end_time,
+            price_ticket_in_near: U128(price_ticket_in_near),
+            discount_divisor: U128(discount_divisor),
+            rewards_breakdown: to_u128_vec(&data.config_lottery.rewards_breakdown),
+            reserve_fee: U128(reserve_fee),
+            operate_fee: U128(operate_fee),
+            first_ticket_id: data.current_ticket_id,
+            amount_collected_in_near: U128(data.pending_injection_next_lottery),
+            max_tickets_per_account,
+            max_tickets_per_lottery,
+            min_jackpot: U128(min_jackpot),
+            fixed_prize_per_bracket: to_u128_vec(&fixed_prize_per_bracket),
+            max_prize_per_bracket: to_u128_vec(&max_prize_per_bracket),
+            overflow_policy,
+            config_version: data.config_versions.len() - 1,
+            reserve_balance: U128(data.reserve_balance),
+        }])
+        .emit();
+
+        // the pending injection is now part of the pot of the new lottery
+        data.ledger.open_pots += data.pending_injection_next_lottery;
         data.pending_injection_next_lottery = 0;
     }
     /**
      * @notice Draw the final number, calculate reward in NEAR per group, and make lottery claimable
      * @param _lotteryId: lottery id
      * @param _autoInjection: re-injects funds into next lottery (vs. withdrawing all)
-     * @dev Callable by operator
+     * @dev Callable by operator or keeper
      */
     #[payable]
     pub fn draw_final_number_and_make_lottery_claimable(
@@ -105,8 +127,8 @@ impl NearLott {
         _auto_injection: bool,
     ) {
         self.assert_one_yoctor();
-        self.assert_operator_calling();
-        self.assert_contract_running();
+        self.assert_any_role_calling(&[Role::Operator, Role::Keeper]);
+        self.assert_not_paused(PauseFlag::Draw);
 
         let data = self.data_mut();
         let mut lottery = data
@@ -121,137 +143,122 @@ impl NearLott {
         );
         //  generate winning number from env:seed
         let final_number = get_random_number();
-        // let final_number = 1327419; // TODO: Only remove on the mainnet. It's necessary for testing purpose
         data.random_result = final_number;
 
         // Calculate the finalNumber based on the randomResult generated
         let _final_number = data.random_result as u32;
-        // Initialize a number to count addresses in the previous bracket
-        let mut _number_addresses_in_previous_bracket: u128 = 0;
 
         // Calculate the amount to share post-treasury fee
         // The totally amount_collected_in_near minus 20% of the reserve pool, minutes 5% of the operator fee
-        let _operate_fee = ((lottery.amount_collected_in_near - lottery.last_pot_size)
-            * lottery.operate_fee)
-            / 10000;
-        let _reserver_fee = ((lottery.amount_collected_in_near - _operate_fee)
-            * lottery.reserve_fee)
-            / 10000;
-        let mut _amount_to_share_to_winners = 0;
-        if lottery.amount_collected_in_near > _operate_fee {
-            _amount_to_share_to_winners =
-                lottery.amount_collected_in_near - _operate_fee - _reserver_fee
-        }
-
-        // Initializes the amount to withdraw to the next lottery
-        let mut _amount_to_withdraw_to_next_lottery: u128 = 0;
+        let (_operate_fee, _reserver_fee, _amount_to_share_to_winners) =
+            _calculate_draw_fees(&lottery);
 
         if lottery.first_ticket_id_next_lottery - lottery.first_ticket_id > 0 {
             let number_tickets_per_lottery = data
                 ._bracket_tickets_number
                 .get(&_lottery_id)
                 .expect(ERR19_LOTTERY_NO_TICKERS_NUMBERS);
-
-            // Calculate prizes in NEAR for each bracket by starting from the highest one
-            for i in 0..6 {
-                let j = 5 - i;
-
-                let bracket_number = data
-                    ._bracket_calculator
-                    .get(&j)
-                    .expect(ERR3_NOT_EXISTING_BRACKET);
-                let _transformed_winning_number =
-                    bracket_number + (_final_number % (10u32.pow(j + 1)));
-
-                let number_ticket_in_winning_number = number_tickets_per_lottery
-                    .get(&_transformed_winning_number)
-                    .unwrap_or(0);
-                lottery.count_winners_per_bracket[j as usize] =
-                    number_ticket_in_winning_number - _number_addresses_in_previous_bracket;
-
-                // A. If number of users for this _bracket number is superior to 0
-                if (number_ticket_in_winning_number - _number_addresses_in_previous_bracket) != 0 {
-                    // B. If rewards at this bracket are > 0, calculate, else, report the numberAddresses from previous bracket
-                    // rewardsBreakdown / total (10000) * amount_to_shared_to_winner / (total bracket winner - previous bracket received. Winner lower bracket does not calculate in higher bracket
-                    if lottery.rewards_breakdown[j as usize] != 0 {
-                        lottery.near_per_bracket[j as usize] =
-                            ((lottery.rewards_breakdown[j as usize]
-                                * _amount_to_share_to_winners)
-                                / (number_ticket_in_winning_number
-                                    - _number_addresses_in_previous_bracket))
-                                / 10000;
-                        // Update numberAddressesInPreviousBracket
-                        _number_addresses_in_previous_bracket = number_ticket_in_winning_number;
-                    }
-                    // A. No NEAR to distribute, they are added to the amount to withdraw to treasury address
-                } else {
-                    lottery.near_per_bracket[j as usize] = 0;
-                    _amount_to_withdraw_to_next_lottery += (lottery.rewards_breakdown
-                        [j as usize]
-                        * _amount_to_share_to_winners)
-                        / 10000;
-                }
-            }
-        } else {
-            _amount_to_withdraw_to_next_lottery = _amount_to_share_to_winners
+            let bracket_calculator = &data._bracket_calculator;
+
+            lottery.count_winners_per_bracket =
+                _calculate_count_winners_per_bracket(&lottery, |j| {
+                    let bracket_number =
+                        bracket_calculator.get(&j).expect(ERR3_NOT_EXISTING_BRACKET);
+                    let _transformed_winning_number =
+                        bracket_number + (_final_number % (10u32.pow(j + 1)));
+                    number_tickets_per_lottery
+                        .get(&_transformed_winning_number)
+                        .unwrap_or(0)
+                });
         }
 
+        // Calculate prizes in NEAR for each bracket, funded by the pot and the reserve
+        let prizes = _calculate_prizes(
+            &mut lottery,
+            _amount_to_share_to_winners,
+            data.reserve_balance,
+        );
+        let mut _amount_to_withdraw_to_next_lottery = prizes.amount_to_withdraw_to_next_lottery;
+        let _jackpot_top_up = prizes.jackpot_top_up;
+        let _fixed_prizes_from_pot = prizes.fixed_prizes_from_pot;
+        let _fixed_prizes_from_reserve = prizes.fixed_prizes_from_reserve;
+        let total_prizes: u128 = (0..6)
+            .map(|j| lottery.near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+            .sum();
+
         // Update internal statuses for lottery
         lottery.final_number = _final_number;
         lottery.status = Status::Claimable;
+        lottery.jackpot_top_up = _jackpot_top_up;
+        lottery.fixed_prizes_from_reserve = _fixed_prizes_from_reserve;
 
         // save to chain
         data._lotteries.insert(&_lottery_id, &lottery);
 
+        // the pot is now split between the prizes, the fees and the next lottery
+        data.ledger.open_pots -= lottery.amount_collected_in_near;
+        data.ledger
+            .internal_add_prize_liabilities(&_lottery_id, total_prizes);
+
+        data.reserve_balance -= _fixed_prizes_from_reserve;
+        // the top up is logged by the draw event
+        data.reserve_balance -= _jackpot_top_up;
+
         if _auto_injection {
             // incase there is no one won, we automatically get the number of shares winner per breakdown to pending injector next lottery
             // add reserve fee to the next lottery
             data.pending_injection_next_lottery =
                 _amount_to_withdraw_to_next_lottery + _reserver_fee;
             _amount_to_withdraw_to_next_lottery = 0;
+        } else {
+            // keep the reserve fee aside to guarantee the minimum jackpot of the next lotteries
+            data.reserve_balance += _reserver_fee;
+            // the brackets without winners are owed to the treasury address
+            data.ledger.treasury_payable += _amount_to_withdraw_to_next_lottery;
         }
 
-        // Transfer NEAR to treasury_address
-        Promise::new(data.treasury_address.clone()).transfer(_operate_fee);
-
-        // convert near per bracket to string
-        let near_per_bracket: Vec<String> = lottery
-            .near_per_bracket
-            .iter()
-            .map(|&id| id.to_string())
-            .collect();
-
-        // get rewards breakdown
-        let rewards_breakdown: Vec<String> = lottery.rewards_breakdown
-            .iter()
-            .map(|&id| id.to_string())
-            .collect();
+        // the overflow of the capped brackets and the dust always roll over to the next lottery
+        data.pending_injection_next_lottery += lottery.overflow_rolled_over + lottery.dust;
 
-        // get count tickets for each bracket
-        let counter_winners: Vec<String> = lottery
-            .count_winners_per_bracket
+        // Transfer the operate fee to its recipients in one joint promise
+        let fee_shares = _calculate_fee_shares(_operate_fee, &data.fee_recipients);
+        data.fee_recipients
             .iter()
-            .map(|&id| id.to_string())
-            .collect();
-
-        env::log_str(
-            &json!({
-                "type": "draw_final_number_and_make_lottery_claimable",
-                "params": {
-                    "final_number":  _final_number,
-                    "current_lottery_id":  _lottery_id,
-                    "amount_to_withdraw_to_treasury": U128(_amount_to_withdraw_to_next_lottery),
-                    "near_per_bracket": near_per_bracket.join(","),
-                    "rewards_breakdown": rewards_breakdown.join(","),
-                    "counter_winners": counter_winners.join(","),
-                    "amount_collected_in_near": U128(lottery.amount_collected_in_near),
-                    "operator_fee": U128(_operate_fee),
-                    "reserver_fee": U128(_reserver_fee),
-                    "amount_to_share_to_winners": U128(_amount_to_share_to_winners),
-                }
-            })
-            .to_string(),
-        );
+            .zip(&fee_shares)
+            .filter(|(_, &share)| share > 0)
+            .map(|(recipient, &share)| Promise::new(recipient.account_id.clone()).transfer(share))
+            .reduce(|transfers, transfer| transfers.and(transfer));
+        WelottEvent::DrawLottery(vec![DrawLotteryData {
+            lottery_id: _lottery_id,
+            final_number: _final_number,
+            amount_collected_in_near: U128(lottery.amount_collected_in_near),
+            rewards_breakdown: to_u128_vec(&lottery.rewards_breakdown),
+            near_per_bracket: to_u128_vec(&lottery.near_per_bracket),
+            count_winners_per_bracket: to_u128_vec(&lottery.count_winners_per_bracket),
+            operate_fee: U128(_operate_fee),
+            fee_recipients: data
+                .fee_recipients
+                .iter()
+                .map(|recipient| recipient.account_id.clone())
+                .collect(),
+            fee_shares: to_u128_vec(&fee_shares),
+            reserve_fee: U128(_reserver_fee),
+            amount_to_share_to_winners: U128(_amount_to_share_to_winners),
+            amount_to_treasury: U128(_amount_to_withdraw_to_next_lottery),
+            jackpot_top_up: U128(_jackpot_top_up),
+            fixed_prize_per_bracket: to_u128_vec(&lottery.fixed_prize_per_bracket),
+            fixed_prizes_from_pot: U128(_fixed_prizes_from_pot),
+            fixed_prizes_from_reserve: U128(_fixed_prizes_from_reserve),
+            fixed_prizes_scale: U128(lottery.fixed_prizes_scale),
+            max_prize_per_bracket: to_u128_vec(&lottery.max_prize_per_bracket),
+            overflow_policy: lottery.overflow_policy.clone(),
+            overflow_per_bracket: to_u128_vec(&lottery.overflow_per_bracket),
+            overflow_received_per_bracket: to_u128_vec(&lottery.overflow_received_per_bracket),
+            overflow_rolled_over: U128(lottery.overflow_rolled_over),
+            dust: U128(lottery.dust),
+            reserve_balance: U128(data.reserve_balance),
+        }])
+        .emit();
     }
 
     /**
@@ -262,17 +269,11 @@ impl NearLott {
      */
     #[payable]
     pub fn buy_tickets(&mut self, _lottery_id: LotteryId, _ticket_numbers: Vec<TicketNumber>) {
-        self.assert_contract_running();
+        self.assert_not_paused(PauseFlag::Buy);
         assert!(!_ticket_numbers.is_empty(), "{}", ERR21_TICKETS__LENGTH);
-        // Check total tickets of user per a lottery
         let account_id = env::predecessor_account_id();
         let mut account = self.internal_unwrap_account(&account_id);
         let user_tickets = account.internal_get_ticket_id_per_lottery_or_default(&_lottery_id);
-        assert!(
-            (user_tickets.len() + _ticket_numbers.len()) <= 120,
-            "{}",
-            ERR43_ACCOUNT_MAX_TICKETS_PER_A_LOTTERY
-        );
 
         let data = self.data_mut();
         let mut lottery = data
@@ -280,6 +281,26 @@ impl NearLott {
             .get(&_lottery_id)
             .expect(ERR1_NOT_EXISTING_LOTTERY);
 
+        // Check total tickets of user per a lottery
+        assert!(
+            (user_tickets.len() + _ticket_numbers.len()) as u64 <= lottery.max_tickets_per_account,
+            "{}: {}",
+            ERR43_ACCOUNT_MAX_TICKETS_PER_A_LOTTERY,
+            lottery.max_tickets_per_account
+        );
+
+        // Check total tickets sold in the lottery
+        let number_tickets_sold =
+            (lottery.first_ticket_id_next_lottery - lottery.first_ticket_id) as u64;
+        assert!(
+            number_tickets_sold + _ticket_numbers.len() as u64 <= lottery.max_tickets_per_lottery,
+            "{}: {} tickets left",
+            ERR46_LOTTERY_SOLD_OUT,
+            lottery
+                .max_tickets_per_lottery
+                .saturating_sub(number_tickets_sold)
+        );
+
         assert!(
             _ticket_numbers.len() <= data.max_number_tickets_per_buy_or_claim as usize,
             "{}",
@@ -339,7 +360,7 @@ impl NearLott {
         let bracket_placeholder: Vec<u32> =
             (1..=6_u32).into_iter().map(create_number_one).collect();
 
-        let mut ticket_ids: Vec<String> = vec![];
+        let mut ticket_ids: Vec<TicketId> = vec![];
 
         for i in 0.._valid_ticket_arrays.len() {
             let ticket_number = _valid_ticket_arrays[i];
@@ -373,33 +394,33 @@ impl NearLott {
 
             // Increase lottery ticket number
             let ticket_id = data.current_ticket_id;
-            ticket_ids.push(ticket_id.to_string());
+            ticket_ids.push(ticket_id);
 
             data.current_ticket_id += 1;
         }
 
+        // the amount paid is refunded if the emergency shutdown freezes the lottery
+        let mut account = internal_get_account_unwrap_by_contract_data(data, &account_id);
+        account.internal_add_amount_paid_per_lottery(&_lottery_id, amount_near_to_transfer);
+        internal_set_account_data(data, &account_id, account);
+
         // saving data
         // Increment the total amount collected for the lottery round
         lottery.amount_collected_in_near += amount_near_to_transfer;
+        lottery.amount_sold_in_near += amount_near_to_transfer;
         lottery.first_ticket_id_next_lottery = data.current_ticket_id;
+        data.ledger.open_pots += amount_near_to_transfer;
         data._lotteries.insert(&_lottery_id, &lottery);
         data.permission_update = PermissionUpdateState::Allow;
 
-        // fire log
-        let _ticket_numbers_str: Vec<String> =
-            _ticket_numbers.iter().map(|&id| id.to_string()).collect();
-        env::log_str(
-            &json!({
-                "type": "buy_tickets",
-                "params": {
-                    "buyer": &env::predecessor_account_id(),
-                    "current_lottery_id":  data.current_lottery_id,
-                    "ticket_numbers": _ticket_numbers_str.join(","),
-                    "ticket_ids": ticket_ids.join(",")
-                }
-            })
-            .to_string(),
-        );
+        WelottEvent::BuyTickets(vec![BuyTicketsData {
+            buyer: account_id,
+            lottery_id: _lottery_id,
+            ticket_numbers: _ticket_numbers,
+            ticket_ids,
+            amount: U128(amount_near_to_transfer),
+        }])
+        .emit();
     }
 
     /**
@@ -417,7 +438,7 @@ impl NearLott {
         _brackets: Vec<BracketPosition>,
     ) {
         self.assert_one_yoctor();
-        self.assert_contract_running();
+        self.assert_not_paused(PauseFlag::Claim);
         let data = self.data_mut();
 
         // check ticket len and bracket
@@ -490,22 +511,19 @@ impl NearLott {
             // Check user is claiming the correct bracket
             assert_ne!(reward_for_ticket_id, 0, "{}", ERR28_LOTTERY_CLAIM_NO_PRIZE);
 
-            // revevalute the bracket positon. If still have a bracket higher has reward. We fire an exception
-            if _brackets[i] != 5 {
-                assert_eq!(
-                    _calculate_rewards_for_ticket_id(
-                        data,
-                        _lottery_id,
-                        this_ticket_id,
-                        _brackets[i] + 1 // bracket higher
-                    ),
-                    0,
-                    "{} - ticket: {}, bracket should be: {}",
-                    ERR29_LOTTERY_CLAIM_BRACKET_MUST_BE_HIGHER,
-                    this_ticket_id,
-                    _brackets[i] + 1
-                );
-            }
+            // revevalute the bracket positon. The draw counts the ticket in its highest matching bracket
+            // paying a prize, if there is one above the claimed bracket we fire an exception
+            let higher_bracket = lottery
+                .highest_bracket(ticket.number)
+                .filter(|&bracket| bracket > _brackets[i]);
+            assert_eq!(
+                higher_bracket,
+                None,
+                "{} - ticket: {}, bracket should be: {}",
+                ERR29_LOTTERY_CLAIM_BRACKET_MUST_BE_HIGHER,
+                this_ticket_id,
+                higher_bracket.unwrap_or_default()
+            );
 
             // Update the lottery ticket owner to 0x address
             let zero_address = AccountId::new_unchecked(ZERO_ADDRESS_WALLET.to_string());
@@ -516,7 +534,7 @@ impl NearLott {
             reward_in_near_to_transfer += reward_for_ticket_id;
 
             // add reward into vector rewards
-            rewards.push(reward_for_ticket_id.to_string());
+            rewards.push(U128(reward_for_ticket_id));
         }
 
         // Transfer money to msg.sender
@@ -525,38 +543,31 @@ impl NearLott {
         // transfer
         if reward_in_near_to_transfer > 0 {
             // before transfer
+            data.ledger
+                .internal_release_prize_liabilities(&_lottery_id, reward_in_near_to_transfer);
             Promise::new(env::predecessor_account_id()).transfer(reward_in_near_to_transfer);
 
-            let _ticket_ids_str: Vec<String> =
-                _ticket_ids.iter().map(|&id| id.to_string()).collect();
-            let _brackets_str: Vec<String> = _brackets.iter().map(|&id| id.to_string()).collect();
-            env::log_str(
-                &json!({
-                    "type": "claim_tickets",
-                    "params": {
-                        "claimer": env::predecessor_account_id(),
-                        "transfer_amount_in_reward":  U128(reward_in_near_to_transfer),
-                        "current_lottery_id": _lottery_id,
-                        "ticket_ids_length": _ticket_ids.len(),
-                        "bracket_rewards": _brackets_str.join(","),
-                        "ticket_ids": _ticket_ids_str.join(","),
-                        "rewards": rewards.join(","),
-                    }
-                })
-                .to_string(),
-            );
+            WelottEvent::ClaimTickets(vec![ClaimTicketsData {
+                claimer: env::predecessor_account_id(),
+                lottery_id: _lottery_id,
+                ticket_ids: _ticket_ids,
+                brackets: _brackets,
+                rewards,
+                amount: U128(reward_in_near_to_transfer),
+            }])
+            .emit();
         }
     }
 
     /**
      * @notice Close lottery
-     * @dev Callable by operator
+     * @dev Callable by operator or keeper
      */
     #[payable]
     pub fn close_lottery(&mut self) {
         self.assert_one_yoctor();
-        self.assert_operator_calling();
-        self.assert_contract_running();
+        self.assert_any_role_calling(&[Role::Operator, Role::Keeper]);
+        self.assert_not_paused(PauseFlag::Draw);
         let data = self.data_mut();
         let _lottery_id = data.current_lottery_id;
         let mut lottery = data
@@ -585,15 +596,10 @@ impl NearLott {
         lottery.status = Status::Close;
         data._lotteries.insert(&_lottery_id, &lottery);
 
-        env::log_str(
-            &json!({
-                "type": "close_lottery",
-                "params": {
-                    "current_lottery_id": _lottery_id,
-                    "current_ticket_id":  data.current_ticket_id,
-                }
-            })
-            .to_string(),
-        );
+        WelottEvent::CloseLottery(vec![CloseLotteryData {
+            lottery_id: _lottery_id,
+            first_ticket_id_next_lottery: data.current_ticket_id,
+        }])
+        .emit();
     }
 }
@@ -0,0 +1,256 @@
+use crate::*;
+
+/// Per account cap of the lotteries started before the cap was configurable
+const V0001_MAX_TICKETS_PER_ACCOUNT: u64 = 120;
+
+#[derive(BorshDeserialize, BorshSerialize, Eq, PartialEq, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub enum RunningState {
+    Running,
+    Paused,
+}
+
+/// Layout of the state deployed before the timelock, multisig and emergency modules
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct ContractDataV0001 {
+    pub owner_id: AccountId,
+    pub state: RunningState,
+    pub current_lottery_id: LotteryId,
+    pub current_ticket_id: TicketId,
+
+    pub injector_address: AccountId,
+    pub operator_address: AccountId,
+    pub treasury_address: AccountId,
+    pub max_number_tickets_per_buy_or_claim: u64,
+    pub pending_injection_next_lottery: u128,
+
+    pub min_discount_divisor: u128,
+    pub max_reserve_fee: u128,
+
+    pub config_lottery: ConfigLotteryV0001,
+
+    pub _lotteries: UnorderedMap<LotteryId, LotteryV0001>,
+    pub _tickets: UnorderedMap<TicketId, Ticket>,
+    pub _bracket_tickets_number:
+        UnorderedMap<LotteryId, UnorderedMap<BracketTicketNumber, CountTicketValue>>,
+    pub _bracket_calculator: LookupMap<BracketPosition, u32>,
+    pub random_result: u32,
+    pub permission_update: PermissionUpdateState,
+
+    pub accounts: UnorderedMap<AccountId, VAccount>,
+    pub storage: LookupMap<AccountId, VStorage>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ConfigLotteryV0001 {
+    pub time_run_lottery: u64,
+    pub price_ticket_in_near: U128,
+    pub discount_divisor: U128,
+    pub rewards_breakdown: Vec<u128>,
+    pub reserve_fee: U128,
+    pub operate_fee: U128,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LotteryV0001 {
+    pub lottery_id: LotteryId,
+    pub status: Status,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub price_ticket_in_near: u128,
+    pub discount_divisor: u128,
+    pub rewards_breakdown: Vec<u128>,
+    pub reserve_fee: u128,
+    pub near_per_bracket: Vec<u128>,
+    pub count_winners_per_bracket: Vec<u128>,
+    pub first_ticket_id: u32,
+    pub first_ticket_id_next_lottery: u32,
+    pub amount_collected_in_near: u128,
+    pub last_pot_size: u128,
+    pub final_number: u32,
+    pub operate_fee: u128,
+}
+
+impl From<ConfigLotteryV0001> for ConfigLottery {
+    fn from(v: ConfigLotteryV0001) -> Self {
+        Self {
+            time_run_lottery: v.time_run_lottery,
+            price_ticket_in_near: v.price_ticket_in_near,
+            discount_divisor: v.discount_divisor,
+            rewards_breakdown: v.rewards_breakdown,
+            reserve_fee: v.reserve_fee,
+            operate_fee: v.operate_fee,
+            max_tickets_per_account: V0001_MAX_TICKETS_PER_ACCOUNT,
+            // the lotteries had no cap but the ticket ids
+            max_tickets_per_lottery: u64::from(TicketId::MAX),
+            min_jackpot: U128(0),
+            fixed_prize_per_bracket: vec![U128(0); 6],
+            max_prize_per_bracket: vec![U128(0); 6],
+            overflow_policy: OverflowPolicy::RollOver,
+        }
+    }
+}
+
+impl From<LotteryV0001> for Lottery {
+    fn from(v: LotteryV0001) -> Self {
+        Self {
+            lottery_id: v.lottery_id,
+            status: v.status,
+            start_time: v.start_time,
+            end_time: v.end_time,
+            price_ticket_in_near: v.price_ticket_in_near,
+            discount_divisor: v.discount_divisor,
+            rewards_breakdown: v.rewards_breakdown,
+            reserve_fee: v.reserve_fee,
+            near_per_bracket: v.near_per_bracket,
+            count_winners_per_bracket: v.count_winners_per_bracket,
+            first_ticket_id: v.first_ticket_id,
+            first_ticket_id_next_lottery: v.first_ticket_id_next_lottery,
+            amount_collected_in_near: v.amount_collected_in_near,
+            last_pot_size: v.last_pot_size,
+            amount_sold_in_near: v.amount_collected_in_near - v.last_pot_size,
+            final_number: v.final_number,
+            operate_fee: v.operate_fee,
+            max_tickets_per_account: V0001_MAX_TICKETS_PER_ACCOUNT,
+            max_tickets_per_lottery: u64::from(TicketId::MAX),
+            min_jackpot: 0,
+            jackpot_top_up: 0,
+            fixed_prize_per_bracket: vec![0; 6],
+            fixed_prizes_scale: 10000,
+            fixed_prizes_from_reserve: 0,
+            max_prize_per_bracket: vec![0; 6],
+            overflow_policy: OverflowPolicy::RollOver,
+            overflow_per_bracket: vec![0; 6],
+            overflow_received_per_bracket: vec![0; 6],
+            overflow_rolled_over: 0,
+            dust: 0,
+            config_version: 0,
+        }
+    }
+}
+
+/// Rewrite the V0001 state in the current layout.
+/// Only the drawn lotteries are migrated, the buyers of an open lottery paid before the
+/// amounts paid were recorded and could not be refunded by the emergency shutdown.
+fn migrate_contract_data_v0001(mut v: ContractDataV0001) -> ContractData {
+    let lotteries: Vec<Lottery> = v
+        ._lotteries
+        .values()
+        .map(|lottery| {
+            assert_eq!(
+                lottery.status,
+                Status::Claimable,
+                "{}",
+                ERR76_MIGRATE_DURING_LOTTERY
+            );
+            lottery.into()
+        })
+        .collect();
+    // the values are cleared raw, the map is rebuilt under the same prefix
+    v._lotteries.clear();
+
+    let mut ledger = Ledger::new();
+    let mut _lotteries = UnorderedMap::new(StorageKey::Lotteries);
+    for lottery in lotteries.iter() {
+        // the prizes claimed before are not recorded, the prizes drawn are kept as liabilities
+        let total_prizes: Balance = (0..6)
+            .map(|j| lottery.near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+            .sum();
+        ledger.internal_add_prize_liabilities(&lottery.lottery_id, total_prizes);
+        _lotteries.insert(&lottery.lottery_id, lottery);
+    }
+    for account_id in v.accounts.keys() {
+        if let Some(storage) = v.storage.get(&account_id) {
+            let storage: Storage = storage.into();
+            ledger.storage_deposits += storage.storage_balance;
+            ledger.storage_used_bytes += storage.used_bytes;
+        }
+    }
+
+    let paused = v.state == RunningState::Paused;
+    let fee_recipients = vec![FeeRecipient {
+        account_id: v.treasury_address.clone(),
+        share: 10000,
+    }];
+
+    ContractData {
+        treasury_address: v.treasury_address,
+        pause_flags: PauseFlags {
+            buy: paused,
+            draw: paused,
+            claim: paused,
+            storage_withdraw: paused,
+            admin_config: paused,
+        },
+        current_lottery_id: v.current_lottery_id,
+        current_ticket_id: v.current_ticket_id,
+        max_number_tickets_per_buy_or_claim: v.max_number_tickets_per_buy_or_claim,
+        pending_injection_next_lottery: v.pending_injection_next_lottery,
+        reserve_balance: 0,
+        ledger,
+        fee_recipients,
+        role_proposals: LookupMap::new(StorageKey::RoleProposals),
+        acl: LookupMap::new(StorageKey::Acl),
+        pending_changes: UnorderedMap::new(StorageKey::PendingChanges),
+        next_change_id: 0,
+        timelock_delay: DEFAULT_TIMELOCK_DELAY,
+        // the owner approves alone until the signers are set
+        multisig: Multisig::new(vec![v.owner_id], 1),
+        governance_id: None,
+        config_versions: Vector::new(StorageKey::ConfigVersions),
+        emergency: None,
+        min_discount_divisor: v.min_discount_divisor,
+        max_reserve_fee: v.max_reserve_fee,
+        config_lottery: v.config_lottery.into(),
+        _lotteries,
+        _tickets: v._tickets,
+        _bracket_tickets_number: v._bracket_tickets_number,
+        _bracket_calculator: v._bracket_calculator,
+        random_result: v.random_result,
+        permission_update: v.permission_update,
+        accounts: v.accounts,
+        storage: v.storage,
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Migrate the state to the layout of the deployed code
+     * @dev Called by the upgrade batch right after the deploy, a failure rolls the deploy back
+     */
+    #[init(ignore_state)]
+    #[private]
+    pub fn migrate_state() -> Self {
+        let NearLott {
+            data,
+            web_app_url,
+            auditor_account_id,
+        } = env::state_read().expect("ERR_NOT_INITIALIZED");
+        let v = match data {
+            VersionedContractData::V0001(v) => v,
+            data => {
+                return Self {
+                    data,
+                    web_app_url,
+                    auditor_account_id,
+                }
+            }
+        };
+        let owner_id = v.owner_id.clone();
+        let operator_address = v.operator_address.clone();
+        let injector_address = v.injector_address.clone();
+
+        let mut contract = Self {
+            data: VersionedContractData::V0002(migrate_contract_data_v0001(v)),
+            web_app_url,
+            auditor_account_id,
+        };
+        contract.internal_grant_role(&Role::Owner, &owner_id);
+        contract.internal_grant_role(&Role::Operator, &operator_address);
+        contract.internal_grant_role(&Role::Injector, &injector_address);
+        let config_lottery = contract.data().config_lottery.clone();
+        contract.internal_apply_config_lottery(config_lottery, env::current_account_id());
+        contract
+    }
+}
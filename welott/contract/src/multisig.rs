@@ -0,0 +1,373 @@
+use crate::*;
+use near_sdk::json_types::Base58CryptoHash;
+
+/// Sensitive actions executed once approved by the threshold of signers
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MultisigAction {
+    // queues the upgrade behind the timelock
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+    // the ownership is transferred from the outgoing owner once accepted
+    ProposeOwner {
+        account_id: AccountId,
+        previous_owner_id: AccountId,
+        expires_in: Option<u64>,
+    },
+    GrantOwner {
+        account_id: AccountId,
+    },
+    RevokeOwner {
+        account_id: AccountId,
+    },
+    // none for NEAR
+    RecoverWrongTokens {
+        token_address: Option<AccountId>,
+        token_amount: U128,
+        receiver_id: AccountId,
+    },
+    SetFeeRecipients {
+        fee_recipients: Vec<FeeRecipient>,
+    },
+    // queues a config changing the fees behind the timelock
+    SetConfigLottery(ConfigLottery),
+    SetSigners {
+        signers: Vec<AccountId>,
+        threshold: u64,
+    },
+    // the DAO applying the config lottery, none to remove it
+    SetGovernance {
+        governance_id: Option<AccountId>,
+    },
+    // queues the delay change behind the timelock
+    SetTimelockDelay {
+        delay: u64,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MultisigProposalStatus {
+    Pending,
+    Executed,
+    Cancelled,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigProposal {
+    pub proposal_id: u64,
+    pub action: MultisigAction,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+    pub status: MultisigProposalStatus,
+    pub created_at: Timestamp,
+    pub executed_at: Option<Timestamp>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Multisig {
+    pub signers: Vec<AccountId>,
+    pub threshold: u64,
+    // every proposal is kept as the approval history
+    pub proposals: UnorderedMap<u64, MultisigProposal>,
+    pub next_proposal_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MultisigSignersView {
+    pub signers: Vec<AccountId>,
+    pub threshold: u64,
+}
+
+impl Multisig {
+    pub fn new(signers: Vec<AccountId>, threshold: u64) -> Self {
+        Self {
+            signers,
+            threshold,
+            proposals: UnorderedMap::new(StorageKey::MultisigProposals),
+            next_proposal_id: 0,
+        }
+    }
+
+    fn assert_signer(&self, account_id: &AccountId) {
+        assert!(
+            self.signers.contains(account_id),
+            "{}",
+            ERR60_NOT_MULTISIG_SIGNER
+        );
+    }
+
+    /// Approvals from the accounts which are still signers
+    fn count_approvals(&self, proposal: &MultisigProposal) -> u64 {
+        proposal
+            .approvals
+            .iter()
+            .filter(|account_id| self.signers.contains(account_id))
+            .count() as u64
+    }
+}
+
+pub fn assert_valid_signers(signers: &[AccountId], threshold: u64) {
+    let mut distinct_signers = signers.to_vec();
+    distinct_signers.sort();
+    distinct_signers.dedup();
+    assert!(
+        distinct_signers.len() == signers.len()
+            && threshold > 0
+            && threshold <= signers.len() as u64,
+        "{}",
+        ERR62_INVALID_MULTISIG_SIGNERS
+    );
+}
+
+impl NearLott {
+    /// Create a proposal approved by the proposer, executed at once if the threshold is reached
+    pub fn internal_multisig_propose(&mut self, action: MultisigAction) -> u64 {
+        let account_id = env::predecessor_account_id();
+        self.data().multisig.assert_signer(&account_id);
+        match &action {
+            MultisigAction::SetSigners { signers, threshold } => {
+                assert_valid_signers(signers, *threshold);
+            }
+            MultisigAction::ProposeOwner {
+                previous_owner_id, ..
+            } => {
+                assert!(
+                    self.internal_has_role(&Role::Owner, previous_owner_id),
+                    "{}",
+                    ERR73_NOT_AN_OWNER
+                );
+            }
+            _ => {}
+        }
+
+        let multisig = &mut self.data_mut().multisig;
+        let proposal_id = multisig.next_proposal_id;
+        multisig.next_proposal_id += 1;
+        let proposal = MultisigProposal {
+            proposal_id,
+            action,
+            proposer: account_id.clone(),
+            approvals: vec![account_id],
+            status: MultisigProposalStatus::Pending,
+            created_at: env::block_timestamp(),
+            executed_at: None,
+        };
+        WelottEvent::MultisigPropose(vec![MultisigProposeData {
+            proposal_id,
+            action: &proposal.action,
+            proposer: proposal.proposer.clone(),
+        }])
+        .emit();
+        multisig.proposals.insert(&proposal_id, &proposal);
+
+        self.internal_multisig_try_execute(proposal_id);
+        proposal_id
+    }
+
+    fn internal_multisig_try_execute(&mut self, proposal_id: u64) {
+        let multisig = &mut self.data_mut().multisig;
+        let mut proposal = multisig.proposals.get(&proposal_id).unwrap();
+        if multisig.count_approvals(&proposal) < multisig.threshold {
+            return;
+        }
+        proposal.status = MultisigProposalStatus::Executed;
+        proposal.executed_at = Some(env::block_timestamp());
+        multisig.proposals.insert(&proposal_id, &proposal);
+
+        match proposal.action {
+            MultisigAction::Upgrade { code_hash } => {
+                self.internal_queue_change(ConfigChange::Upgrade { code_hash });
+            }
+            MultisigAction::ProposeOwner {
+                account_id,
+                previous_owner_id,
+                expires_in,
+            } => self.internal_propose_role(
+                AddressRole::Owner,
+                account_id,
+                expires_in,
+                Some(previous_owner_id),
+            ),
+            MultisigAction::GrantOwner { account_id } => {
+                self.internal_grant_role(&Role::Owner, &account_id);
+            }
+            MultisigAction::RevokeOwner { account_id } => {
+                self.internal_revoke_role(&Role::Owner, &account_id);
+            }
+            MultisigAction::RecoverWrongTokens {
+                token_address,
+                token_amount,
+                receiver_id,
+            } => self.internal_recover_wrong_tokens(token_address, token_amount.0, receiver_id),
+            MultisigAction::SetFeeRecipients { fee_recipients } => {
+                WelottEvent::SetFeeRecipients(vec![SetFeeRecipientsData {
+                    fee_recipients: &fee_recipients,
+                }])
+                .emit();
+                self.data_mut().fee_recipients = fee_recipients;
+            }
+            MultisigAction::SetConfigLottery(config_lottery) => {
+                self.internal_queue_change(ConfigChange::ConfigLottery {
+                    config_lottery,
+                    approval: ConfigApproval::Multisig { proposal_id },
+                });
+            }
+            MultisigAction::SetSigners { signers, threshold } => {
+                WelottEvent::SetSigners(vec![SetSignersData {
+                    signers: &signers,
+                    threshold,
+                }])
+                .emit();
+                let multisig = &mut self.data_mut().multisig;
+                multisig.signers = signers;
+                multisig.threshold = threshold;
+            }
+            MultisigAction::SetGovernance { governance_id } => {
+                WelottEvent::SetGovernance(vec![SetGovernanceData {
+                    governance_id: governance_id.clone(),
+                }])
+                .emit();
+                self.data_mut().governance_id = governance_id;
+            }
+            MultisigAction::SetTimelockDelay { delay } => {
+                self.internal_queue_change(ConfigChange::TimelockDelay { delay });
+            }
+        }
+
+        WelottEvent::MultisigExecute(vec![MultisigProposalData {
+            proposal_id,
+            sender: env::predecessor_account_id(),
+        }])
+        .emit();
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Propose a sensitive action, approved by the proposer
+     * @param action: action to execute once the threshold of approvals is reached
+     * @return id of the proposal
+     * @dev Callable by a signer
+     */
+    #[payable]
+    pub fn multisig_propose(&mut self, action: MultisigAction) -> u64 {
+        self.assert_one_yoctor();
+        match &action {
+            MultisigAction::SetFeeRecipients { fee_recipients } => {
+                assert_valid_fee_recipients(fee_recipients)
+            }
+            MultisigAction::SetConfigLottery(config_lottery) => {
+                self.assert_valid_config_lottery(config_lottery)
+            }
+            MultisigAction::SetTimelockDelay { delay } => assert_valid_timelock_delay(*delay),
+            _ => (),
+        }
+        self.internal_multisig_propose(action)
+    }
+
+    /**
+     * @notice Approve a pending proposal, executed once the threshold of approvals is reached
+     * @param proposal_id: id of the proposal
+     * @dev Callable by a signer
+     */
+    #[payable]
+    pub fn multisig_approve(&mut self, proposal_id: u64) {
+        self.assert_one_yoctor();
+        let account_id = env::predecessor_account_id();
+        let multisig = &mut self.data_mut().multisig;
+        multisig.assert_signer(&account_id);
+
+        let mut proposal = multisig
+            .proposals
+            .get(&proposal_id)
+            .expect(ERR61_NOT_EXISTING_MULTISIG_PROPOSAL);
+        assert_eq!(
+            proposal.status,
+            MultisigProposalStatus::Pending,
+            "{}",
+            ERR63_MULTISIG_PROPOSAL_NOT_PENDING
+        );
+        assert!(
+            !proposal.approvals.contains(&account_id),
+            "{}",
+            ERR64_MULTISIG_ALREADY_APPROVED
+        );
+        proposal.approvals.push(account_id.clone());
+        multisig.proposals.insert(&proposal_id, &proposal);
+
+        WelottEvent::MultisigApprove(vec![MultisigApproveData {
+            proposal_id,
+            signer: account_id,
+            approvals: multisig.count_approvals(&proposal),
+            threshold: multisig.threshold,
+        }])
+        .emit();
+
+        self.internal_multisig_try_execute(proposal_id);
+    }
+
+    /**
+     * @notice Cancel a pending proposal
+     * @param proposal_id: id of the proposal
+     * @dev Callable by a signer
+     */
+    #[payable]
+    pub fn multisig_cancel(&mut self, proposal_id: u64) {
+        self.assert_one_yoctor();
+        let multisig = &mut self.data_mut().multisig;
+        multisig.assert_signer(&env::predecessor_account_id());
+
+        let mut proposal = multisig
+            .proposals
+            .get(&proposal_id)
+            .expect(ERR61_NOT_EXISTING_MULTISIG_PROPOSAL);
+        assert_eq!(
+            proposal.status,
+            MultisigProposalStatus::Pending,
+            "{}",
+            ERR63_MULTISIG_PROPOSAL_NOT_PENDING
+        );
+        proposal.status = MultisigProposalStatus::Cancelled;
+        multisig.proposals.insert(&proposal_id, &proposal);
+
+        WelottEvent::MultisigCancel(vec![MultisigProposalData {
+            proposal_id,
+            sender: env::predecessor_account_id(),
+        }])
+        .emit();
+    }
+
+    /// Get the signers and the number of approvals required
+    pub fn view_multisig_signers(&self) -> MultisigSignersView {
+        let multisig = &self.data().multisig;
+        MultisigSignersView {
+            signers: multisig.signers.clone(),
+            threshold: multisig.threshold,
+        }
+    }
+
+    /// Get a proposal with its approvals
+    pub fn view_multisig_proposal(&self, proposal_id: u64) -> Option<MultisigProposal> {
+        self.data().multisig.proposals.get(&proposal_id)
+    }
+
+    /// Get the proposals with their approvals, pending or not
+    pub fn view_multisig_proposals(
+        &self,
+        _cursor: Option<u64>,
+        _size: Option<u64>,
+    ) -> Vec<MultisigProposal> {
+        let proposals = &self.data().multisig.proposals;
+        proposals
+            .iter()
+            .skip(_cursor.unwrap_or(0) as usize)
+            .take(_size.unwrap_or_else(|| proposals.len()) as usize)
+            .map(|x| x.1)
+            .collect()
+    }
+}
@@ -1,15 +1,182 @@
 use crate::*;
 
+/// Roles and addresses which are given by a proposal accepted by the proposed account
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Eq, PartialEq, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AddressRole {
+    Owner,
+    Operator,
+    Injector,
+    Treasury,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct RoleProposal {
+    pub account_id: AccountId,
+    // the outgoing owner, revoked and replaced as a signer once accepted
+    pub previous_account_id: Option<AccountId>,
+    // the proposal can not be accepted after this timestamp, if any
+    pub expires_at: Option<Timestamp>,
+}
+
+impl NearLott {
+    pub fn internal_propose_role(
+        &mut self,
+        role: AddressRole,
+        account_id: AccountId,
+        expires_in: Option<u64>,
+        previous_account_id: Option<AccountId>,
+    ) {
+        let proposal = RoleProposal {
+            account_id,
+            previous_account_id,
+            expires_at: expires_in.map(|duration| env::block_timestamp() + duration),
+        };
+        self.data_mut().role_proposals.insert(&role, &proposal);
+
+        WelottEvent::ProposeRole(vec![RoleProposalData {
+            role,
+            account_id: proposal.account_id,
+            expires_at: proposal.expires_at,
+        }])
+        .emit();
+    }
+
+    pub fn internal_accept_role(&mut self, role: AddressRole) {
+        self.assert_one_yoctor();
+
+        let proposal = self
+            .data()
+            .role_proposals
+            .get(&role)
+            .expect(ERR52_NO_ROLE_PROPOSAL);
+        assert_eq!(
+            env::predecessor_account_id(),
+            proposal.account_id,
+            "{}",
+            ERR53_NOT_PROPOSED_ACCOUNT
+        );
+        assert!(
+            proposal
+                .expires_at
+                .map_or(true, |expires_at| env::block_timestamp() <= expires_at),
+            "{}",
+            ERR54_ROLE_PROPOSAL_EXPIRED
+        );
+        self.data_mut().role_proposals.remove(&role);
+
+        // the ownership is transferred from the outgoing owner, the operator and injector roles are granted
+        // next to the current members, and the treasury address is replaced
+        let previous_account_id = match role {
+            AddressRole::Owner => {
+                let previous_account_id = proposal.previous_account_id.expect(ERR73_NOT_AN_OWNER);
+                assert!(
+                    self.internal_has_role(&Role::Owner, &previous_account_id),
+                    "{}",
+                    ERR73_NOT_AN_OWNER
+                );
+                self.internal_grant_role(&Role::Owner, &proposal.account_id);
+                if previous_account_id != proposal.account_id {
+                    self.internal_revoke_role(&Role::Owner, &previous_account_id);
+                    self.internal_replace_signer(&previous_account_id, &proposal.account_id);
+                }
+                Some(previous_account_id)
+            }
+            AddressRole::Operator => {
+                self.internal_grant_role(&Role::Operator, &proposal.account_id);
+                None
+            }
+            AddressRole::Injector => {
+                self.internal_grant_role(&Role::Injector, &proposal.account_id);
+                None
+            }
+            AddressRole::Treasury => {
+                let previous_account_id = std::mem::replace(
+                    &mut self.data_mut().treasury_address,
+                    proposal.account_id.clone(),
+                );
+                // the operate fee of the outgoing treasury goes to the incoming one
+                self.internal_replace_fee_recipient(&previous_account_id, &proposal.account_id);
+                Some(previous_account_id)
+            }
+        };
+
+        WelottEvent::AcceptRole(vec![AcceptRoleData {
+            role,
+            previous_account_id,
+            account_id: env::predecessor_account_id(),
+        }])
+        .emit();
+    }
+
+    /// The incoming owner signs the multisig in place of the outgoing one
+    pub fn internal_replace_signer(
+        &mut self,
+        previous_account_id: &AccountId,
+        account_id: &AccountId,
+    ) {
+        let multisig = &mut self.data_mut().multisig;
+        if let Some(position) = multisig
+            .signers
+            .iter()
+            .position(|signer| signer == previous_account_id)
+        {
+            // the incoming owner may have been a signer already
+            if multisig.signers.contains(account_id) {
+                multisig.signers.remove(position);
+            } else {
+                multisig.signers[position] = account_id.clone();
+            }
+            multisig.threshold = std::cmp::min(multisig.threshold, multisig.signers.len() as u64);
+        }
+    }
+
+    /// Set a pause flag, with an event named after the flag, e.g. `buy_paused` or `claim_resumed`
+    pub fn internal_set_paused(&mut self, flag: PauseFlag, paused: bool) {
+        if !self.data_mut().pause_flags.set(&flag, paused) {
+            return;
+        }
+        WelottEvent::pause(
+            &flag,
+            paused,
+            PauseData {
+                sender: env::predecessor_account_id(),
+            },
+        )
+        .emit();
+    }
+
+    pub fn internal_cancel_role_proposal(&mut self, role: AddressRole) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+
+        let proposal = self
+            .data_mut()
+            .role_proposals
+            .remove(&role)
+            .expect(ERR52_NO_ROLE_PROPOSAL);
+
+        WelottEvent::CancelRoleProposal(vec![RoleProposalData {
+            role,
+            account_id: proposal.account_id,
+            expires_at: proposal.expires_at,
+        }])
+        .emit();
+    }
+}
+
 #[near_bindgen]
 impl NearLott {
     /**
      * @notice Inject funds
      * @param _lotteryId: lottery id
-     * @dev Callable by owner or injector address
+     * @dev Callable by owner or injector
      */
     #[payable]
     pub fn inject_funds(&mut self, _lottery_id: LotteryId) {
-        self.assert_injector_or_owner_calling();
+        self.assert_any_role_calling(&[Role::Injector, Role::Owner]);
+        self.assert_not_emergency();
 
         let data = self.data_mut();
         let mut lottery: Lottery = data
@@ -23,100 +190,252 @@ impl NearLott {
             ERR17_LOTTERY_IS_NOT_OPEN
         );
         lottery.amount_collected_in_near += env::attached_deposit();
+        data.ledger.open_pots += env::attached_deposit();
 
         // save lottery
         data._lotteries.insert(&_lottery_id, &lottery);
 
-        env::log_str(
-            &json!({
-                "type": "inject_funds",
-                "params": {
-                    "lottery_id": _lottery_id,
-                    "amount": U128(env::attached_deposit()),
-                }
-            })
-            .to_string(),
-        );
+        WelottEvent::InjectFunds(vec![InjectFundsData {
+            injector: env::predecessor_account_id(),
+            lottery_id: Some(_lottery_id),
+            amount: U128(env::attached_deposit()),
+            reserve_balance: U128(data.reserve_balance),
+        }])
+        .emit();
+    }
+
+    /**
+     * @notice Inject funds into the reserve used to guarantee the minimum jackpot
+     * @dev Callable by owner or injector
+     */
+    #[payable]
+    pub fn inject_reserve_funds(&mut self) {
+        self.assert_any_role_calling(&[Role::Injector, Role::Owner]);
+        self.assert_not_emergency();
+
+        let data = self.data_mut();
+        data.reserve_balance += env::attached_deposit();
+
+        WelottEvent::InjectReserveFunds(vec![InjectFundsData {
+            injector: env::predecessor_account_id(),
+            lottery_id: None,
+            amount: U128(env::attached_deposit()),
+            reserve_balance: U128(data.reserve_balance),
+        }])
+        .emit();
     }
 
-    /// Get the owner of this contract
+    /**
+     * @notice Transfer the NEAR owed to the treasury address
+     * @dev Callable by owner
+     */
+    #[payable]
+    pub fn withdraw_treasury_payable(&mut self) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+
+        let data = self.data_mut();
+        let amount = data.ledger.treasury_payable;
+        assert!(amount > 0, "{}", ERR50_NOTHING_TO_WITHDRAW);
+        data.ledger.treasury_payable = 0;
+        Promise::new(data.treasury_address.clone()).transfer(amount);
+
+        WelottEvent::WithdrawTreasuryPayable(vec![WithdrawData {
+            account_id: data.treasury_address.clone(),
+            lottery_id: None,
+            amount: U128(amount),
+        }])
+        .emit();
+    }
+
+    /// Get the first owner of this contract, see `view_role_members` for all of them
     pub fn get_owner(&self) -> AccountId {
-        self.data().owner_id.clone()
+        self.internal_role_members(&Role::Owner)[0].clone()
     }
 
-    /// Change owner. Only can be called by owner
+    /**
+     * @notice Propose a new owner to the multisig. Once approved, the proposed account becomes
+     * the owner in place of the outgoing owner by accepting
+     * @param owner_id: proposed owner
+     * @param previous_owner_id: outgoing owner, revoked and replaced as a signer
+     * @param expires_in: optional duration (in nanoseconds) for accepting the proposal
+     * @return id of the multisig proposal
+     * @dev Callable by a multisig signer
+     */
     #[payable]
-    pub fn set_owner(&mut self, owner_id: AccountId) {
+    pub fn propose_owner(
+        &mut self,
+        owner_id: AccountId,
+        previous_owner_id: AccountId,
+        expires_in: Option<u64>,
+    ) -> u64 {
+        self.assert_one_yoctor();
+        self.internal_multisig_propose(MultisigAction::ProposeOwner {
+            account_id: owner_id,
+            previous_owner_id,
+            expires_in,
+        })
+    }
+
+    /**
+     * @notice Accept the ownership
+     * @dev Callable by the proposed owner
+     */
+    #[payable]
+    pub fn accept_owner(&mut self) {
+        self.internal_accept_role(AddressRole::Owner);
+    }
+
+    /**
+     * @notice Cancel the pending owner proposal
+     * @dev Callable by owner
+     */
+    #[payable]
+    pub fn cancel_owner_proposal(&mut self) {
+        self.internal_cancel_role_proposal(AddressRole::Owner);
+    }
+
+    /**
+     * @notice Propose a new operator, injector or treasury address, which is set once accepted
+     * @param role: address to change
+     * @param account_id: proposed account
+     * @param expires_in: optional duration (in nanoseconds) for accepting the proposal
+     * @dev Callable by owner. The owner is proposed by `propose_owner`
+     */
+    #[payable]
+    pub fn propose_role(
+        &mut self,
+        role: AddressRole,
+        account_id: AccountId,
+        expires_in: Option<u64>,
+    ) {
         self.assert_one_yoctor();
         self.assert_owner_calling();
-        self.data_mut().owner_id = owner_id;
+        assert!(
+            role != AddressRole::Owner,
+            "{}",
+            ERR66_OWNER_CHANGE_REQUIRES_MULTISIG
+        );
+        self.internal_propose_role(role, account_id, expires_in, None);
     }
 
+    /**
+     * @notice Accept an operator, injector or treasury address proposal
+     * @param role: address proposed
+     * @dev Callable by the proposed account
+     */
     #[payable]
-    pub fn pause_contract(&mut self) {
+    pub fn accept_role(&mut self, role: AddressRole) {
+        self.internal_accept_role(role);
+    }
+
+    /**
+     * @notice Cancel a pending operator, injector or treasury address proposal
+     * @param role: address proposed
+     * @dev Callable by owner
+     */
+    #[payable]
+    pub fn cancel_role_proposal(&mut self, role: AddressRole) {
+        self.internal_cancel_role_proposal(role);
+    }
+
+    /// Get the pending proposal for an address, if any
+    pub fn view_role_proposal(&self, role: AddressRole) -> Option<RoleProposal> {
+        self.data().role_proposals.get(&role)
+    }
+
+    /**
+     * @notice Pause an operation, the other operations keep running
+     * @param flag: operation to pause
+     * @dev Callable by pauser or owner
+     */
+    #[payable]
+    pub fn pause(&mut self, flag: PauseFlag) {
+        self.assert_one_yoctor();
+        self.assert_any_role_calling(&[Role::Pauser, Role::Owner]);
+        self.internal_set_paused(flag, true);
+    }
+
+    /**
+     * @notice Resume a paused operation
+     * @param flag: operation to resume
+     * @dev Callable by owner
+     */
+    #[payable]
+    pub fn resume(&mut self, flag: PauseFlag) {
         self.assert_one_yoctor();
         self.assert_owner_calling();
+        self.internal_set_paused(flag, false);
+    }
 
-        if self.data().state == RunningState::Running {
-            let msg = format!("Contract paused by {}", env::predecessor_account_id());
-            env::log_str(&msg);
-            self.data_mut().state = RunningState::Paused;
-        } else {
-            env::log_str("Contract state is already in Paused");
+    /**
+     * @notice Pause every operation
+     * @dev Callable by pauser or owner
+     */
+    #[payable]
+    pub fn pause_contract(&mut self) {
+        self.assert_one_yoctor();
+        self.assert_any_role_calling(&[Role::Pauser, Role::Owner]);
+        for flag in ALL_PAUSE_FLAGS.iter() {
+            self.internal_set_paused(flag.clone(), true);
         }
     }
+
+    /**
+     * @notice Resume every operation
+     * @dev Callable by owner
+     */
     #[payable]
     pub fn resume_contract(&mut self) {
         self.assert_one_yoctor();
         self.assert_owner_calling();
-
-        if self.data().state == RunningState::Paused {
-            env::log_str(&format!(
-                "Contract resumed by {}",
-                env::predecessor_account_id()
-            ));
-            self.data_mut().state = RunningState::Running;
-        } else {
-            env::log_str("Contract state is already in Running");
+        for flag in ALL_PAUSE_FLAGS.iter() {
+            self.internal_set_paused(flag.clone(), false);
         }
     }
 
-    /// Migration function between versions
-    /// For next version upgrades, change this function
-    #[init(ignore_state)]
-    pub fn migrate() -> Self {
-        assert_eq!(
-            env::predecessor_account_id(),
-            env::current_account_id(),
-            "ERR_INVALID_PREDECESSOR"
-        );
-        let mut contract: NearLott = env::state_read().expect("ERR_NOT_INITIALIZED");
-        contract.data = match contract.data {
-            VersionedContractData::V0001(data) => VersionedContractData::V0001(data),
-        };
-        contract
+    /// Get the paused operations
+    pub fn view_pause_flags(&self) -> PauseFlags {
+        self.data().pause_flags.clone()
     }
 }
 
 mod upgrade {
+    use near_sdk::serde_json::json;
     use near_sdk::{require, Gas};
 
     use super::*;
     use near_sys as sys;
 
     const GAS_TO_COMPLETE_UPGRADE_CALL: Gas = Gas(Gas::ONE_TERA.0 * 10);
+    const GAS_FOR_ON_UPGRADE_CALL: Gas = Gas(Gas::ONE_TERA.0 * 10);
     const GAS_FOR_GET_CONFIG_CALL: Gas = Gas(Gas::ONE_TERA.0 * 5);
     const MIN_GAS_FOR_MIGRATE_STATE_CALL: Gas = Gas(Gas::ONE_TERA.0 * 10);
 
     /// Self upgrade and call migrate, optimizes gas by not loading into memory the code.
     /// Takes as input non serialized set of bytes of the code.
+    /// The code must be queued by `propose_upgrade` and its timelock delay passed, then anyone can call.
+    /// The upgrade is removed from the queue by `on_upgrade`, in the batch of the deploy.
     #[no_mangle]
     pub extern "C" fn upgrade() {
         env::setup_panic_hook();
         let contract: NearLott = env::state_read().expect("ERR_CONTRACT_IS_NOT_INITIALIZED");
-        contract.assert_owner_calling();
+        let code_hash = unsafe {
+            sys::input(0);
+            // hash the code from the register, without loading it into memory
+            sys::sha256(u64::MAX, 0, 1);
+            env::read_register(1).expect("ERR_NO_CODE_HASH")
+        };
+        let change_id = contract.internal_find_upgrade(&code_hash);
         let current_account_id = env::current_account_id().as_bytes().to_vec();
         let migrate_method_name = b"migrate_state".to_vec();
+        let on_upgrade_method_name = b"on_upgrade".to_vec();
+        let on_upgrade_args = json!({
+            "_change_id": change_id,
+            "_sender": env::predecessor_account_id(),
+        })
+        .to_string()
+        .into_bytes();
         let get_config_method_name = b"get_config".to_vec();
         let empty_args = b"{}".to_vec();
         unsafe {
@@ -127,8 +446,10 @@ mod upgrade {
             );
             sys::promise_batch_action_deploy_contract(promise_id, u64::MAX as _, 0);
             // Gas required to complete this call.
-            let required_gas =
-                env::used_gas() + GAS_TO_COMPLETE_UPGRADE_CALL + GAS_FOR_GET_CONFIG_CALL;
+            let required_gas = env::used_gas()
+                + GAS_TO_COMPLETE_UPGRADE_CALL
+                + GAS_FOR_ON_UPGRADE_CALL
+                + GAS_FOR_GET_CONFIG_CALL;
             require!(
                 env::prepaid_gas() >= required_gas + MIN_GAS_FOR_MIGRATE_STATE_CALL,
                 "Not enough gas to complete state migration"
@@ -144,14 +465,22 @@ mod upgrade {
                 0 as _,
                 migrate_state_attached_gas.0,
             );
+            // Scheduling the removal of the upgrade from the queue, only once the code is deployed.
+            sys::promise_batch_action_function_call(
+                promise_id,
+                on_upgrade_method_name.len() as _,
+                on_upgrade_method_name.as_ptr() as _,
+                on_upgrade_args.len() as _,
+                on_upgrade_args.as_ptr() as _,
+                0 as _,
+                GAS_FOR_ON_UPGRADE_CALL.0,
+            );
             // Scheduling to return config after the migration is completed.
             //
             // The upgrade method attaches it as an action, so the entire upgrade including deploy
             // contract action and migration can be rolled back if the config view call can't be
-            // returned successfully. The view call deserializes the state and deserializes the
-            // config which contains the owner_id. If the contract can deserialize the current config,
-            // then it can validate the owner and execute the upgrade again (in case the previous
-            // upgrade/migration went badly).
+            // returned successfully. The view call deserializes the state with the new code, an
+            // upgrade to a code which can not read the migrated state is rolled back.
             //
             // It's an extra safety guard for the remote contract upgrades.
             sys::promise_batch_action_function_call(
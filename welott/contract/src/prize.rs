@@ -0,0 +1,210 @@
+//! Prize maths of a draw, pure functions shared with the simulator
+use crate::*;
+
+/// What a draw takes from the pot and the reserve, besides the prizes per bracket
+#[derive(Default)]
+pub struct DrawPrizes {
+    pub amount_to_withdraw_to_next_lottery: u128,
+    pub jackpot_top_up: u128,
+    pub fixed_prizes_from_pot: u128,
+    pub fixed_prizes_from_reserve: u128,
+}
+
+/**
+ * @notice Calculate the fees of a lottery being drawn
+ * @param lottery: lottery being drawn
+ * @return operate fee, reserve fee, and amount to share to the winners
+ * @dev The operate fee is not taken from the pot injected before the lottery
+ */
+pub fn _calculate_draw_fees(lottery: &Lottery) -> (u128, u128, u128) {
+    let _operate_fee =
+        ((lottery.amount_collected_in_near - lottery.last_pot_size) * lottery.operate_fee) / 10000;
+    let _reserver_fee =
+        ((lottery.amount_collected_in_near - _operate_fee) * lottery.reserve_fee) / 10000;
+    let mut _amount_to_share_to_winners = 0;
+    if lottery.amount_collected_in_near > _operate_fee {
+        _amount_to_share_to_winners =
+            lottery.amount_collected_in_near - _operate_fee - _reserver_fee
+    }
+    (_operate_fee, _reserver_fee, _amount_to_share_to_winners)
+}
+
+/**
+ * @notice Count the winning tickets of each bracket by starting from the highest one
+ * @param lottery: lottery being drawn
+ * @param _number_of_tickets: number of tickets matching the final number in a bracket
+ * @dev The winners of a bracket paying a prize do not count in the lower brackets
+ */
+pub fn _calculate_count_winners_per_bracket<F>(
+    lottery: &Lottery,
+    _number_of_tickets: F,
+) -> Vec<u128>
+where
+    F: Fn(BracketPosition) -> u128,
+{
+    // Initialize a number to count addresses in the previous bracket
+    let mut _number_addresses_in_previous_bracket: u128 = 0;
+    let mut count_winners_per_bracket = vec![0; 6];
+    for i in 0..6 {
+        let j = 5 - i;
+        let number_ticket_in_winning_number = _number_of_tickets(j);
+        count_winners_per_bracket[j as usize] =
+            number_ticket_in_winning_number - _number_addresses_in_previous_bracket;
+
+        // If this bracket pays a prize, winners of this bracket do not count in the lower brackets
+        if count_winners_per_bracket[j as usize] != 0
+            && (lottery.rewards_breakdown[j as usize] != 0
+                || lottery.fixed_prize_per_bracket[j as usize] != 0)
+        {
+            // Update numberAddressesInPreviousBracket
+            _number_addresses_in_previous_bracket = number_ticket_in_winning_number;
+        }
+    }
+    count_winners_per_bracket
+}
+
+/**
+ * @notice Calculate the prizes in NEAR per winning ticket of each bracket
+ * @param lottery: lottery with its winners counted, gets its prizes, overflows and dust
+ * @param _amount_to_share_to_winners: the pot once the fees are taken
+ * @param _reserve_balance: reserve paying the fixed prizes on shortfall and guaranteeing the minimum jackpot
+ */
+pub fn _calculate_prizes(
+    lottery: &mut Lottery,
+    _amount_to_share_to_winners: u128,
+    _reserve_balance: u128,
+) -> DrawPrizes {
+    // Initializes the amount to withdraw to the next lottery
+    let mut _amount_to_withdraw_to_next_lottery: u128 = 0;
+
+    // Initializes the amount taken from the reserve to top up the jackpot
+    let mut _jackpot_top_up: u128 = 0;
+
+    // Initializes the amounts paid out as fixed prizes
+    let mut _fixed_prizes_from_pot: u128 = 0;
+    let mut _fixed_prizes_from_reserve: u128 = 0;
+
+    if lottery.first_ticket_id_next_lottery - lottery.first_ticket_id > 0 {
+        // Fixed prizes are paid first from the pot, then from the reserve, and scaled down pro-rata on shortfall
+        let total_fixed_prizes: u128 = (0..6)
+            .map(|j| lottery.fixed_prize_per_bracket[j] * lottery.count_winners_per_bracket[j])
+            .sum();
+        if total_fixed_prizes > 0 {
+            let available_for_fixed_prizes = std::cmp::min(
+                total_fixed_prizes,
+                _amount_to_share_to_winners + _reserve_balance,
+            );
+            let (fixed_near_per_bracket, fixed_prizes_scale) = _calculate_fixed_prizes(
+                &lottery.fixed_prize_per_bracket,
+                &lottery.count_winners_per_bracket,
+                available_for_fixed_prizes,
+            );
+            let fixed_prizes_paid: u128 = (0..6)
+                .map(|j| fixed_near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+                .sum();
+            _fixed_prizes_from_pot = std::cmp::min(fixed_prizes_paid, _amount_to_share_to_winners);
+            _fixed_prizes_from_reserve = fixed_prizes_paid - _fixed_prizes_from_pot;
+            lottery.fixed_prizes_scale = fixed_prizes_scale;
+            for j in 0..6 {
+                if lottery.fixed_prize_per_bracket[j] != 0 {
+                    lottery.near_per_bracket[j] = fixed_near_per_bracket[j];
+                }
+            }
+        }
+
+        // The percentage brackets share what is left in the pot
+        let _amount_to_share_per_percentage = _amount_to_share_to_winners - _fixed_prizes_from_pot;
+
+        // Initializes the amount overflowing the caps of the higher brackets
+        let mut _overflow_to_lower_brackets: u128 = 0;
+
+        // Calculate prizes in NEAR for each percentage bracket by starting from the highest one
+        for i in 0..6 {
+            let j = 5 - i;
+            if lottery.fixed_prize_per_bracket[j as usize] != 0 {
+                continue;
+            }
+            let number_winners = lottery.count_winners_per_bracket[j as usize];
+
+            // A. If number of users for this _bracket number is superior to 0
+            if number_winners != 0 {
+                // B. If rewards at this bracket are > 0, calculate
+                // rewardsBreakdown / total (10000) * amount_to_shared_to_winner / (total bracket winner - previous bracket received. Winner lower bracket does not calculate in higher bracket
+                if lottery.rewards_breakdown[j as usize] != 0 {
+                    lottery.near_per_bracket[j as usize] =
+                        ((lottery.rewards_breakdown[j as usize] * _amount_to_share_per_percentage)
+                            / number_winners)
+                            / 10000;
+
+                    // Receive the overflow cascading from the higher brackets
+                    if _overflow_to_lower_brackets > 0 {
+                        let overflow_per_ticket = _overflow_to_lower_brackets / number_winners;
+                        lottery.near_per_bracket[j as usize] += overflow_per_ticket;
+                        lottery.overflow_received_per_bracket[j as usize] =
+                            overflow_per_ticket * number_winners;
+                        _overflow_to_lower_brackets -= overflow_per_ticket * number_winners;
+                    }
+
+                    // Cap the prize per winning ticket, the excess overflows following the policy
+                    let max_prize = lottery.max_prize_per_bracket[j as usize];
+                    if max_prize != 0 && lottery.near_per_bracket[j as usize] > max_prize {
+                        let overflow =
+                            (lottery.near_per_bracket[j as usize] - max_prize) * number_winners;
+                        lottery.near_per_bracket[j as usize] = max_prize;
+                        lottery.overflow_per_bracket[j as usize] = overflow;
+                        match lottery.overflow_policy {
+                            OverflowPolicy::CascadeToLowerBrackets => {
+                                _overflow_to_lower_brackets += overflow
+                            }
+                            OverflowPolicy::RollOver => lottery.overflow_rolled_over += overflow,
+                        }
+                    }
+
+                    // Guarantee the minimum jackpot for the top bracket, funded from the reserve
+                    if j == 5 {
+                        let top_bracket_pot = (lottery.rewards_breakdown[j as usize]
+                            * _amount_to_share_per_percentage)
+                            / 10000
+                            - lottery.overflow_per_bracket[j as usize];
+                        if top_bracket_pot < lottery.min_jackpot {
+                            let top_up_per_ticket = std::cmp::min(
+                                lottery.min_jackpot - top_bracket_pot,
+                                _reserve_balance - _fixed_prizes_from_reserve,
+                            ) / number_winners;
+                            _jackpot_top_up = top_up_per_ticket * number_winners;
+                            lottery.near_per_bracket[j as usize] += top_up_per_ticket;
+                        }
+                    }
+                }
+                // A. No NEAR to distribute, they are added to the amount to withdraw to treasury address
+            } else {
+                lottery.near_per_bracket[j as usize] = 0;
+                _amount_to_withdraw_to_next_lottery += (lottery.rewards_breakdown[j as usize]
+                    * _amount_to_share_per_percentage)
+                    / 10000;
+            }
+        }
+
+        // The overflow which no lower bracket could receive rolls over to the next lottery
+        lottery.overflow_rolled_over += _overflow_to_lower_brackets;
+    } else {
+        _amount_to_withdraw_to_next_lottery = _amount_to_share_to_winners
+    }
+
+    // The dust is what the integer divisions left over once the prizes, the brackets without winners
+    // and the rolled over overflow are taken out of the pot, the fixed prizes and the jackpot top up
+    let total_prizes: u128 = (0..6)
+        .map(|j| lottery.near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+        .sum();
+    lottery.dust = _amount_to_share_to_winners + _fixed_prizes_from_reserve + _jackpot_top_up
+        - total_prizes
+        - _amount_to_withdraw_to_next_lottery
+        - lottery.overflow_rolled_over;
+
+    DrawPrizes {
+        amount_to_withdraw_to_next_lottery: _amount_to_withdraw_to_next_lottery,
+        jackpot_top_up: _jackpot_top_up,
+        fixed_prizes_from_pot: _fixed_prizes_from_pot,
+        fixed_prizes_from_reserve: _fixed_prizes_from_reserve,
+    }
+}
@@ -0,0 +1,161 @@
+use crate::*;
+
+#[cfg(feature = "test-randomness")]
+use near_sdk::collections::LazyOption;
+
+/// Source of the final numbers of the draws, in a range 1000000 - 1999999
+pub trait RandomnessSource {
+    fn name(&self) -> &'static str;
+
+    fn final_number(&mut self) -> TicketNumber;
+}
+
+/// The VRF of the protocol, the random seed of the block
+pub struct VrfSource;
+
+impl RandomnessSource for VrfSource {
+    fn name(&self) -> &'static str {
+        "vrf"
+    }
+
+    fn final_number(&mut self) -> TicketNumber {
+        // generate 15 number position with random position from [1..9]
+        let random: Vec<u8> = random_position();
+
+        // convert so string
+        let randomness_instr = random.iter().map(|x| x.to_string()).collect::<String>();
+        // convert to u64 to prepare for final number
+        let randomness = randomness_instr
+            .parse::<u128>()
+            .expect(ERR34_RANDOM_NUMBER_INVALID);
+
+        // determine final number
+        let win_number = (1000000 + (randomness % 1000000)) as u32;
+
+        WelottEvent::DrawFinalNumber(vec![DrawFinalNumberData {
+            source: self.name(),
+            block_height: env::block_height(),
+            final_number: win_number,
+            random_seed: Some(env::random_seed()),
+            selected_numbers: Some(random),
+            queued_numbers_left: None,
+        }])
+        .emit();
+        // return
+        win_number
+    }
+}
+
+/// Final numbers queued by the owner, drawn in order before falling back to the VRF.
+/// Only compiled with the `test-randomness` feature, for the tests and the staging builds
+#[cfg(feature = "test-randomness")]
+pub struct QueuedSource {
+    // outside of the contract data, the state layout is the same with and without the feature
+    queue: LazyOption<Vec<TicketNumber>>,
+}
+
+#[cfg(feature = "test-randomness")]
+impl QueuedSource {
+    pub fn new() -> Self {
+        Self {
+            queue: LazyOption::new(StorageKey::QueuedFinalNumbers, None),
+        }
+    }
+
+    pub fn queued(&self) -> Vec<TicketNumber> {
+        self.queue.get().unwrap_or_default()
+    }
+
+    pub fn push(&mut self, final_numbers: Vec<TicketNumber>) {
+        let mut queued = self.queued();
+        queued.extend(final_numbers);
+        self.queue.set(&queued);
+    }
+}
+
+#[cfg(feature = "test-randomness")]
+impl Default for QueuedSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "test-randomness")]
+impl RandomnessSource for QueuedSource {
+    fn name(&self) -> &'static str {
+        "queue"
+    }
+
+    fn final_number(&mut self) -> TicketNumber {
+        let mut queued = self.queued();
+        if queued.is_empty() {
+            return VrfSource.final_number();
+        }
+        let final_number = queued.remove(0);
+        self.queue.set(&queued);
+
+        WelottEvent::DrawFinalNumber(vec![DrawFinalNumberData {
+            source: self.name(),
+            block_height: env::block_height(),
+            final_number,
+            random_seed: None,
+            selected_numbers: None,
+            queued_numbers_left: Some(queued.len() as u64),
+        }])
+        .emit();
+        final_number
+    }
+}
+
+/// Randomness source of the build, the mainnet builds only have the VRF
+#[cfg(not(feature = "test-randomness"))]
+pub fn randomness_source() -> impl RandomnessSource {
+    VrfSource
+}
+
+/// Randomness source of the build, the mainnet builds only have the VRF
+#[cfg(feature = "test-randomness")]
+pub fn randomness_source() -> impl RandomnessSource {
+    QueuedSource::new()
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice View the randomness source drawing the final numbers: "vrf" on mainnet,
+     * "queue" on the builds with the `test-randomness` feature
+     */
+    pub fn view_randomness_source(&self) -> String {
+        randomness_source().name().to_string()
+    }
+}
+
+#[cfg(feature = "test-randomness")]
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Queue the final numbers of the next draws, drawn in order before falling back to the VRF
+     * @param _final_numbers: final numbers in a range 1000000 - 1999999
+     * @dev Callable by the owner, only in the builds with the `test-randomness` feature
+     */
+    #[payable]
+    pub fn queue_final_numbers(&mut self, _final_numbers: Vec<TicketNumber>) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+        for final_number in &_final_numbers {
+            assert!(
+                (1000000..=1999999).contains(final_number),
+                "{}",
+                ERR31_TICKET_NUMBER_RANGE
+            );
+        }
+        QueuedSource::new().push(_final_numbers);
+    }
+
+    /**
+     * @notice View the final numbers queued for the next draws
+     */
+    pub fn view_queued_final_numbers(&self) -> Vec<TicketNumber> {
+        QueuedSource::new().queued()
+    }
+}
@@ -58,22 +58,15 @@ impl Storage {
     }
 }
 
-impl NearLott {
-    pub fn internal_get_storage(&self, account_id: &AccountId) -> Option<Storage> {
-        self.data().storage.get(account_id).map(|o| o.into())
-    }
-
-    pub fn internal_unwrap_storage(&self, account_id: &AccountId) -> Storage {
-        self.internal_get_storage(account_id)
-            .expect("Storage for account is missing")
-    }
-
+impl ContractData {
+    /// Save the storage of an account, recording the bytes it added or released
     pub fn internal_set_storage(&mut self, account_id: &AccountId, mut storage: Storage) {
         if storage.storage_tracker.bytes_added >= storage.storage_tracker.bytes_released {
             let extra_bytes_used =
                 storage.storage_tracker.bytes_added - storage.storage_tracker.bytes_released;
             storage.used_bytes += extra_bytes_used;
             storage.assert_storage_covered();
+            self.ledger.storage_used_bytes += extra_bytes_used;
         } else {
             let bytes_released =
                 storage.storage_tracker.bytes_released - storage.storage_tracker.bytes_added;
@@ -82,10 +75,26 @@ impl NearLott {
                 "Internal storage accounting bug"
             );
             storage.used_bytes -= bytes_released;
+            self.ledger.storage_used_bytes -= bytes_released;
         }
         storage.storage_tracker.bytes_released = 0;
         storage.storage_tracker.bytes_added = 0;
-        self.data_mut().storage.insert(account_id, &storage.into());
+        self.storage.insert(account_id, &storage.into());
+    }
+}
+
+impl NearLott {
+    pub fn internal_get_storage(&self, account_id: &AccountId) -> Option<Storage> {
+        self.data().storage.get(account_id).map(|o| o.into())
+    }
+
+    pub fn internal_unwrap_storage(&self, account_id: &AccountId) -> Storage {
+        self.internal_get_storage(account_id)
+            .expect("Storage for account is missing")
+    }
+
+    pub fn internal_set_storage(&mut self, account_id: &AccountId, storage: Storage) {
+        self.data_mut().internal_set_storage(account_id, storage);
     }
 
     pub fn internal_storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
@@ -121,6 +130,7 @@ impl NearLott {
             } else {
                 storage.storage_balance += amount;
                 self.internal_set_storage(&account_id, storage);
+                self.data_mut().ledger.storage_deposits += amount;
             }
         } else {
             let min_balance = self.storage_balance_bounds().min.0;
@@ -138,6 +148,7 @@ impl NearLott {
             } else {
                 storage.storage_balance = amount;
             }
+            self.data_mut().ledger.storage_deposits += storage.storage_balance;
 
             let mut account = Account::new(&account_id);
             // HACK: Tracking the extra bytes required to store the storage object itself and
@@ -148,12 +159,20 @@ impl NearLott {
             account.storage_tracker.stop();
             self.internal_set_account(&account_id, account);
         }
-        self.internal_storage_balance_of(&account_id).unwrap()
+        let storage_balance = self.internal_storage_balance_of(&account_id).unwrap();
+        WelottEvent::StorageDeposit(vec![StorageData {
+            account_id,
+            amount: U128(amount),
+            storage_balance: storage_balance.total,
+        }])
+        .emit();
+        storage_balance
     }
 
     #[payable]
     pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
         assert_one_yocto();
+        self.assert_not_paused(PauseFlag::StorageWithdraw);
         let account_id = env::predecessor_account_id();
         if let Some(storage_balance) = self.internal_storage_balance_of(&account_id) {
             let amount = amount.unwrap_or(storage_balance.available).0;
@@ -164,9 +183,17 @@ impl NearLott {
                 let mut storage = self.internal_unwrap_storage(&account_id);
                 storage.storage_balance -= amount;
                 self.internal_set_storage(&account_id, storage);
+                self.data_mut().ledger.storage_deposits -= amount;
                 Promise::new(account_id.clone()).transfer(amount);
             }
-            self.internal_storage_balance_of(&account_id).unwrap()
+            let storage_balance = self.internal_storage_balance_of(&account_id).unwrap();
+            WelottEvent::StorageWithdraw(vec![StorageData {
+                account_id,
+                amount: U128(amount),
+                storage_balance: storage_balance.total,
+            }])
+            .emit();
+            storage_balance
         } else {
             env::panic_str(&format!("The account {} is not registered", &account_id));
         }
@@ -0,0 +1,234 @@
+use crate::*;
+use near_sdk::json_types::Base58CryptoHash;
+use near_sdk::CryptoHash;
+
+/// Default delay before a queued change can be executed
+pub const DEFAULT_TIMELOCK_DELAY: u64 = 86_400_000_000_000; // 24 hours
+/// Minimum delay, the players are always given notice of the changes
+pub const MIN_TIMELOCK_DELAY: u64 = 3_600_000_000_000; // 1 hour
+
+/// A configuration change waiting for its delay
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ConfigChange {
+    Config {
+        max_number_tickets_per_buy: u64,
+        min_discount_divisor: U128,
+    },
+    ConfigLottery {
+        config_lottery: ConfigLottery,
+        approval: ConfigApproval,
+    },
+    TimelockDelay {
+        delay: u64,
+    },
+    // executed by calling `upgrade` with the code matching the hash
+    Upgrade {
+        code_hash: Base58CryptoHash,
+    },
+}
+
+/// Who queued a config lottery, the fees are only changed by the multisig or the DAO
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ConfigApproval {
+    ConfigManager,
+    Multisig { proposal_id: u64 },
+    Governance,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingChange {
+    pub change_id: u64,
+    pub change: ConfigChange,
+    pub proposed_by: AccountId,
+    pub effective_at: Timestamp,
+}
+
+pub fn assert_valid_timelock_delay(delay: u64) {
+    assert!(
+        delay >= MIN_TIMELOCK_DELAY,
+        "{}",
+        ERR72_TIMELOCK_DELAY_TOO_SHORT
+    );
+}
+
+impl NearLott {
+    pub fn internal_queue_change(&mut self, change: ConfigChange) -> u64 {
+        let data = self.data_mut();
+        let change_id = data.next_change_id;
+        data.next_change_id += 1;
+
+        let pending_change = PendingChange {
+            change_id,
+            change,
+            proposed_by: env::predecessor_account_id(),
+            effective_at: env::block_timestamp() + data.timelock_delay,
+        };
+        WelottEvent::QueueChange(vec![QueueChangeData {
+            change_id,
+            change: &pending_change.change,
+            proposed_by: pending_change.proposed_by.clone(),
+            effective_at: pending_change.effective_at,
+        }])
+        .emit();
+        data.pending_changes.insert(&change_id, &pending_change);
+        change_id
+    }
+
+    /// Find the upgrade queued for a code, once its delay has passed
+    pub fn internal_find_upgrade(&self, code_hash: &[u8]) -> u64 {
+        self.data()
+            .pending_changes
+            .iter()
+            .find(|(_, pending_change)| match &pending_change.change {
+                ConfigChange::Upgrade {
+                    code_hash: queued_code_hash,
+                } => {
+                    CryptoHash::from(*queued_code_hash) == code_hash
+                        && env::block_timestamp() >= pending_change.effective_at
+                }
+                _ => false,
+            })
+            .map(|(change_id, _)| change_id)
+            .expect(ERR58_UPGRADE_NOT_QUEUED)
+    }
+}
+
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Propose a change of the delay before the changes can be executed to the multisig.
+     * Once approved it is queued behind the current delay
+     * @param _delay: delay in nanoseconds, at least 1 hour
+     * @return id of the multisig proposal
+     * @dev Only callable by a multisig signer
+     */
+    #[payable]
+    pub fn set_timelock_delay(&mut self, _delay: u64) -> u64 {
+        self.assert_one_yoctor();
+        assert_valid_timelock_delay(_delay);
+        self.internal_multisig_propose(MultisigAction::SetTimelockDelay { delay: _delay })
+    }
+
+    /**
+     * @notice Propose an upgrade to the multisig. Once approved it is queued, and the code matching
+     * the hash can be deployed by `upgrade` after the delay
+     * @param _code_hash: sha256 of the new code
+     * @return id of the multisig proposal
+     * @dev Only callable by a multisig signer
+     */
+    #[payable]
+    pub fn propose_upgrade(&mut self, _code_hash: Base58CryptoHash) -> u64 {
+        self.assert_one_yoctor();
+        self.internal_multisig_propose(MultisigAction::Upgrade {
+            code_hash: _code_hash,
+        })
+    }
+
+    /**
+     * @notice Apply a queued change once its delay has passed
+     * @param _change_id: id of the change
+     * @dev Callable by anyone
+     */
+    pub fn execute_change(&mut self, _change_id: u64) {
+        self.assert_not_paused(PauseFlag::AdminConfig);
+        let pending_change = self
+            .data()
+            .pending_changes
+            .get(&_change_id)
+            .expect(ERR56_NOT_EXISTING_CHANGE);
+        assert!(
+            env::block_timestamp() >= pending_change.effective_at,
+            "{}: {}",
+            ERR57_CHANGE_NOT_EFFECTIVE,
+            pending_change.effective_at
+        );
+
+        match pending_change.change {
+            ConfigChange::Config {
+                max_number_tickets_per_buy,
+                min_discount_divisor,
+            } => {
+                let data = self.data_mut();
+                data.max_number_tickets_per_buy_or_claim = max_number_tickets_per_buy;
+                data.min_discount_divisor = min_discount_divisor.0;
+            }
+            ConfigChange::ConfigLottery {
+                config_lottery,
+                approval,
+            } => {
+                match approval {
+                    // the fees may have been changed since the config was queued
+                    ConfigApproval::ConfigManager => self.assert_same_fees(&config_lottery),
+                    // the DAO changes the config between lotteries only
+                    ConfigApproval::Governance => self.assert_lottery_running(),
+                    ConfigApproval::Multisig { .. } => {}
+                }
+                self.assert_valid_config_lottery(&config_lottery);
+                self.internal_apply_config_lottery(config_lottery, pending_change.proposed_by);
+            }
+            ConfigChange::TimelockDelay { delay } => self.data_mut().timelock_delay = delay,
+            ConfigChange::Upgrade { .. } => env::panic_str(ERR59_UPGRADE_CHANGE),
+        }
+        self.data_mut().pending_changes.remove(&_change_id);
+
+        WelottEvent::ExecuteChange(vec![ChangeData {
+            change_id: _change_id,
+            sender: env::predecessor_account_id(),
+        }])
+        .emit();
+    }
+
+    /**
+     * @notice Remove an upgrade from the queue once its code is deployed and the state migrated
+     * @param _change_id: id of the upgrade
+     * @param _sender: account calling `upgrade`
+     * @dev Called by the upgrade batch with the deployed code, a cancelled upgrade rolls the deploy back
+     */
+    #[private]
+    pub fn on_upgrade(&mut self, _change_id: u64, _sender: AccountId) {
+        self.data_mut()
+            .pending_changes
+            .remove(&_change_id)
+            .expect(ERR58_UPGRADE_NOT_QUEUED);
+
+        WelottEvent::ExecuteChange(vec![ChangeData {
+            change_id: _change_id,
+            sender: _sender,
+        }])
+        .emit();
+    }
+
+    /**
+     * @notice Cancel a queued change
+     * @param _change_id: id of the change
+     * @dev Only callable by owner
+     */
+    #[payable]
+    pub fn cancel_change(&mut self, _change_id: u64) {
+        self.assert_one_yoctor();
+        self.assert_owner_calling();
+        self.data_mut()
+            .pending_changes
+            .remove(&_change_id)
+            .expect(ERR56_NOT_EXISTING_CHANGE);
+
+        WelottEvent::CancelChange(vec![ChangeData {
+            change_id: _change_id,
+            sender: env::predecessor_account_id(),
+        }])
+        .emit();
+    }
+
+    /// Get the queued changes with the timestamps they become effective
+    pub fn view_pending_changes(&self) -> Vec<PendingChange> {
+        self.data().pending_changes.values().collect()
+    }
+
+    /// Get the delay before a queued change can be executed
+    pub fn view_timelock_delay(&self) -> u64 {
+        self.data().timelock_delay
+    }
+}
@@ -1,34 +1,118 @@
-use crate::callback::ext_ft_contract;
-use crate::gas::GAS_FOR_FT_TRANSFER;
+use crate::callback::{ext_ft_contract, ext_self};
+use crate::gas::{GAS_FOR_FT_TRANSFER, GAS_FOR_RESOLVE_RECOVER};
 
 use crate::*;
 
-use near_sdk::AccountId;
+use near_sdk::{AccountId, PromiseResult};
+
+uint::construct_uint! {
+    /// 256-bit unsigned integer used for intermediate prize calculations.
+    pub struct U256(4);
+}
 
 impl NearLott {
     /**
-     * @notice It allows the admin to recover wrong tokens sent to the contract
-     * @param _token_address: the address of the token to withdraw
-     * @param _token_amount: the number of token amount to withdraw
-     * @dev Only callable by owner.
+     * @notice Transfer wrong tokens sent to the contract, once approved by the multisig
+     * @param token_address: the address of the token to withdraw, none for NEAR
+     * @param token_amount: the number of token amount to withdraw
+     * @param receiver_id: the account receiving the tokens
+     * @dev NEAR is the ticket currency, only its excess over the ledger can be recovered
      */
-    pub fn recover_wrong_tokens(&self, _token_address: AccountId, _token_amount: u128) {
-        self.assert_owner_calling();
-
-        let data = self.data();
-        let sender_id = data.owner_id.clone();
-
-        ext_ft_contract::ft_transfer(
-            sender_id,
-            _token_amount.into(),
-            None,
-            _token_address,
-            1, // one yocto near
-            GAS_FOR_FT_TRANSFER,
+    pub fn internal_recover_wrong_tokens(
+        &mut self,
+        token_address: Option<AccountId>,
+        token_amount: u128,
+        receiver_id: AccountId,
+    ) {
+        let transfer = match &token_address {
+            Some(token_address) => ext_ft_contract::ft_transfer(
+                receiver_id.clone(),
+                token_amount.into(),
+                None,
+                token_address.clone(),
+                1, // one yocto near
+                GAS_FOR_FT_TRANSFER,
+            ),
+            None => {
+                self.assert_near_excess(token_amount);
+                Promise::new(receiver_id.clone()).transfer(token_amount)
+            }
+        };
+        transfer.then(ext_self::on_recover_wrong_tokens(
+            token_address,
+            token_amount.into(),
+            receiver_id,
+            env::current_account_id(),
+            0,
+            GAS_FOR_RESOLVE_RECOVER,
+        ));
+    }
+
+    /// Assert the NEAR amount is not owed by the ledger
+    pub fn assert_near_excess(&self, amount: u128) {
+        let excess = self.view_solvency().surplus.0;
+        assert!(
+            amount <= excess,
+            "{}: {}",
+            ERR70_RECOVER_MORE_THAN_EXCESS,
+            excess
         );
     }
 }
 
+#[near_bindgen]
+impl NearLott {
+    /**
+     * @notice Propose to the multisig the recovery of wrong tokens sent to the contract
+     * @param _token_address: the address of the token to withdraw, none for NEAR
+     * @param _token_amount: the number of token amount to withdraw
+     * @param _receiver_id: the account receiving the tokens
+     * @return id of the multisig proposal
+     * @dev Callable by a multisig signer. NEAR is the ticket currency, only its excess over the ledger
+     * can be recovered
+     */
+    #[payable]
+    pub fn recover_wrong_tokens(
+        &mut self,
+        _token_address: Option<AccountId>,
+        _token_amount: U128,
+        _receiver_id: AccountId,
+    ) -> u64 {
+        self.assert_one_yoctor();
+        assert!(_token_amount.0 > 0, "{}", ERR50_NOTHING_TO_WITHDRAW);
+        if _token_address.is_none() {
+            self.assert_near_excess(_token_amount.0);
+        }
+        self.internal_multisig_propose(MultisigAction::RecoverWrongTokens {
+            token_address: _token_address,
+            token_amount: _token_amount,
+            receiver_id: _receiver_id,
+        })
+    }
+
+    /**
+     * @notice Log the result of a token recovery
+     * @dev Only callable by this contract
+     */
+    #[private]
+    pub fn on_recover_wrong_tokens(
+        &mut self,
+        token_address: Option<AccountId>,
+        token_amount: U128,
+        receiver_id: AccountId,
+    ) -> bool {
+        let success = matches!(env::promise_result(0), PromiseResult::Successful(_));
+        WelottEvent::RecoverWrongTokens(vec![RecoverWrongTokensData {
+            token_address,
+            token_amount,
+            receiver_id,
+            success,
+        }])
+        .emit();
+        success
+    }
+}
+
 /**
 * @notice Create a number generated from number of '1'
 * @param sequence: the time repeats number generator
@@ -115,45 +199,60 @@ pub fn _calculate_rewards_for_ticket_id(
 }
 
 /**
- * @notice Request randomness from a user-provided seed
- * @param _seed: seed provided by the NearLott lottery
+ * @notice Calculate fixed prizes per winning ticket, scaled down pro-rata on shortfall
+ * @param _fixed_prize_per_bracket: fixed prize per winning ticket for each bracket (0 = percentage bracket)
+ * @param _count_winners_per_bracket: number of winning tickets for each bracket
+ * @param _available_amount: amount available to pay the fixed prizes
+ * @return prize per winning ticket for each bracket, and the scale applied (10,000 = 100%)
  */
-pub fn get_random_number() -> u32 {
-    // generate 15 number position with random position from [1..9]
-    let random: Vec<u8> = random_position();
-    let rand_array_str = format!("{:?}", &random);
+pub fn _calculate_fixed_prizes(
+    _fixed_prize_per_bracket: &[u128],
+    _count_winners_per_bracket: &[u128],
+    _available_amount: u128,
+) -> (Vec<u128>, u128) {
+    let total_fixed_prizes: u128 = _fixed_prize_per_bracket
+        .iter()
+        .zip(_count_winners_per_bracket)
+        .map(|(prize, winners)| prize * winners)
+        .sum();
+    if total_fixed_prizes <= _available_amount {
+        return (_fixed_prize_per_bracket.to_vec(), 10000);
+    }
 
-    // convert so string
-    let randomness_instr = random
-        .into_iter()
-        .map(|x| x.to_string())
-        .collect::<String>();
-    // convert to u64 to prepare for final number
-    let randomness = randomness_instr
-        .parse::<u128>()
-        .expect(ERR34_RANDOM_NUMBER_INVALID);
-
-    // determine final number
-    let win_number = (1000000 + (randomness % 1000000)) as u32;
-
-    // write log
-    env::log_str(
-        &json!({
-            "type": "draw_final_number_process",
-            "params": {
-                "block_height": env::block_height(),
-                "vrf_numbers": env::random_seed(),
-                "ten_numbers_selected": rand_array_str,
-                "ten_numbers_selected_in_string":randomness_instr,
-                "current_timestamp": env::block_timestamp(),
-                "logic": format!("(1000000 + ({} % 1000000))", &randomness),
-                "final_number":  &win_number
-            }
+    let prizes = _fixed_prize_per_bracket
+        .iter()
+        .map(|&prize| {
+            (U256::from(prize) * U256::from(_available_amount) / U256::from(total_fixed_prizes))
+                .as_u128()
         })
-        .to_string(),
-    );
-    // return
-    win_number
+        .collect();
+    (prizes, _available_amount * 10000 / total_fixed_prizes)
+}
+
+/**
+ * @notice Split the operate fee between its recipients
+ * @param _operate_fee: operate fee of a lottery
+ * @param _fee_recipients: recipients with their shares in basis points
+ * @return amount for each recipient, the first one also receives the rounding remainder
+ */
+pub fn _calculate_fee_shares(_operate_fee: u128, _fee_recipients: &[FeeRecipient]) -> Vec<u128> {
+    let mut shares: Vec<u128> = _fee_recipients
+        .iter()
+        .map(|recipient| _operate_fee * recipient.share / 10000)
+        .collect();
+    let total_shares: u128 = shares.iter().sum();
+    if let Some(first_share) = shares.first_mut() {
+        *first_share += _operate_fee - total_shares;
+    }
+    shares
+}
+
+/**
+ * @notice Draw a final number from the randomness source of the build
+ * @dev The VRF, unless the contract is built with the `test-randomness` feature
+ */
+pub fn get_random_number() -> u32 {
+    randomness_source().final_number()
 }
 
 /**
@@ -203,36 +302,6 @@ pub fn internal_get_account_unwrap_by_contract_data(
     account.expect(ERR42_ACCOUNT_NO_EXISTING)
 }
 
-pub fn internal_set_storage_data(
-    data: &mut ContractData,
-    account_id: &AccountId,
-    mut storage: Storage,
-) {
-    if storage.storage_tracker.bytes_added >= storage.storage_tracker.bytes_released {
-        let extra_bytes_used =
-            storage.storage_tracker.bytes_added - storage.storage_tracker.bytes_released;
-        storage.used_bytes += extra_bytes_used;
-
-        // check assert
-        let storage_balance_needed = Balance::from(storage.used_bytes) * env::storage_byte_cost();
-        assert!(
-            storage_balance_needed <= storage.storage_balance,
-            "Not enough storage balance"
-        );
-    } else {
-        let bytes_released =
-            storage.storage_tracker.bytes_released - storage.storage_tracker.bytes_added;
-        assert!(
-            storage.used_bytes >= bytes_released,
-            "Internal storage accounting bug"
-        );
-        storage.used_bytes -= bytes_released;
-    }
-    storage.storage_tracker.bytes_released = 0;
-    storage.storage_tracker.bytes_added = 0;
-    data.storage.insert(account_id, &storage.into());
-}
-
 pub fn internal_set_account_data(
     data: &mut ContractData,
     account_id: &AccountId,
@@ -249,5 +318,5 @@ pub fn internal_set_account_data(
     storage.storage_tracker.start();
     data.accounts.insert(account_id, &account.into());
     storage.storage_tracker.stop();
-    internal_set_storage_data(data, account_id, storage);
+    data.internal_set_storage(account_id, storage);
 }
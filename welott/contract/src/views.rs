@@ -114,6 +114,73 @@ impl NearLott {
         lottery.first_ticket_id_next_lottery - lottery.first_ticket_id
     }
 
+    /**
+     * @notice View number of tickets still available in a lottery
+     * @param _lottery_id: lottery id
+     */
+    pub fn view_remaining_tickets_per_lottery(&self, _lottery_id: LotteryId) -> u64 {
+        let data = self.data();
+        let lottery = data
+            ._lotteries
+            .get(&_lottery_id)
+            .expect(ERR1_NOT_EXISTING_LOTTERY);
+        let number_tickets_sold =
+            (lottery.first_ticket_id_next_lottery - lottery.first_ticket_id) as u64;
+        lottery
+            .max_tickets_per_lottery
+            .saturating_sub(number_tickets_sold)
+    }
+
+    /**
+     * @notice View number of tickets a user can still buy in a lottery
+     * @param _user: user address
+     * @param _lottery_id: lottery id
+     */
+    pub fn view_remaining_tickets_per_account(
+        &self,
+        _user: AccountId,
+        _lottery_id: LotteryId,
+    ) -> u64 {
+        let data = self.data();
+        let lottery = data
+            ._lotteries
+            .get(&_lottery_id)
+            .expect(ERR1_NOT_EXISTING_LOTTERY);
+        let number_tickets_bought = self
+            .internal_get_account(&_user)
+            .and_then(|account| account.internal_get_ticket_ids_per_lottery(&_lottery_id))
+            .map(|ticket_ids| ticket_ids.len() as u64)
+            .unwrap_or(0);
+        let remaining_per_account = lottery
+            .max_tickets_per_account
+            .saturating_sub(number_tickets_bought);
+        std::cmp::min(
+            remaining_per_account,
+            self.view_remaining_tickets_per_lottery(_lottery_id),
+        )
+    }
+
+    /**
+     * @notice View the reserve used to guarantee the minimum jackpot
+     */
+    pub fn view_reserve_balance(&self) -> U128 {
+        U128(self.data().reserve_balance)
+    }
+
+    /**
+     * @notice View the dust left over by the integer divisions of a lottery, rolled over to the next lottery
+     * @param _lottery_id: lottery id
+     */
+    pub fn view_lottery_dust(&self, _lottery_id: LotteryId) -> U128 {
+        U128(
+            self.data()
+                ._lotteries
+                .get(&_lottery_id)
+                .expect(ERR1_NOT_EXISTING_LOTTERY)
+                .dust,
+        )
+    }
+
     /**
      * @notice: Get detail the running lottery
      */
@@ -314,7 +381,7 @@ impl NearLott {
      */
     #[private]
     pub fn view_random(&self) -> u32 {
-        get_random_number()
+        VrfSource.final_number()
     }
 
     /**
@@ -336,12 +403,18 @@ impl NearLott {
             discount_divisor: data.config_lottery.discount_divisor,
             rewards_breakdown: data.config_lottery.rewards_breakdown.clone(),
             reserve_fee: data.config_lottery.reserve_fee,
-            operate_fee: data.config_lottery.operate_fee
+            operate_fee: data.config_lottery.operate_fee,
+            max_tickets_per_account: data.config_lottery.max_tickets_per_account,
+            max_tickets_per_lottery: data.config_lottery.max_tickets_per_lottery,
+            min_jackpot: data.config_lottery.min_jackpot,
+            fixed_prize_per_bracket: data.config_lottery.fixed_prize_per_bracket.clone(),
+            max_prize_per_bracket: data.config_lottery.max_prize_per_bracket.clone(),
+            overflow_policy: data.config_lottery.overflow_policy.clone(),
         }
     }
 
     pub fn view_accounts(&self, _cursor: usize, _size: usize) -> Vec<AccountId> {
-        self.assert_operator_calling();
+        self.assert_any_role_calling(&[Role::Operator]);
         self.data().accounts.iter().map(|account| {
             account.0
         }).skip(_cursor).take(_size).collect()
@@ -0,0 +1,173 @@
+//! Cross-check of the indexed state against the views of the contract
+use crate::db::Db;
+use crate::error::Result;
+use crate::events::{LotteryId, TicketId, U128};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Read;
+
+/// Fields of `view_lottery` that the indexer materialises, amounts are JSON numbers there
+#[derive(Debug, Clone, Deserialize)]
+pub struct LotteryView {
+    pub lottery_id: LotteryId,
+    pub status: String,
+    pub first_ticket_id: TicketId,
+    pub first_ticket_id_next_lottery: TicketId,
+    pub amount_collected_in_near: u128,
+    pub final_number: u32,
+    pub near_per_bracket: Vec<u128>,
+    pub count_winners_per_bracket: Vec<u128>,
+}
+
+/// Views of the contract used by the cross-check
+pub trait ContractViews {
+    /// All the lotteries, every page of `view_lotteries`
+    fn view_lotteries(&self) -> Result<Vec<LotteryView>>;
+    fn view_reserve_balance(&self) -> Result<u128>;
+}
+
+/// Views dumped to a JSON file, `{"lotteries": [...], "reserve_balance": "..."}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ViewsDump {
+    pub lotteries: Vec<LotteryView>,
+    pub reserve_balance: U128,
+}
+
+impl ViewsDump {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+impl ContractViews for ViewsDump {
+    fn view_lotteries(&self) -> Result<Vec<LotteryView>> {
+        Ok(self.lotteries.clone())
+    }
+
+    fn view_reserve_balance(&self) -> Result<u128> {
+        Ok(self.reserve_balance.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    // none for the contract wide totals
+    pub lottery_id: Option<LotteryId>,
+    pub field: &'static str,
+    pub indexed: String,
+    pub contract: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(lottery_id) = self.lottery_id {
+            write!(f, "lottery {}: ", lottery_id)?;
+        }
+        write!(
+            f,
+            "{} indexed {} but contract has {}",
+            self.field, self.indexed, self.contract
+        )
+    }
+}
+
+struct Checker {
+    mismatches: Vec<Mismatch>,
+}
+
+impl Checker {
+    fn compare<T: fmt::Debug + PartialEq>(
+        &mut self,
+        lottery_id: Option<LotteryId>,
+        field: &'static str,
+        indexed: T,
+        contract: T,
+    ) {
+        if indexed != contract {
+            self.mismatches.push(Mismatch {
+                lottery_id,
+                field,
+                indexed: format!("{:?}", indexed),
+                contract: format!("{:?}", contract),
+            });
+        }
+    }
+}
+
+/// Compare the indexed lotteries and reserve with the contract, empty when they agree
+pub fn cross_check(db: &Db, views: &dyn ContractViews) -> Result<Vec<Mismatch>> {
+    let mut checker = Checker { mismatches: vec![] };
+    let mut indexed: BTreeMap<LotteryId, _> = db
+        .lotteries()?
+        .into_iter()
+        .map(|lottery| (lottery.lottery_id, lottery))
+        .collect();
+
+    for view in views.view_lotteries()? {
+        let id = Some(view.lottery_id);
+        let lottery = match indexed.remove(&view.lottery_id) {
+            Some(lottery) => lottery,
+            None => {
+                checker.compare(id, "lottery", "missing", "present");
+                continue;
+            }
+        };
+        checker.compare(id, "status", &lottery.status, &view.status);
+        checker.compare(
+            id,
+            "first_ticket_id",
+            lottery.first_ticket_id,
+            view.first_ticket_id,
+        );
+        checker.compare(
+            id,
+            "first_ticket_id_next_lottery",
+            lottery.first_ticket_id_next_lottery,
+            view.first_ticket_id_next_lottery,
+        );
+        checker.compare(
+            id,
+            "tickets_sold",
+            lottery.tickets_sold,
+            (view.first_ticket_id_next_lottery - view.first_ticket_id) as u64,
+        );
+        checker.compare(
+            id,
+            "amount_collected_in_near",
+            lottery.amount_collected_in_near,
+            view.amount_collected_in_near,
+        );
+        if view.status == "Claimable" {
+            checker.compare(
+                id,
+                "final_number",
+                lottery.final_number,
+                Some(view.final_number),
+            );
+            checker.compare(
+                id,
+                "near_per_bracket",
+                lottery.near_per_bracket,
+                Some(view.near_per_bracket),
+            );
+            checker.compare(
+                id,
+                "count_winners_per_bracket",
+                lottery.count_winners_per_bracket,
+                Some(view.count_winners_per_bracket),
+            );
+        }
+    }
+    for lottery_id in indexed.keys() {
+        checker.compare(Some(*lottery_id), "lottery", "present", "missing");
+    }
+
+    checker.compare(
+        None,
+        "reserve_balance",
+        db.reserve_balance()?,
+        views.view_reserve_balance()?,
+    );
+    Ok(checker.mismatches)
+}
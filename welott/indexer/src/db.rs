@@ -0,0 +1,268 @@
+//! SQLite schema of the indexed state. Amounts are yoctoNEAR stored as decimal TEXT, they do not fit in INTEGER
+use crate::error::{Error, Result};
+use crate::events::{AccountId, LotteryId, TicketId};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    receipt_id TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    block_height INTEGER NOT NULL,
+    block_timestamp INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (receipt_id, log_index)
+);
+CREATE TABLE IF NOT EXISTS lotteries (
+    lottery_id INTEGER PRIMARY KEY,
+    status TEXT NOT NULL,
+    start_time INTEGER NOT NULL,
+    end_time INTEGER NOT NULL,
+    price_ticket_in_near TEXT NOT NULL,
+    discount_divisor TEXT NOT NULL,
+    rewards_breakdown TEXT NOT NULL,
+    reserve_fee TEXT NOT NULL,
+    operate_fee TEXT NOT NULL,
+    config_version INTEGER NOT NULL,
+    first_ticket_id INTEGER NOT NULL,
+    first_ticket_id_next_lottery INTEGER NOT NULL,
+    amount_collected_in_near TEXT NOT NULL,
+    final_number INTEGER,
+    near_per_bracket TEXT,
+    count_winners_per_bracket TEXT,
+    amount_to_treasury TEXT,
+    jackpot_top_up TEXT,
+    amount_claimed TEXT NOT NULL DEFAULT '0',
+    started_at_block INTEGER NOT NULL,
+    drawn_at_block INTEGER
+);
+CREATE TABLE IF NOT EXISTS tickets (
+    ticket_id INTEGER PRIMARY KEY,
+    lottery_id INTEGER NOT NULL,
+    owner_id TEXT NOT NULL,
+    number INTEGER NOT NULL,
+    bought_at_block INTEGER NOT NULL,
+    bracket INTEGER,
+    reward TEXT,
+    claimed_at_block INTEGER
+);
+CREATE INDEX IF NOT EXISTS tickets_by_owner ON tickets (owner_id, lottery_id);
+CREATE TABLE IF NOT EXISTS claims (
+    receipt_id TEXT NOT NULL,
+    log_index INTEGER NOT NULL,
+    data_index INTEGER NOT NULL,
+    claimer_id TEXT NOT NULL,
+    lottery_id INTEGER NOT NULL,
+    ticket_count INTEGER NOT NULL,
+    amount TEXT NOT NULL,
+    block_height INTEGER NOT NULL,
+    PRIMARY KEY (receipt_id, log_index, data_index)
+);
+CREATE TABLE IF NOT EXISTS accounts (
+    account_id TEXT PRIMARY KEY,
+    tickets_bought INTEGER NOT NULL DEFAULT 0,
+    tickets_won INTEGER NOT NULL DEFAULT 0,
+    amount_spent TEXT NOT NULL DEFAULT '0',
+    amount_claimed TEXT NOT NULL DEFAULT '0',
+    storage_balance TEXT NOT NULL DEFAULT '0'
+);
+CREATE TABLE IF NOT EXISTS state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+pub const STATE_RESERVE_BALANCE: &str = "reserve_balance";
+pub const STATE_FEE_RECIPIENTS: &str = "fee_recipients";
+pub const STATE_CONFIG_VERSION: &str = "config_version";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LotteryRow {
+    pub lottery_id: LotteryId,
+    pub status: String,
+    pub first_ticket_id: TicketId,
+    pub first_ticket_id_next_lottery: TicketId,
+    pub amount_collected_in_near: u128,
+    pub final_number: Option<u32>,
+    pub near_per_bracket: Option<Vec<u128>>,
+    pub count_winners_per_bracket: Option<Vec<u128>>,
+    pub amount_claimed: u128,
+    pub tickets_sold: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountRow {
+    pub account_id: AccountId,
+    pub tickets_bought: u64,
+    pub tickets_won: u64,
+    pub amount_spent: u128,
+    pub amount_claimed: u128,
+    pub storage_balance: u128,
+}
+
+pub fn parse_amount(amount: &str) -> Result<u128> {
+    amount
+        .parse()
+        .map_err(|_| Error::InvalidAmount(amount.to_string()))
+}
+
+/// Amounts as a JSON array of strings, the way the events log them
+pub fn amounts_to_json(amounts: &[u128]) -> String {
+    let amounts: Vec<String> = amounts.iter().map(|amount| amount.to_string()).collect();
+    serde_json::to_string(&amounts).unwrap()
+}
+
+pub fn amounts_from_json(json: &str) -> Result<Vec<u128>> {
+    let amounts: Vec<String> = serde_json::from_str(json)?;
+    amounts.iter().map(|amount| parse_amount(amount)).collect()
+}
+
+pub struct Db {
+    pub conn: Connection,
+}
+
+impl Db {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::init(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn get_state(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT value FROM state WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    pub fn set_state(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO state (key, value) VALUES (?1, ?2)
+             ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    pub fn reserve_balance(&self) -> Result<u128> {
+        self.get_state(STATE_RESERVE_BALANCE)?
+            .map_or(Ok(0), |amount| parse_amount(&amount))
+    }
+
+    pub fn is_paused(&self, flag: &str) -> Result<bool> {
+        Ok(self.get_state(&format!("paused:{}", flag))?.as_deref() == Some("true"))
+    }
+
+    pub fn lottery(&self, lottery_id: LotteryId) -> Result<Option<LotteryRow>> {
+        Ok(self.query_lotteries(Some(lottery_id))?.pop())
+    }
+
+    pub fn lotteries(&self) -> Result<Vec<LotteryRow>> {
+        self.query_lotteries(None)
+    }
+
+    fn query_lotteries(&self, lottery_id: Option<LotteryId>) -> Result<Vec<LotteryRow>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT l.lottery_id, l.status, l.first_ticket_id, l.first_ticket_id_next_lottery,
+                    l.amount_collected_in_near, l.final_number, l.near_per_bracket,
+                    l.count_winners_per_bracket, l.amount_claimed,
+                    (SELECT COUNT(*) FROM tickets t WHERE t.lottery_id = l.lottery_id)
+             FROM lotteries l
+             WHERE ?1 IS NULL OR l.lottery_id = ?1
+             ORDER BY l.lottery_id",
+        )?;
+        let rows = stmt.query_map([lottery_id], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<u32>>(5)?,
+                row.get::<_, Option<String>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+                row.get::<_, String>(8)?,
+                row.get::<_, u64>(9)?,
+            ))
+        })?;
+        let mut lotteries = vec![];
+        for row in rows {
+            let (
+                lottery_id,
+                status,
+                first_ticket_id,
+                first_ticket_id_next_lottery,
+                amount_collected_in_near,
+                final_number,
+                near_per_bracket,
+                count_winners_per_bracket,
+                amount_claimed,
+                tickets_sold,
+            ) = row?;
+            lotteries.push(LotteryRow {
+                lottery_id,
+                status,
+                first_ticket_id,
+                first_ticket_id_next_lottery,
+                amount_collected_in_near: parse_amount(&amount_collected_in_near)?,
+                final_number,
+                near_per_bracket: near_per_bracket
+                    .as_deref()
+                    .map(amounts_from_json)
+                    .transpose()?,
+                count_winners_per_bracket: count_winners_per_bracket
+                    .as_deref()
+                    .map(amounts_from_json)
+                    .transpose()?,
+                amount_claimed: parse_amount(&amount_claimed)?,
+                tickets_sold,
+            });
+        }
+        Ok(lotteries)
+    }
+
+    pub fn account(&self, account_id: &str) -> Result<Option<AccountRow>> {
+        let row = self
+            .conn
+            .query_row(
+                "SELECT account_id, tickets_bought, tickets_won, amount_spent, amount_claimed,
+                        storage_balance
+                 FROM accounts WHERE account_id = ?1",
+                [account_id],
+                |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, u64>(1)?,
+                        row.get::<_, u64>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, String>(5)?,
+                    ))
+                },
+            )
+            .optional()?;
+        row.map(
+            |(account_id, tickets_bought, tickets_won, spent, claimed, storage_balance)| {
+                Ok(AccountRow {
+                    account_id,
+                    tickets_bought,
+                    tickets_won,
+                    amount_spent: parse_amount(&spent)?,
+                    amount_claimed: parse_amount(&claimed)?,
+                    storage_balance: parse_amount(&storage_balance)?,
+                })
+            },
+        )
+        .transpose()
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Sqlite(rusqlite::Error),
+    /// Line of the outcomes file that is not a receipt outcome
+    InvalidOutcome {
+        line: usize,
+        source: serde_json::Error,
+    },
+    /// Event of a major version the indexer does not understand
+    UnsupportedVersion(String),
+    /// Amount stored in the database that is not a decimal u128
+    InvalidAmount(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Json(err) => write!(f, "json error: {}", err),
+            Error::Sqlite(err) => write!(f, "sqlite error: {}", err),
+            Error::InvalidOutcome { line, source } => {
+                write!(f, "invalid outcome at line {}: {}", line, source)
+            }
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported event version: {}", version)
+            }
+            Error::InvalidAmount(amount) => write!(f, "invalid amount: {}", amount),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
@@ -0,0 +1,340 @@
+//! Welott events as logged by the contract, `EVENT_JSON:{"standard":"welott","version":"1.0.0","event":...,"data":[...]}`
+use crate::error::{Error, Result};
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+pub const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+pub const EVENT_STANDARD: &str = "welott";
+/// Major version of the events understood by the indexer
+pub const EVENT_STANDARD_MAJOR_VERSION: &str = "1";
+
+pub type AccountId = String;
+pub type LotteryId = u32;
+pub type TicketId = u32;
+pub type TicketNumber = u32;
+pub type BracketPosition = u32;
+pub type Timestamp = u64;
+
+/// Amount in yoctoNEAR, logged as a string like near-sdk `U128`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct U128(pub u128);
+
+impl<'de> Deserialize<'de> for U128 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<u128>()
+            .map(U128)
+            .map_err(|err| serde::de::Error::custom(format!("invalid U128 {:?}: {}", s, err)))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartLotteryData {
+    pub lottery_id: LotteryId,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub price_ticket_in_near: U128,
+    pub discount_divisor: U128,
+    pub rewards_breakdown: Vec<U128>,
+    pub reserve_fee: U128,
+    pub operate_fee: U128,
+    pub first_ticket_id: TicketId,
+    pub amount_collected_in_near: U128,
+    pub max_tickets_per_account: u64,
+    pub max_tickets_per_lottery: u64,
+    pub min_jackpot: U128,
+    pub fixed_prize_per_bracket: Vec<U128>,
+    pub max_prize_per_bracket: Vec<U128>,
+    pub overflow_policy: String,
+    pub config_version: u64,
+    pub reserve_balance: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BuyTicketsData {
+    pub buyer: AccountId,
+    pub lottery_id: LotteryId,
+    pub ticket_numbers: Vec<TicketNumber>,
+    pub ticket_ids: Vec<TicketId>,
+    pub amount: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CloseLotteryData {
+    pub lottery_id: LotteryId,
+    pub first_ticket_id_next_lottery: TicketId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DrawLotteryData {
+    pub lottery_id: LotteryId,
+    pub final_number: u32,
+    pub amount_collected_in_near: U128,
+    pub rewards_breakdown: Vec<U128>,
+    pub near_per_bracket: Vec<U128>,
+    pub count_winners_per_bracket: Vec<U128>,
+    pub operate_fee: U128,
+    pub fee_recipients: Vec<AccountId>,
+    pub fee_shares: Vec<U128>,
+    pub reserve_fee: U128,
+    pub amount_to_share_to_winners: U128,
+    pub amount_to_treasury: U128,
+    pub jackpot_top_up: U128,
+    pub fixed_prize_per_bracket: Vec<U128>,
+    pub fixed_prizes_from_pot: U128,
+    pub fixed_prizes_from_reserve: U128,
+    pub fixed_prizes_scale: U128,
+    pub max_prize_per_bracket: Vec<U128>,
+    pub overflow_policy: String,
+    pub overflow_per_bracket: Vec<U128>,
+    pub overflow_received_per_bracket: Vec<U128>,
+    pub overflow_rolled_over: U128,
+    pub dust: U128,
+    pub reserve_balance: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ClaimTicketsData {
+    pub claimer: AccountId,
+    pub lottery_id: LotteryId,
+    pub ticket_ids: Vec<TicketId>,
+    pub brackets: Vec<BracketPosition>,
+    pub rewards: Vec<U128>,
+    pub amount: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct InjectFundsData {
+    pub injector: AccountId,
+    pub lottery_id: Option<LotteryId>,
+    pub amount: U128,
+    pub reserve_balance: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageData {
+    pub account_id: AccountId,
+    pub amount: U128,
+    pub storage_balance: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueueChangeData {
+    pub change_id: u64,
+    // the queued `ConfigChange`, kept as logged
+    pub change: Value,
+    pub proposed_by: AccountId,
+    pub effective_at: Timestamp,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChangeData {
+    pub change_id: u64,
+    pub sender: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApplyConfigLotteryData {
+    pub version: u64,
+    pub applied_by: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeeRecipient {
+    pub account_id: AccountId,
+    pub share: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetFeeRecipientsData {
+    pub fee_recipients: Vec<FeeRecipient>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PauseData {
+    pub sender: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoleData {
+    pub role: String,
+    pub account_id: AccountId,
+    pub sender: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoleProposalData {
+    pub role: String,
+    pub account_id: AccountId,
+    pub expires_at: Option<Timestamp>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AcceptRoleData {
+    pub role: String,
+    pub previous_account_id: Option<AccountId>,
+    pub account_id: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WithdrawData {
+    pub account_id: AccountId,
+    pub lottery_id: Option<LotteryId>,
+    pub amount: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultisigProposeData {
+    pub proposal_id: u64,
+    // the proposed `MultisigAction`, kept as logged
+    pub action: Value,
+    pub proposer: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultisigApproveData {
+    pub proposal_id: u64,
+    pub signer: AccountId,
+    pub approvals: u64,
+    pub threshold: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MultisigProposalData {
+    pub proposal_id: u64,
+    pub sender: AccountId,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnterEmergencyData {
+    pub sender: AccountId,
+    pub frozen_lottery_ids: Vec<LotteryId>,
+    pub amount_to_treasury: U128,
+    pub reserve_balance: U128,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecoverWrongTokensData {
+    pub token_address: Option<AccountId>,
+    pub token_amount: U128,
+    pub receiver_id: AccountId,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DrawFinalNumberData {
+    pub source: String,
+    pub block_height: u64,
+    pub final_number: TicketNumber,
+    pub random_seed: Option<Vec<u8>>,
+    pub selected_numbers: Option<Vec<u8>>,
+    pub queued_numbers_left: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetSignersData {
+    pub signers: Vec<AccountId>,
+    pub threshold: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetGovernanceData {
+    pub governance_id: Option<AccountId>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum WelottEvent {
+    StartLottery(Vec<StartLotteryData>),
+    BuyTickets(Vec<BuyTicketsData>),
+    CloseLottery(Vec<CloseLotteryData>),
+    DrawLottery(Vec<DrawLotteryData>),
+    ClaimTickets(Vec<ClaimTicketsData>),
+    InjectFunds(Vec<InjectFundsData>),
+    InjectReserveFunds(Vec<InjectFundsData>),
+    StorageDeposit(Vec<StorageData>),
+    StorageWithdraw(Vec<StorageData>),
+    QueueChange(Vec<QueueChangeData>),
+    ExecuteChange(Vec<ChangeData>),
+    CancelChange(Vec<ChangeData>),
+    ApplyConfigLottery(Vec<ApplyConfigLotteryData>),
+    SetFeeRecipients(Vec<SetFeeRecipientsData>),
+    BuyPaused(Vec<PauseData>),
+    BuyResumed(Vec<PauseData>),
+    DrawPaused(Vec<PauseData>),
+    DrawResumed(Vec<PauseData>),
+    ClaimPaused(Vec<PauseData>),
+    ClaimResumed(Vec<PauseData>),
+    StorageWithdrawPaused(Vec<PauseData>),
+    StorageWithdrawResumed(Vec<PauseData>),
+    AdminConfigPaused(Vec<PauseData>),
+    AdminConfigResumed(Vec<PauseData>),
+    RoleGranted(Vec<RoleData>),
+    RoleRevoked(Vec<RoleData>),
+    ProposeRole(Vec<RoleProposalData>),
+    AcceptRole(Vec<AcceptRoleData>),
+    CancelRoleProposal(Vec<RoleProposalData>),
+    WithdrawTreasuryPayable(Vec<WithdrawData>),
+    MultisigPropose(Vec<MultisigProposeData>),
+    MultisigApprove(Vec<MultisigApproveData>),
+    MultisigExecute(Vec<MultisigProposalData>),
+    MultisigCancel(Vec<MultisigProposalData>),
+    EnterEmergency(Vec<EnterEmergencyData>),
+    EmergencyWithdraw(Vec<WithdrawData>),
+    EmergencyWithdrawSurplus(Vec<WithdrawData>),
+    RecoverWrongTokens(Vec<RecoverWrongTokensData>),
+    DrawFinalNumber(Vec<DrawFinalNumberData>),
+    SetSigners(Vec<SetSignersData>),
+    SetGovernance(Vec<SetGovernanceData>),
+}
+
+/// An event log as found in a receipt outcome, before decoding its data
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawEvent {
+    pub standard: String,
+    pub version: String,
+    pub event: String,
+    pub data: Value,
+}
+
+impl RawEvent {
+    /// Parse a log line, `None` for the plain logs and the events of other standards
+    pub fn from_log(log: &str) -> Result<Option<RawEvent>> {
+        let json = match log.strip_prefix(EVENT_JSON_PREFIX) {
+            Some(json) => json,
+            None => return Ok(None),
+        };
+        let raw: RawEvent = serde_json::from_str(json)?;
+        if raw.standard != EVENT_STANDARD {
+            return Ok(None);
+        }
+        if raw.version.split('.').next() != Some(EVENT_STANDARD_MAJOR_VERSION) {
+            return Err(Error::UnsupportedVersion(raw.version));
+        }
+        Ok(Some(raw))
+    }
+
+    /// Decode the data of the event
+    pub fn decode(&self) -> Result<WelottEvent> {
+        let tagged = serde_json::json!({ "event": self.event, "data": self.data });
+        Ok(serde_json::from_value(tagged)?)
+    }
+}
+
+/// Pause flag and whether it was paused, for the pause and resume events
+pub fn pause_change(event: &WelottEvent) -> Option<(&'static str, bool)> {
+    match event {
+        WelottEvent::BuyPaused(_) => Some(("buy", true)),
+        WelottEvent::BuyResumed(_) => Some(("buy", false)),
+        WelottEvent::DrawPaused(_) => Some(("draw", true)),
+        WelottEvent::DrawResumed(_) => Some(("draw", false)),
+        WelottEvent::ClaimPaused(_) => Some(("claim", true)),
+        WelottEvent::ClaimResumed(_) => Some(("claim", false)),
+        WelottEvent::StorageWithdrawPaused(_) => Some(("storage_withdraw", true)),
+        WelottEvent::StorageWithdrawResumed(_) => Some(("storage_withdraw", false)),
+        WelottEvent::AdminConfigPaused(_) => Some(("admin_config", true)),
+        WelottEvent::AdminConfigResumed(_) => Some(("admin_config", false)),
+        _ => None,
+    }
+}
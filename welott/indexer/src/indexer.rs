@@ -0,0 +1,336 @@
+//! Materialise the lotteries, tickets, claims and accounts from the events of the contract
+use crate::db::{self, Db, STATE_CONFIG_VERSION, STATE_FEE_RECIPIENTS, STATE_RESERVE_BALANCE};
+use crate::error::Result;
+use crate::events::{self, RawEvent, WelottEvent, U128};
+use crate::outcome::{read_outcomes, Outcome};
+use rusqlite::{params, OptionalExtension};
+use std::io::BufRead;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IndexStats {
+    pub outcomes: u64,
+    // failed outcomes and outcomes of other contracts
+    pub skipped_outcomes: u64,
+    pub events: u64,
+    // events already indexed by a previous run
+    pub duplicate_events: u64,
+}
+
+/// Where an event was logged
+struct EventContext<'a> {
+    outcome: &'a Outcome,
+    log_index: usize,
+}
+
+pub struct Indexer {
+    pub db: Db,
+    contract_id: String,
+}
+
+fn to_u128_vec(amounts: &[U128]) -> Vec<u128> {
+    amounts.iter().map(|amount| amount.0).collect()
+}
+
+impl Indexer {
+    pub fn new(db: Db, contract_id: &str) -> Self {
+        Self {
+            db,
+            contract_id: contract_id.to_string(),
+        }
+    }
+
+    /// Index a JSON-lines dump of receipt outcomes, the events already indexed are skipped
+    pub fn index<R: BufRead>(&mut self, reader: R) -> Result<IndexStats> {
+        let mut stats = IndexStats::default();
+        for outcome in read_outcomes(reader) {
+            self.index_outcome(&outcome?, &mut stats)?;
+        }
+        Ok(stats)
+    }
+
+    /// Index the events of one outcome, atomically
+    pub fn index_outcome(&mut self, outcome: &Outcome, stats: &mut IndexStats) -> Result<()> {
+        stats.outcomes += 1;
+        if outcome.executor_id != self.contract_id || !outcome.status.is_success() {
+            stats.skipped_outcomes += 1;
+            return Ok(());
+        }
+
+        let tx = self.db.conn.unchecked_transaction()?;
+        for (log_index, log) in outcome.logs.iter().enumerate() {
+            let raw = match RawEvent::from_log(log)? {
+                Some(raw) => raw,
+                None => continue,
+            };
+            let inserted = self.db.conn.execute(
+                "INSERT OR IGNORE INTO events
+                 (receipt_id, log_index, block_height, block_timestamp, event, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    outcome.receipt_id,
+                    log_index,
+                    outcome.block_height,
+                    outcome.block_timestamp,
+                    raw.event,
+                    raw.data.to_string()
+                ],
+            )?;
+            if inserted == 0 {
+                stats.duplicate_events += 1;
+                continue;
+            }
+            let ctx = EventContext { outcome, log_index };
+            self.apply(&raw.decode()?, &ctx)?;
+            stats.events += 1;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn apply(&self, event: &WelottEvent, ctx: &EventContext) -> Result<()> {
+        let conn = &self.db.conn;
+        let block_height = ctx.outcome.block_height;
+        match event {
+            WelottEvent::StartLottery(data) => {
+                for start in data {
+                    conn.execute(
+                        "INSERT OR REPLACE INTO lotteries
+                         (lottery_id, status, start_time, end_time, price_ticket_in_near,
+                          discount_divisor, rewards_breakdown, reserve_fee, operate_fee,
+                          config_version, first_ticket_id, first_ticket_id_next_lottery,
+                          amount_collected_in_near, started_at_block)
+                         VALUES (?1, 'Open', ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10, ?11, ?12)",
+                        params![
+                            start.lottery_id,
+                            start.start_time,
+                            start.end_time,
+                            start.price_ticket_in_near.0.to_string(),
+                            start.discount_divisor.0.to_string(),
+                            db::amounts_to_json(&to_u128_vec(&start.rewards_breakdown)),
+                            start.reserve_fee.0.to_string(),
+                            start.operate_fee.0.to_string(),
+                            start.config_version,
+                            start.first_ticket_id,
+                            start.amount_collected_in_near.0.to_string(),
+                            block_height
+                        ],
+                    )?;
+                    self.db
+                        .set_state(STATE_RESERVE_BALANCE, &start.reserve_balance.0.to_string())?;
+                }
+            }
+            WelottEvent::BuyTickets(data) => {
+                for buy in data {
+                    for (ticket_id, number) in buy.ticket_ids.iter().zip(&buy.ticket_numbers) {
+                        conn.execute(
+                            "INSERT OR REPLACE INTO tickets
+                             (ticket_id, lottery_id, owner_id, number, bought_at_block)
+                             VALUES (?1, ?2, ?3, ?4, ?5)",
+                            params![ticket_id, buy.lottery_id, buy.buyer, number, block_height],
+                        )?;
+                    }
+                    if let Some(last_ticket_id) = buy.ticket_ids.iter().max() {
+                        conn.execute(
+                            "UPDATE lotteries SET first_ticket_id_next_lottery = ?2
+                             WHERE lottery_id = ?1",
+                            params![buy.lottery_id, last_ticket_id + 1],
+                        )?;
+                    }
+                    self.add_lottery_amount(
+                        buy.lottery_id,
+                        "amount_collected_in_near",
+                        buy.amount.0,
+                    )?;
+                    self.ensure_account(&buy.buyer)?;
+                    conn.execute(
+                        "UPDATE accounts SET tickets_bought = tickets_bought + ?2
+                         WHERE account_id = ?1",
+                        params![buy.buyer, buy.ticket_ids.len()],
+                    )?;
+                    self.add_account_amount(&buy.buyer, "amount_spent", buy.amount.0)?;
+                }
+            }
+            WelottEvent::CloseLottery(data) => {
+                for close in data {
+                    conn.execute(
+                        "UPDATE lotteries SET status = 'Close', first_ticket_id_next_lottery = ?2
+                         WHERE lottery_id = ?1",
+                        params![close.lottery_id, close.first_ticket_id_next_lottery],
+                    )?;
+                }
+            }
+            WelottEvent::DrawLottery(data) => {
+                for draw in data {
+                    conn.execute(
+                        "UPDATE lotteries SET status = 'Claimable', final_number = ?2,
+                         amount_collected_in_near = ?3, near_per_bracket = ?4,
+                         count_winners_per_bracket = ?5, amount_to_treasury = ?6,
+                         jackpot_top_up = ?7, drawn_at_block = ?8
+                         WHERE lottery_id = ?1",
+                        params![
+                            draw.lottery_id,
+                            draw.final_number,
+                            draw.amount_collected_in_near.0.to_string(),
+                            db::amounts_to_json(&to_u128_vec(&draw.near_per_bracket)),
+                            db::amounts_to_json(&to_u128_vec(&draw.count_winners_per_bracket)),
+                            draw.amount_to_treasury.0.to_string(),
+                            draw.jackpot_top_up.0.to_string(),
+                            block_height
+                        ],
+                    )?;
+                    self.db
+                        .set_state(STATE_RESERVE_BALANCE, &draw.reserve_balance.0.to_string())?;
+                }
+            }
+            WelottEvent::ClaimTickets(data) => {
+                for (index, claim) in data.iter().enumerate() {
+                    conn.execute(
+                        "INSERT OR REPLACE INTO claims
+                         (receipt_id, log_index, data_index, claimer_id, lottery_id, ticket_count,
+                          amount, block_height)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                        params![
+                            ctx.outcome.receipt_id,
+                            ctx.log_index,
+                            index,
+                            claim.claimer,
+                            claim.lottery_id,
+                            claim.ticket_ids.len(),
+                            claim.amount.0.to_string(),
+                            block_height
+                        ],
+                    )?;
+                    for ((ticket_id, bracket), reward) in claim
+                        .ticket_ids
+                        .iter()
+                        .zip(&claim.brackets)
+                        .zip(&claim.rewards)
+                    {
+                        conn.execute(
+                            "UPDATE tickets SET bracket = ?2, reward = ?3, claimed_at_block = ?4
+                             WHERE ticket_id = ?1",
+                            params![ticket_id, bracket, reward.0.to_string(), block_height],
+                        )?;
+                    }
+                    self.add_lottery_amount(claim.lottery_id, "amount_claimed", claim.amount.0)?;
+                    self.ensure_account(&claim.claimer)?;
+                    conn.execute(
+                        "UPDATE accounts SET tickets_won = tickets_won + ?2
+                         WHERE account_id = ?1",
+                        params![claim.claimer, claim.ticket_ids.len()],
+                    )?;
+                    self.add_account_amount(&claim.claimer, "amount_claimed", claim.amount.0)?;
+                }
+            }
+            WelottEvent::InjectFunds(data) | WelottEvent::InjectReserveFunds(data) => {
+                for inject in data {
+                    if let Some(lottery_id) = inject.lottery_id {
+                        self.add_lottery_amount(
+                            lottery_id,
+                            "amount_collected_in_near",
+                            inject.amount.0,
+                        )?;
+                    }
+                    self.db
+                        .set_state(STATE_RESERVE_BALANCE, &inject.reserve_balance.0.to_string())?;
+                }
+            }
+            WelottEvent::StorageDeposit(data) | WelottEvent::StorageWithdraw(data) => {
+                for storage in data {
+                    self.ensure_account(&storage.account_id)?;
+                    conn.execute(
+                        "UPDATE accounts SET storage_balance = ?2 WHERE account_id = ?1",
+                        params![storage.account_id, storage.storage_balance.0.to_string()],
+                    )?;
+                }
+            }
+            WelottEvent::ApplyConfigLottery(data) => {
+                for apply in data {
+                    self.db
+                        .set_state(STATE_CONFIG_VERSION, &apply.version.to_string())?;
+                }
+            }
+            WelottEvent::SetFeeRecipients(data) => {
+                for set in data {
+                    let fee_recipients: Vec<(&str, u64)> = set
+                        .fee_recipients
+                        .iter()
+                        .map(|recipient| (recipient.account_id.as_str(), recipient.share))
+                        .collect();
+                    self.db.set_state(
+                        STATE_FEE_RECIPIENTS,
+                        &serde_json::to_string(&fee_recipients)?,
+                    )?;
+                }
+            }
+            // the reserve is released to the owner
+            WelottEvent::EnterEmergency(data) => {
+                for emergency in data {
+                    self.db.set_state(
+                        STATE_RESERVE_BALANCE,
+                        &emergency.reserve_balance.0.to_string(),
+                    )?;
+                }
+            }
+            // the timelocked changes are only kept in the events table
+            WelottEvent::QueueChange(_)
+            | WelottEvent::ExecuteChange(_)
+            | WelottEvent::CancelChange(_) => {}
+            _ => {
+                if let Some((flag, paused)) = events::pause_change(event) {
+                    self.db
+                        .set_state(&format!("paused:{}", flag), &paused.to_string())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn ensure_account(&self, account_id: &str) -> Result<()> {
+        self.db.conn.execute(
+            "INSERT OR IGNORE INTO accounts (account_id) VALUES (?1)",
+            [account_id],
+        )?;
+        Ok(())
+    }
+
+    fn add_account_amount(&self, account_id: &str, column: &str, amount: u128) -> Result<()> {
+        self.add_amount("accounts", "account_id", &account_id, column, amount)
+    }
+
+    fn add_lottery_amount(&self, lottery_id: u32, column: &str, amount: u128) -> Result<()> {
+        self.add_amount("lotteries", "lottery_id", &lottery_id, column, amount)
+    }
+
+    /// SQLite has no 128 bits integers, the TEXT amounts are added here
+    fn add_amount(
+        &self,
+        table: &str,
+        key_column: &str,
+        key: &dyn rusqlite::ToSql,
+        column: &str,
+        amount: u128,
+    ) -> Result<()> {
+        let current: Option<String> = self
+            .db
+            .conn
+            .query_row(
+                &format!("SELECT {} FROM {} WHERE {} = ?1", column, table, key_column),
+                [key],
+                |row| row.get(0),
+            )
+            .optional()?;
+        // events of a lottery started before the dump are not materialised
+        if let Some(current) = current {
+            let total = db::parse_amount(&current)? + amount;
+            self.db.conn.execute(
+                &format!(
+                    "UPDATE {} SET {} = ?2 WHERE {} = ?1",
+                    table, column, key_column
+                ),
+                params![key, total.to_string()],
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+//! Off-chain indexer of the Welott contract. It reads a JSON-lines dump of receipt outcomes,
+//! parses the `EVENT_JSON` logs of the contract and materialises the lotteries, tickets,
+//! claims and per-account stats into SQLite.
+mod check;
+mod db;
+mod error;
+mod events;
+mod indexer;
+mod outcome;
+
+pub use crate::check::*;
+pub use crate::db::*;
+pub use crate::error::*;
+pub use crate::events::*;
+pub use crate::indexer::*;
+pub use crate::outcome::*;
@@ -0,0 +1,46 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+use welott_indexer::{cross_check, Db, Indexer, Result, ViewsDump};
+
+const USAGE: &str = "usage: welott-indexer <contract_id> <outcomes.jsonl> <db.sqlite> [views.json]";
+
+fn run(args: &[String]) -> Result<bool> {
+    let (contract_id, outcomes_path, db_path) = (&args[1], &args[2], &args[3]);
+    let mut indexer = Indexer::new(Db::open(db_path)?, contract_id);
+    let stats = indexer.index(BufReader::new(File::open(outcomes_path)?))?;
+    println!(
+        "outcomes: {}, skipped: {}, events: {}, already indexed: {}",
+        stats.outcomes, stats.skipped_outcomes, stats.events, stats.duplicate_events
+    );
+
+    let views_path = match args.get(4) {
+        Some(views_path) => views_path,
+        None => return Ok(true),
+    };
+    let views = ViewsDump::from_reader(BufReader::new(File::open(views_path)?))?;
+    let mismatches = cross_check(&indexer.db, &views)?;
+    for mismatch in &mismatches {
+        println!("mismatch: {}", mismatch);
+    }
+    if mismatches.is_empty() {
+        println!("cross-check: indexed state matches the contract views");
+    }
+    Ok(mismatches.is_empty())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 4 || args.len() > 5 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+    match run(&args) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Receipt outcomes of the JSON-lines dump, one outcome per line
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::BufRead;
+
+/// Status of an execution outcome, as returned by the NEAR RPC
+#[derive(Debug, Clone, Deserialize)]
+pub enum ExecutionStatus {
+    Unknown,
+    Failure(Value),
+    SuccessValue(String),
+    SuccessReceiptId(String),
+}
+
+impl ExecutionStatus {
+    pub fn is_success(&self) -> bool {
+        matches!(
+            self,
+            ExecutionStatus::SuccessValue(_) | ExecutionStatus::SuccessReceiptId(_)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Outcome {
+    pub block_height: u64,
+    pub block_timestamp: u64,
+    pub receipt_id: String,
+    pub predecessor_id: String,
+    pub executor_id: String,
+    #[serde(default)]
+    pub method_name: Option<String>,
+    pub status: ExecutionStatus,
+    pub logs: Vec<String>,
+}
+
+/// Read the outcomes of a JSON-lines dump, skipping the blank lines
+pub fn read_outcomes<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Outcome>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| match line {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(
+                serde_json::from_str(&line).map_err(|source| Error::InvalidOutcome {
+                    line: index + 1,
+                    source,
+                }),
+            ),
+            Err(err) => Some(Err(err.into())),
+        })
+}
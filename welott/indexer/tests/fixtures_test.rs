@@ -0,0 +1,161 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Cursor};
+use welott_indexer::*;
+
+const CONTRACT_ID: &str = "welott.testnet";
+const ONE_NEAR: u128 = 10u128.pow(24);
+
+fn fixture(name: &str) -> BufReader<File> {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    BufReader::new(File::open(path).unwrap())
+}
+
+fn index_fixture() -> (Indexer, IndexStats) {
+    let mut indexer = Indexer::new(Db::open_in_memory().unwrap(), CONTRACT_ID);
+    let stats = indexer.index(fixture("outcomes.jsonl")).unwrap();
+    (indexer, stats)
+}
+
+fn views_fixture() -> ViewsDump {
+    ViewsDump::from_reader(fixture("views.json")).unwrap()
+}
+
+#[test]
+fn test_index_outcomes() {
+    let (indexer, stats) = index_fixture();
+    assert_eq!(
+        stats,
+        IndexStats {
+            outcomes: 17,
+            // the failed buy and the other contract
+            skipped_outcomes: 2,
+            events: 15,
+            duplicate_events: 0,
+        }
+    );
+
+    let lottery = indexer.db.lottery(1).unwrap().unwrap();
+    assert_eq!(lottery.status, "Claimable");
+    assert_eq!(lottery.final_number, Some(1567891));
+    assert_eq!(lottery.tickets_sold, 3);
+    assert_eq!(lottery.first_ticket_id_next_lottery, 3);
+    // 2 NEAR injected and 3 tickets with the discount
+    assert_eq!(lottery.amount_collected_in_near, 4_999 * ONE_NEAR / 1000);
+    assert_eq!(
+        lottery.count_winners_per_bracket,
+        Some(vec![0, 0, 1, 0, 0, 1])
+    );
+    assert_eq!(
+        lottery.amount_claimed,
+        1_899_620_000_000_000_000_000_000 + 284_943_000_000_000_000_000_000
+    );
+
+    let lottery = indexer.db.lottery(2).unwrap().unwrap();
+    assert_eq!(lottery.status, "Open");
+    assert_eq!(lottery.final_number, None);
+    assert_eq!(lottery.tickets_sold, 1);
+
+    let eugene = indexer.db.account("eugene").unwrap().unwrap();
+    assert_eq!(eugene.tickets_bought, 2);
+    assert_eq!(eugene.tickets_won, 1);
+    assert_eq!(eugene.amount_spent, 1_999 * ONE_NEAR / 1000);
+    assert_eq!(eugene.amount_claimed, 1_899_620_000_000_000_000_000_000);
+    assert_eq!(eugene.storage_balance, ONE_NEAR / 10);
+
+    let fargo = indexer.db.account("fargo").unwrap().unwrap();
+    assert_eq!(fargo.tickets_bought, 2);
+    assert_eq!(fargo.amount_spent, 2 * ONE_NEAR);
+    assert_eq!(fargo.amount_claimed, 284_943_000_000_000_000_000_000);
+
+    let (bracket, reward): (u32, String) = indexer
+        .db
+        .conn
+        .query_row(
+            "SELECT bracket, reward FROM tickets WHERE ticket_id = 0",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(bracket, 5);
+    assert_eq!(reward, "1899620000000000000000000");
+
+    assert_eq!(indexer.db.reserve_balance().unwrap(), 5 * ONE_NEAR);
+    assert!(indexer.db.is_paused("claim").unwrap());
+    assert!(!indexer.db.is_paused("buy").unwrap());
+}
+
+#[test]
+fn test_cross_check_fixture() {
+    let (indexer, _) = index_fixture();
+    assert_eq!(cross_check(&indexer.db, &views_fixture()).unwrap(), vec![]);
+}
+
+#[test]
+fn test_index_twice() {
+    let (mut indexer, _) = index_fixture();
+    let stats = indexer.index(fixture("outcomes.jsonl")).unwrap();
+    assert_eq!(stats.events, 0);
+    assert_eq!(stats.duplicate_events, 15);
+
+    let fargo = indexer.db.account("fargo").unwrap().unwrap();
+    assert_eq!(fargo.tickets_bought, 2);
+    assert_eq!(cross_check(&indexer.db, &views_fixture()).unwrap(), vec![]);
+}
+
+#[test]
+fn test_cross_check_missing_outcome() {
+    // drop the ticket bought in the second lottery
+    let outcomes: Vec<String> = fixture("outcomes.jsonl")
+        .lines()
+        .map(|line| line.unwrap())
+        .filter(|line| !line.contains(r#""receipt_id":"receipt-113""#))
+        .collect();
+    let mut indexer = Indexer::new(Db::open_in_memory().unwrap(), CONTRACT_ID);
+    indexer.index(Cursor::new(outcomes.join("\n"))).unwrap();
+
+    let mismatches = cross_check(&indexer.db, &views_fixture()).unwrap();
+    let fields: Vec<(Option<LotteryId>, &str)> = mismatches
+        .iter()
+        .map(|mismatch| (mismatch.lottery_id, mismatch.field))
+        .collect();
+    assert_eq!(
+        fields,
+        vec![
+            (Some(2), "first_ticket_id_next_lottery"),
+            (Some(2), "tickets_sold"),
+            (Some(2), "amount_collected_in_near"),
+        ]
+    );
+}
+
+#[test]
+fn test_cross_check_after_enter_emergency() {
+    let (mut indexer, _) = index_fixture();
+    let outcome = r#"{"block_height":116,"block_timestamp":162615612350679,"receipt_id":"receipt-116","predecessor_id":"alice","executor_id":"welott.testnet","method_name":"enter_emergency","status":{"SuccessValue":""},"logs":["EVENT_JSON:{\"standard\":\"welott\",\"version\":\"1.0.0\",\"event\":\"enter_emergency\",\"data\":[{\"sender\":\"alice\",\"frozen_lottery_ids\":[2],\"amount_to_treasury\":\"0\",\"reserve_balance\":\"0\"}]}"]}"#;
+    let stats = indexer.index(Cursor::new(outcome)).unwrap();
+    assert_eq!(stats.events, 1);
+    assert_eq!(indexer.db.reserve_balance().unwrap(), 0);
+
+    // the reserve is released, the contract views it as zero
+    let mut views = views_fixture();
+    views.reserve_balance = U128(0);
+    assert_eq!(cross_check(&indexer.db, &views).unwrap(), vec![]);
+}
+
+#[test]
+fn test_plain_and_foreign_logs() {
+    assert!(RawEvent::from_log(r#"{"type":"buy_tickets","params":{}}"#)
+        .unwrap()
+        .is_none());
+    assert!(RawEvent::from_log(
+        r#"EVENT_JSON:{"standard":"nep171","version":"1.0.0","event":"nft_mint","data":[]}"#
+    )
+    .unwrap()
+    .is_none());
+    assert!(matches!(
+        RawEvent::from_log(
+            r#"EVENT_JSON:{"standard":"welott","version":"2.0.0","event":"buy_tickets","data":[]}"#
+        ),
+        Err(Error::UnsupportedVersion(_))
+    ));
+}
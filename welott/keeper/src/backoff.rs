@@ -0,0 +1,30 @@
+//! Delays between the attempts of a failing call, doubling up to a maximum
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(5),
+            max: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before the next attempt, after `failures` failed attempts in a row
+    pub fn delay(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::from_secs(0);
+        }
+        let factor = 2u32.saturating_pow(failures - 1);
+        self.initial
+            .checked_mul(factor)
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
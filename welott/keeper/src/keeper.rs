@@ -0,0 +1,215 @@
+//! Runs the lottery rounds: closes the open lottery once its end time has passed, draws it with
+//! auto-injection and starts the next one. The chain is the source of truth, every tick plans
+//! the next step from the views so a step sent twice fails in the contract instead of repeating
+use crate::backoff::Backoff;
+use crate::error::Result;
+use crate::state::{Action, KeeperState, StateFile, Step};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use welott_client::{Lottery, LotteryId, Status, Transport, WelottClient};
+
+pub trait Clock {
+    /// Unix time
+    fn now(&self) -> Duration;
+    fn sleep(&self, duration: Duration);
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration)
+    }
+}
+
+/// Next step of the rounds from the latest lottery and the block time in ns
+pub fn plan(latest_lottery_id: LotteryId, lottery: Option<&Lottery>, block_timestamp: u64) -> Plan {
+    let lottery = match lottery {
+        Some(lottery) if latest_lottery_id != 0 => lottery,
+        _ => {
+            return Plan::Step(Step {
+                action: Action::Start,
+                lottery_id: latest_lottery_id + 1,
+            })
+        }
+    };
+    let step = |action| {
+        Plan::Step(Step {
+            action,
+            lottery_id: lottery.lottery_id,
+        })
+    };
+    match lottery.status {
+        Status::Open if block_timestamp >= lottery.end_time => step(Action::Close),
+        Status::Open => Plan::WaitUntil(lottery.end_time),
+        Status::Close => step(Action::Draw),
+        Status::Claimable => Plan::Step(Step {
+            action: Action::Start,
+            lottery_id: lottery.lottery_id + 1,
+        }),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plan {
+    Step(Step),
+    /// Wait for the block time in ns when the open lottery can be closed
+    WaitUntil(u64),
+}
+
+/// Outcome of a tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tick {
+    Done(Step),
+    /// The step failed, it is retried after the backoff delay
+    Failed {
+        step: Step,
+        error: String,
+        retry_in: Duration,
+    },
+    /// Nothing to do until the lottery ends or the backoff delay passes
+    Wait(Duration),
+    /// The rounds are paused by the contract
+    Paused,
+    /// The contract is shut down, the keeper has nothing left to do
+    Shutdown,
+}
+
+pub struct Keeper<T, C> {
+    client: WelottClient<T>,
+    state_file: StateFile,
+    clock: C,
+    pub backoff: Backoff,
+    // longest wait between two ticks
+    pub poll_interval: Duration,
+}
+
+impl<T: Transport, C: Clock> Keeper<T, C> {
+    pub fn new(client: WelottClient<T>, state_file: StateFile, clock: C) -> Self {
+        Self {
+            client,
+            state_file,
+            clock,
+            backoff: Backoff::default(),
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn state(&self) -> Result<KeeperState> {
+        self.state_file.load()
+    }
+
+    /// Plan and send the next step. The views failing are returned as errors, the failing
+    /// step is retried with backoff
+    pub fn tick(&mut self) -> Result<Tick> {
+        let mut state = self.state_file.load()?;
+        let now_ms = self.clock.now().as_millis() as u64;
+        if state.retry_at > now_ms {
+            return Ok(Tick::Wait(Duration::from_millis(state.retry_at - now_ms)));
+        }
+
+        let client = &self.client;
+        if client.view_emergency().send()?.is_some() {
+            return Ok(Tick::Shutdown);
+        }
+        if client.view_pause_flags().send()?.draw {
+            return Ok(Tick::Paused);
+        }
+        let latest_lottery_id = client.view_latest_lottery_id().send()?;
+        let lottery = match latest_lottery_id {
+            0 => None,
+            lottery_id => Some(client.view_lottery(lottery_id).send()?),
+        };
+        let block_timestamp = match &lottery {
+            Some(lottery) if lottery.status == Status::Open => {
+                client.get_current_timestamp().send()?
+            }
+            _ => 0,
+        };
+
+        let step = match plan(latest_lottery_id, lottery.as_ref(), block_timestamp) {
+            Plan::Step(step) => step,
+            Plan::WaitUntil(end_time) => {
+                let wait = Duration::from_nanos(end_time.saturating_sub(block_timestamp));
+                return Ok(Tick::Wait(wait.min(self.poll_interval)));
+            }
+        };
+        // a different step means the failing one went through or is no longer needed
+        if state.in_flight != Some(step) {
+            state.failures = 0;
+        }
+        state.in_flight = Some(step);
+        self.state_file.save(&state)?;
+
+        let result = match step.action {
+            Action::Start => client.start_lottery().send(),
+            Action::Close => client.close_lottery().send(),
+            Action::Draw => client
+                .draw_final_number_and_make_lottery_claimable(step.lottery_id, true)
+                .send(),
+        };
+        let tick = match result {
+            Ok(_) => {
+                state.last_step = Some(step);
+                state.in_flight = None;
+                state.failures = 0;
+                state.retry_at = 0;
+                state.last_error = None;
+                Tick::Done(step)
+            }
+            Err(err) => {
+                state.failures += 1;
+                let retry_in = self.backoff.delay(state.failures);
+                state.retry_at = now_ms + retry_in.as_millis() as u64;
+                state.last_error = Some(err.to_string());
+                Tick::Failed {
+                    step,
+                    error: err.to_string(),
+                    retry_in,
+                }
+            }
+        };
+        self.state_file.save(&state)?;
+        Ok(tick)
+    }
+
+    /// Tick until the contract is shut down, reporting every tick
+    pub fn run<F: FnMut(&Result<Tick>)>(&mut self, mut report: F) {
+        let mut view_failures = 0;
+        loop {
+            let tick = self.tick();
+            report(&tick);
+            let wait = match &tick {
+                Ok(Tick::Shutdown) => return,
+                Ok(Tick::Done(_)) => Duration::from_secs(0),
+                Ok(Tick::Failed { retry_in, .. }) => *retry_in,
+                Ok(Tick::Wait(wait)) => *wait,
+                Ok(Tick::Paused) => self.poll_interval,
+                // the node or the network is down, back off as for a failing step
+                Err(_) => {
+                    view_failures += 1;
+                    self.backoff.delay(view_failures)
+                }
+            };
+            if tick.is_ok() {
+                view_failures = 0;
+            }
+            self.clock.sleep(wait);
+        }
+    }
+}
@@ -0,0 +1,10 @@
+//! Keeper of the Welott contract, running the lottery rounds without an operator
+mod backoff;
+mod error;
+mod keeper;
+mod state;
+
+pub use crate::backoff::*;
+pub use crate::error::*;
+pub use crate::keeper::*;
+pub use crate::state::*;
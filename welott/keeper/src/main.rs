@@ -0,0 +1,61 @@
+use clap::Parser;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+use welott_cli::Config;
+use welott_client::{JsonRpcTransport, Signer, WelottClient};
+use welott_keeper::{Keeper, StateFile, SystemClock, Tick};
+
+/// Run the lottery rounds of the contract, signed by the keeper account of a profile
+#[derive(Parser)]
+#[clap(name = "welott-keeper", version)]
+struct Args {
+    /// Config file with the network profiles
+    #[clap(long, default_value = "welott.toml")]
+    config: PathBuf,
+    #[clap(long, short, default_value = "testnet")]
+    profile: String,
+    /// Local state of the keeper, kept across restarts
+    #[clap(long, default_value = "welott-keeper.json")]
+    state: PathBuf,
+    /// Longest wait between two ticks, in seconds
+    #[clap(long, default_value = "30")]
+    poll_interval: u64,
+}
+
+fn main() {
+    let args = Args::parse();
+    let client = Config::load(&args.config)
+        .and_then(|config| {
+            let profile = config.profile(&args.profile)?.clone();
+            let signer =
+                Signer::from_credentials_file(profile.credentials_path(&profile.signer_id))?;
+            let transport = JsonRpcTransport::new(&profile.rpc_url).with_signer(signer);
+            Ok(WelottClient::new(profile.contract_id, transport))
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(1);
+        });
+
+    let mut keeper = Keeper::new(client, StateFile::new(&args.state), SystemClock);
+    keeper.poll_interval = Duration::from_secs(args.poll_interval);
+    keeper.run(|tick| match tick {
+        Ok(Tick::Done(step)) => println!("{:?} lottery {}", step.action, step.lottery_id),
+        Ok(Tick::Failed {
+            step,
+            error,
+            retry_in,
+        }) => eprintln!(
+            "{:?} lottery {} failed, retrying in {}s: {}",
+            step.action,
+            step.lottery_id,
+            retry_in.as_secs(),
+            error
+        ),
+        Ok(Tick::Paused) => println!("rounds are paused"),
+        Ok(Tick::Shutdown) => println!("the contract is shut down, stopping"),
+        Ok(Tick::Wait(_)) => {}
+        Err(err) => eprintln!("error: {}", err),
+    });
+}
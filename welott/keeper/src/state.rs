@@ -0,0 +1,61 @@
+//! Local state of the keeper, saved after every step so a restart neither resets the backoff
+//! nor forgets a call that may have been sent
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use welott_client::LotteryId;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Start,
+    Close,
+    Draw,
+}
+
+/// Action on a lottery, the one to start for `Start`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub action: Action,
+    pub lottery_id: LotteryId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeeperState {
+    // last step confirmed by its outcome
+    pub last_step: Option<Step>,
+    // step sent without an outcome yet, the chain tells on restart whether it went through
+    pub in_flight: Option<Step>,
+    // failed attempts in a row of the next step
+    pub failures: u32,
+    // unix time in ms before which the failing step is not retried
+    pub retry_at: u64,
+    pub last_error: Option<String>,
+}
+
+/// JSON file of the state, replaced atomically
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Saved state, the initial one if the keeper never ran
+    pub fn load(&self) -> Result<KeeperState> {
+        if !self.path.exists() {
+            return Ok(KeeperState::default());
+        }
+        Ok(serde_json::from_str(&std::fs::read_to_string(&self.path)?)?)
+    }
+
+    pub fn save(&self, state: &KeeperState) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(state)?)?;
+        std::fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+use near_sdk::AccountId;
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::time::Duration;
+use welott_client::{Lottery, MockTransport, PauseFlags, RecordedRequest, Status, WelottClient};
+use welott_keeper::*;
+
+const SECOND_NS: u64 = 1_000_000_000;
+
+/// Clock moving only when the keeper sleeps
+struct FakeClock(Cell<Duration>);
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration)
+    }
+}
+
+fn state_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "welott-keeper-{}-{}.json",
+        name,
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn keeper<'a>(
+    transport: &'a MockTransport,
+    state_path: &PathBuf,
+    clock: &'a FakeClock,
+) -> Keeper<&'a MockTransport, &'a FakeClock> {
+    let contract_id: AccountId = "welott.testnet".parse().unwrap();
+    Keeper::new(
+        WelottClient::new(contract_id, transport),
+        StateFile::new(state_path),
+        clock,
+    )
+}
+
+fn lottery(lottery_id: u32, status: Status, end_time: u64) -> Lottery {
+    Lottery {
+        lottery_id,
+        status,
+        end_time,
+        ..Default::default()
+    }
+}
+
+/// Queue the views of one tick
+fn chain(transport: &MockTransport, lottery: Option<Lottery>, block_timestamp: u64) {
+    transport.respond("view_emergency", &());
+    transport.respond("view_pause_flags", &PauseFlags::default());
+    transport.respond(
+        "view_latest_lottery_id",
+        &lottery.as_ref().map_or(0, |lottery| lottery.lottery_id),
+    );
+    if let Some(lottery) = lottery {
+        let open = lottery.status == Status::Open;
+        transport.respond("view_lottery", &lottery);
+        if open {
+            transport.respond("get_current_timestamp", &block_timestamp);
+        }
+    }
+}
+
+fn calls(transport: &MockTransport) -> Vec<String> {
+    transport
+        .requests()
+        .iter()
+        .filter_map(|request| match request {
+            RecordedRequest::Call { request, .. } => {
+                Some(format!("{} {}", request.method_name, request.args_str()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn step(action: Action, lottery_id: u32) -> Step {
+    Step { action, lottery_id }
+}
+
+#[test]
+fn test_plan() {
+    assert_eq!(plan(0, None, 0), Plan::Step(step(Action::Start, 1)));
+    let open = lottery(3, Status::Open, 100 * SECOND_NS);
+    assert_eq!(
+        plan(3, Some(&open), 40 * SECOND_NS),
+        Plan::WaitUntil(100 * SECOND_NS)
+    );
+    assert_eq!(
+        plan(3, Some(&open), 100 * SECOND_NS),
+        Plan::Step(step(Action::Close, 3))
+    );
+    assert_eq!(
+        plan(3, Some(&lottery(3, Status::Close, 0)), 0),
+        Plan::Step(step(Action::Draw, 3))
+    );
+    assert_eq!(
+        plan(3, Some(&lottery(3, Status::Claimable, 0)), 0),
+        Plan::Step(step(Action::Start, 4))
+    );
+}
+
+#[test]
+fn test_backoff() {
+    let backoff = Backoff {
+        initial: Duration::from_secs(5),
+        max: Duration::from_secs(60),
+    };
+    assert_eq!(backoff.delay(0), Duration::from_secs(0));
+    assert_eq!(backoff.delay(1), Duration::from_secs(5));
+    assert_eq!(backoff.delay(3), Duration::from_secs(20));
+    assert_eq!(backoff.delay(5), Duration::from_secs(60));
+    assert_eq!(backoff.delay(100), Duration::from_secs(60));
+}
+
+#[test]
+fn test_full_round() {
+    let transport = MockTransport::new();
+    let clock = FakeClock(Cell::new(Duration::from_secs(1_000)));
+    let path = state_path("round");
+    let mut keeper = keeper(&transport, &path, &clock);
+
+    chain(&transport, None, 0);
+    assert_eq!(keeper.tick().unwrap(), Tick::Done(step(Action::Start, 1)));
+
+    // the wait is capped by the poll interval
+    chain(
+        &transport,
+        Some(lottery(1, Status::Open, 100 * SECOND_NS)),
+        0,
+    );
+    assert_eq!(keeper.tick().unwrap(), Tick::Wait(keeper.poll_interval));
+    chain(
+        &transport,
+        Some(lottery(1, Status::Open, 100 * SECOND_NS)),
+        90 * SECOND_NS,
+    );
+    assert_eq!(keeper.tick().unwrap(), Tick::Wait(Duration::from_secs(10)));
+
+    chain(
+        &transport,
+        Some(lottery(1, Status::Open, 100 * SECOND_NS)),
+        100 * SECOND_NS,
+    );
+    assert_eq!(keeper.tick().unwrap(), Tick::Done(step(Action::Close, 1)));
+    chain(&transport, Some(lottery(1, Status::Close, 0)), 0);
+    assert_eq!(keeper.tick().unwrap(), Tick::Done(step(Action::Draw, 1)));
+    chain(&transport, Some(lottery(1, Status::Claimable, 0)), 0);
+    assert_eq!(keeper.tick().unwrap(), Tick::Done(step(Action::Start, 2)));
+
+    assert_eq!(
+        calls(&transport),
+        vec![
+            "start_lottery {}",
+            "close_lottery {}",
+            r#"draw_final_number_and_make_lottery_claimable {"_lottery_id":1,"_auto_injection":true}"#,
+            "start_lottery {}",
+        ]
+    );
+    let state = keeper.state().unwrap();
+    assert_eq!(state.last_step, Some(step(Action::Start, 2)));
+    assert_eq!(state.in_flight, None);
+}
+
+#[test]
+fn test_retry_with_backoff_across_restarts() {
+    let transport = MockTransport::new();
+    let clock = FakeClock(Cell::new(Duration::from_secs(1_000)));
+    let path = state_path("retry");
+
+    chain(&transport, Some(lottery(2, Status::Close, 0)), 0);
+    transport.fail(
+        "draw_final_number_and_make_lottery_claimable",
+        "Exceeded the prepaid gas",
+    );
+    match keeper(&transport, &path, &clock).tick().unwrap() {
+        Tick::Failed {
+            step: failed,
+            error,
+            retry_in,
+        } => {
+            assert_eq!(failed, step(Action::Draw, 2));
+            assert!(error.contains("prepaid gas"));
+            assert_eq!(retry_in, Duration::from_secs(5));
+        }
+        tick => panic!("unexpected tick {:?}", tick),
+    }
+
+    // a restarted keeper keeps waiting for the backoff delay, without any request
+    let requests = transport.requests().len();
+    let mut keeper = keeper(&transport, &path, &clock);
+    assert_eq!(keeper.tick().unwrap(), Tick::Wait(Duration::from_secs(5)));
+    assert_eq!(transport.requests().len(), requests);
+    let state = keeper.state().unwrap();
+    assert_eq!(state.in_flight, Some(step(Action::Draw, 2)));
+    assert_eq!(state.failures, 1);
+
+    // the delay doubles on the next failure
+    clock.sleep(Duration::from_secs(5));
+    chain(&transport, Some(lottery(2, Status::Close, 0)), 0);
+    transport.fail("draw_final_number_and_make_lottery_claimable", "E30");
+    assert!(matches!(
+        keeper.tick().unwrap(),
+        Tick::Failed { retry_in, .. } if retry_in == Duration::from_secs(10)
+    ));
+
+    clock.sleep(Duration::from_secs(10));
+    chain(&transport, Some(lottery(2, Status::Close, 0)), 0);
+    assert_eq!(keeper.tick().unwrap(), Tick::Done(step(Action::Draw, 2)));
+    let state = keeper.state().unwrap();
+    assert_eq!(state.failures, 0);
+    assert_eq!(state.retry_at, 0);
+    assert_eq!(state.last_error, None);
+}
+
+#[test]
+fn test_sent_step_is_not_repeated_after_restart() {
+    let transport = MockTransport::new();
+    let clock = FakeClock(Cell::new(Duration::from_secs(1_000)));
+    let path = state_path("in-flight");
+    // the keeper stopped after sending the close, before its outcome
+    StateFile::new(&path)
+        .save(&KeeperState {
+            in_flight: Some(step(Action::Close, 1)),
+            failures: 2,
+            ..Default::default()
+        })
+        .unwrap();
+
+    // the close went through, the keeper moves on to the draw with a fresh backoff
+    chain(&transport, Some(lottery(1, Status::Close, 0)), 0);
+    transport.fail("draw_final_number_and_make_lottery_claimable", "E30");
+    assert!(matches!(
+        keeper(&transport, &path, &clock).tick().unwrap(),
+        Tick::Failed { retry_in, .. } if retry_in == Duration::from_secs(5)
+    ));
+    assert_eq!(
+        calls(&transport),
+        vec![
+            r#"draw_final_number_and_make_lottery_claimable {"_lottery_id":1,"_auto_injection":true}"#
+        ]
+    );
+}
+
+#[test]
+fn test_paused_and_shutdown() {
+    let transport = MockTransport::new();
+    let clock = FakeClock(Cell::new(Duration::from_secs(1_000)));
+    let path = state_path("paused");
+    let mut keeper = keeper(&transport, &path, &clock);
+
+    transport.respond("view_emergency", &());
+    transport.respond(
+        "view_pause_flags",
+        &PauseFlags {
+            draw: true,
+            ..Default::default()
+        },
+    );
+    assert_eq!(keeper.tick().unwrap(), Tick::Paused);
+
+    let emergency = serde_json::json!({"started_at": 0, "frozen_pots": []});
+    transport.respond("view_emergency", &emergency);
+    assert_eq!(keeper.tick().unwrap(), Tick::Shutdown);
+    // the daemon stops on the shutdown
+    transport.respond("view_emergency", &emergency);
+    keeper.run(|tick| assert!(matches!(tick, Ok(Tick::Shutdown))));
+    assert!(calls(&transport).is_empty());
+}
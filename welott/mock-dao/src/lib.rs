@@ -0,0 +1,132 @@
+//! A mock of a Sputnik DAO, used by the simulation tests to deliver proposals as function calls.
+//! A proposal is executed once a council member approves it.
+use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
+use near_sdk::collections::Vector;
+use near_sdk::json_types::{Base64VecU8, U128, U64};
+use near_sdk::serde::{Deserialize, Serialize};
+use near_sdk::{env, near_bindgen, AccountId, Gas, PanicOnDefault, Promise};
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ActionCall {
+    pub method_name: String,
+    pub args: Base64VecU8,
+    pub deposit: U128,
+    pub gas: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalKind {
+    FunctionCall {
+        receiver_id: AccountId,
+        actions: Vec<ActionCall>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalInput {
+    pub description: String,
+    pub kind: ProposalKind,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalStatus {
+    InProgress,
+    Approved,
+    Rejected,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Action {
+    VoteApprove,
+    VoteReject,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    pub proposer: AccountId,
+    pub description: String,
+    pub kind: ProposalKind,
+    pub status: ProposalStatus,
+}
+
+#[near_bindgen]
+#[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
+pub struct MockDao {
+    council: Vec<AccountId>,
+    proposals: Vector<Proposal>,
+}
+
+#[near_bindgen]
+impl MockDao {
+    #[init]
+    pub fn new(council: Vec<AccountId>) -> Self {
+        assert!(!env::state_exists(), "Already initialized");
+        Self {
+            council,
+            proposals: Vector::new(b"p".to_vec()),
+        }
+    }
+
+    pub fn add_proposal(&mut self, proposal: ProposalInput) -> u64 {
+        let id = self.proposals.len();
+        self.proposals.push(&Proposal {
+            proposer: env::predecessor_account_id(),
+            description: proposal.description,
+            kind: proposal.kind,
+            status: ProposalStatus::InProgress,
+        });
+        id
+    }
+
+    /// The calls of an approved proposal are returned, their failure fails the vote
+    pub fn act_proposal(&mut self, id: u64, action: Action) -> Option<Promise> {
+        assert!(
+            self.council.contains(&env::predecessor_account_id()),
+            "ERR_NOT_COUNCIL"
+        );
+        let mut proposal = self.proposals.get(id).expect("ERR_NO_PROPOSAL");
+        assert_eq!(
+            proposal.status,
+            ProposalStatus::InProgress,
+            "ERR_PROPOSAL_NOT_IN_PROGRESS"
+        );
+
+        let promise = match action {
+            Action::VoteApprove => {
+                proposal.status = ProposalStatus::Approved;
+                let ProposalKind::FunctionCall {
+                    receiver_id,
+                    actions,
+                } = proposal.kind.clone();
+                Some(
+                    actions
+                        .into_iter()
+                        .fold(Promise::new(receiver_id), |promise, action| {
+                            promise.function_call(
+                                action.method_name,
+                                action.args.into(),
+                                action.deposit.0,
+                                Gas(action.gas.0),
+                            )
+                        }),
+                )
+            }
+            Action::VoteReject => {
+                proposal.status = ProposalStatus::Rejected;
+                None
+            }
+        };
+        self.proposals.replace(id, &proposal);
+        promise
+    }
+
+    pub fn get_proposal(&self, id: u64) -> Proposal {
+        self.proposals.get(id).expect("ERR_NO_PROPOSAL")
+    }
+}
@@ -25,6 +25,11 @@ export NEAR_ENV=$NETWORK
 # echo "################ DEPLOY CONTRACT #########################"
 # near deploy $CONTRACT_ACC ../out/nearlott.wasm
 
+# the staging wasm draws the queued final numbers first, then falls back to the VRF
+# near deploy $CONTRACT_ACC ../out/nearlott_staging.wasm
+# near call $CONTRACT_ACC --accountId=$OWNER queue_final_numbers '{"_final_numbers": [1327419]}' --depositYocto=1
+# near view $CONTRACT_ACC view_queued_final_numbers ''
+
 # echo "################# INIT CONTRACT #########################"
 #  near call $CONTRACT_ACC --accountId=$OWNER new '{
 #      "owner_id":"'$OWNER'",
@@ -37,7 +42,13 @@ export NEAR_ENV=$NETWORK
 #         "discount_divisor": "0",
 #         "rewards_breakdown": [125, 375, 750, 1250, 2500, 5000],
 #         "reserve_fee": "2200",
-#         "operate_fee": "500"
+#         "operate_fee": "500",
+#         "max_tickets_per_account": 120,
+#         "max_tickets_per_lottery": 10000,
+#         "min_jackpot": "0",
+#         "fixed_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#         "max_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#         "overflow_policy": "RollOver"
 #      }
 #  }'
 
@@ -51,12 +62,37 @@ export NEAR_ENV=$NETWORK
 #     "discount_divisor": "0",
 #     "rewards_breakdown": [125, 375, 750, 1250, 2500, 5000],
 #     "reserve_fee": "2200",
-#     "operate_fee": "500"
+#     "operate_fee": "500",
+#     "max_tickets_per_account": 120,
+#     "max_tickets_per_lottery": 10000,
+#     "min_jackpot": "0",
+#     "fixed_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#     "max_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#     "overflow_policy": "RollOver"
 #     }
 # }'
 
+# the config is queued, it can be executed by anyone once the timelock delay has passed
+# near view $CONTRACT_ACC view_pending_changes ''
+# near call $CONTRACT_ACC --accountId=$OWNER execute_change '{"_change_id": 0}'
+
 #  near view $CONTRACT_ACC view_config_lottery ''
 
+# near call $CONTRACT_ACC --accountId=$OWNER set_fee_recipients '{
+#     "_fee_recipients": [
+#         {"account_id": "'$TREASURY_ACC'", "share": 10000}
+#     ]
+# }' --depositYocto=1
+
+# the fee recipients are a multisig proposal, executed once the threshold of signers approved it
+# near view $CONTRACT_ACC view_multisig_signers ''
+# near view $CONTRACT_ACC view_multisig_proposals '{"_cursor": 0, "_size": 50}'
+# near call $CONTRACT_ACC --accountId=$SIGNER multisig_approve '{"proposal_id": 0}' --depositYocto=1
+
+# the DAO applies the config lottery between lotteries, by a function call proposal to apply_governance_config
+# near call $CONTRACT_ACC --accountId=$OWNER set_governance '{"governance_id": "'$DAO_ACC'"}' --depositYocto=1
+# near view $CONTRACT_ACC view_config_versions '{"_cursor": 0, "_size": 50}'
+
 # echo "####################### GET CONFIG CONTRACT #########################"
 # near view $CONTRACT_ACC --accountId=$CONTRACT_ACC view_random '' 
 
@@ -11,12 +11,13 @@ OPERATOR_ACC=lamns1.$NETWORK
 
 export NEAR_ENV=$NETWORK
 
-echo "############### CONFIG OPERATOR AND TREASURY AND INJECTOR ADDRESS #####################"
-near call $CONTRACT_ACC --accountId=$OWNER set_operator_and_treasury_and_injector_addresses '{
-    "_injector_address":"'$INJECTOR_ACC'",
-    "_operator_address": "'$OPERATOR_ACC'",
-    "_treasury_address": "'$TREASURY_ACC'"
-}'
+echo "############### CONFIG OPERATOR AND INJECTOR ROLES #####################"
+near call $CONTRACT_ACC --accountId=$OWNER grant_role '{"role":"Operator","account_id":"'$OPERATOR_ACC'"}' --depositYocto 1
+near call $CONTRACT_ACC --accountId=$OWNER grant_role '{"role":"Injector","account_id":"'$INJECTOR_ACC'"}' --depositYocto 1
+
+echo "############### CONFIG TREASURY ADDRESS #####################"
+near call $CONTRACT_ACC --accountId=$OWNER propose_role '{"role":"Treasury","account_id":"'$TREASURY_ACC'"}' --depositYocto 1
+near call $CONTRACT_ACC --accountId=$TREASURY_ACC accept_role '{"role":"Treasury"}' --depositYocto 1
 
 
 echo "####################### GET CONFIG CONTRACT #########################"
@@ -25,9 +26,11 @@ near view $CONTRACT_ACC get_config ''
 
 echo "############### CONFIG MAX NUMBER TICKET BUY #####################"
 
-near call $CONTRACT_ACC --accountId=$OWNER set_max_number_tickets_per_buy '{
-    "_max_number_tickets_per_buy":100
-}'
+# queued behind the timelock, applied by `execute_change` with the returned id
+near call $CONTRACT_ACC --accountId=$OWNER set_config '{
+    "_max_number_tickets_per_buy":100,
+    "_min_discount_divisor":100
+}' --depositYocto 1
 
 echo "####################### GET CONFIG CONTRACT #########################"
 near view $CONTRACT_ACC get_config ''
\ No newline at end of file
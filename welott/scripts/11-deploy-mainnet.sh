@@ -21,7 +21,11 @@ export NEAR_ENV=mainnet
 ../build.sh
 
 # echo "################ DEPLOY CONTRACT #########################"
-near deploy $CONTRACT_ACC ../out/nearlott.wasm 
+# the state is migrated in the same batch, the deploy is rolled back if the migration fails
+near deploy $CONTRACT_ACC ../out/nearlott.wasm --initFunction migrate_state --initArgs '{}'
+
+# the final numbers are drawn by the VRF, should print "vrf"
+near view $CONTRACT_ACC view_randomness_source ''
 
 # echo "####################### GET CONFIG LOTTERY #########################"
 # near view $CONTRACT_ACC view_config_lottery ''
@@ -39,7 +43,13 @@ near deploy $CONTRACT_ACC ../out/nearlott.wasm
 #         "discount_divisor": "0",
 #         "rewards_breakdown": [125, 375, 750, 1250, 2500, 5000],
 #         "reserve_fee": "2200",
-#         "operate_fee": "500"
+#         "operate_fee": "500",
+#         "max_tickets_per_account": 120,
+#         "max_tickets_per_lottery": 10000,
+#         "min_jackpot": "0",
+#         "fixed_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#         "max_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#         "overflow_policy": "RollOver"
 #      }
 # }'
 
@@ -54,7 +64,13 @@ near view $CONTRACT_ACC get_config ''
 #     "discount_divisor": "0",
 #     "rewards_breakdown": [125, 375, 750, 1250, 2500, 5000],
 #     "reserve_fee": "2200",
-#     "operate_fee": "500"
+#     "operate_fee": "500",
+#     "max_tickets_per_account": 120,
+#     "max_tickets_per_lottery": 10000,
+#     "min_jackpot": "0",
+#     "fixed_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#     "max_prize_per_bracket": ["0", "0", "0", "0", "0", "0"],
+#     "overflow_policy": "RollOver"
 #     }
 # }'
 
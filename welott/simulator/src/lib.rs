@@ -0,0 +1,11 @@
+//! Monte Carlo simulator of the Welott rounds. The prizes are calculated by the pure functions of the
+//! contract, under the player demand of a scenario, to tune the config lottery before proposing it.
+mod error;
+mod report;
+mod scenario;
+mod simulation;
+
+pub use crate::error::*;
+pub use crate::report::*;
+pub use crate::scenario::*;
+pub use crate::simulation::*;
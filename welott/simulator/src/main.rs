@@ -0,0 +1,73 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+use std::process;
+use welott_simulator::{
+    format_near, simulate, summarize, write_report_csv, write_rounds_csv, Result, Scenario,
+};
+
+/// Simulate the rounds of a scenario and write the CSV reports
+#[derive(Parser)]
+#[clap(name = "welott-simulator", version)]
+struct Args {
+    /// Scenario with the config lottery and the player demand
+    #[clap(long, default_value = "scenario.toml")]
+    scenario: PathBuf,
+    /// Overrides the runs of the scenario
+    #[clap(long)]
+    runs: Option<u32>,
+    /// Overrides the seed of the scenario
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Directory of `rounds.csv` and `report.csv`
+    #[clap(long, default_value = ".")]
+    out: PathBuf,
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut scenario = Scenario::load(&args.scenario)?;
+    scenario.runs = args.runs.unwrap_or(scenario.runs);
+    scenario.seed = args.seed.unwrap_or(scenario.seed);
+    scenario.validate()?;
+
+    let outcomes = simulate(&scenario);
+    let summaries = summarize(&outcomes);
+    std::fs::create_dir_all(&args.out)?;
+    write_rounds_csv(
+        BufWriter::new(File::create(args.out.join("rounds.csv"))?),
+        &outcomes,
+    )?;
+    write_report_csv(
+        BufWriter::new(File::create(args.out.join("report.csv"))?),
+        &summaries,
+    )?;
+
+    if let Some(last) = summaries.last() {
+        let tickets: u64 = outcomes.iter().map(|outcome| outcome.tickets).sum();
+        let prizes: u128 = outcomes.iter().map(|outcome| outcome.prizes).sum();
+        println!(
+            "{} runs of {} rounds, {} tickets",
+            scenario.runs, scenario.rounds, tickets
+        );
+        println!("house edge: {:.2}%", last.house_edge * 100.0);
+        println!(
+            "EV per ticket: {} NEAR",
+            format_near(prizes / (tickets as u128).max(1))
+        );
+        println!("final jackpot p50: {} NEAR", format_near(last.jackpot_p50));
+        println!(
+            "reserve depletion risk: {:.2}%",
+            last.depletion_risk * 100.0
+        );
+    }
+    println!("reports written to {}", args.out.display());
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Args::parse()) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,164 @@
+//! CSV reports of the simulated rounds, the amounts in NEAR
+use crate::error::Result;
+use crate::simulation::{RoundOutcome, ONE_NEAR};
+use near_sdk::Balance;
+use std::io::Write;
+
+/// Distribution of a round index over all the runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundSummary {
+    pub round: u32,
+    pub jackpot_p5: Balance,
+    pub jackpot_p50: Balance,
+    pub jackpot_p95: Balance,
+    /// Share of the runs where the top bracket was won in this round
+    pub jackpot_won_rate: f64,
+    /// Share of the sales not paid back as prizes, from the first round to this one
+    pub house_edge: f64,
+    /// Prizes paid per ticket bought in this round
+    pub ev_per_ticket: Balance,
+    /// Prizes paid per NEAR spent on tickets in this round
+    pub ev_ratio: f64,
+    pub reserve_p50: Balance,
+    /// Share of the runs where the reserve fell short in this round or before
+    pub depletion_risk: f64,
+}
+
+/// Nearest rank quantile of sorted amounts
+pub fn quantile(sorted: &[Balance], q: f64) -> Balance {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[((sorted.len() - 1) as f64 * q).round() as usize]
+}
+
+fn ratio(numerator: Balance, denominator: Balance) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    numerator as f64 / denominator as f64
+}
+
+/// Summary of each round index, the outcomes of a run being in the order of its rounds
+pub fn summarize(outcomes: &[RoundOutcome]) -> Vec<RoundSummary> {
+    let rounds = outcomes.iter().map(|outcome| outcome.round + 1).max();
+    let rounds = rounds.unwrap_or(0);
+    let runs = outcomes.iter().map(|outcome| outcome.run + 1).max();
+    let runs = runs.unwrap_or(0) as usize;
+
+    let mut short_runs = vec![false; runs];
+    let (mut total_sales, mut total_prizes) = (0, 0);
+    (0..rounds)
+        .map(|round| {
+            let round_outcomes: Vec<&RoundOutcome> = outcomes
+                .iter()
+                .filter(|outcome| outcome.round == round)
+                .collect();
+            let sorted = |amount: fn(&RoundOutcome) -> Balance| {
+                let mut amounts: Vec<Balance> = round_outcomes
+                    .iter()
+                    .map(|outcome| amount(outcome))
+                    .collect();
+                amounts.sort_unstable();
+                amounts
+            };
+            let jackpots = sorted(|outcome| outcome.jackpot);
+            let reserves = sorted(|outcome| outcome.reserve);
+
+            let sales: Balance = round_outcomes.iter().map(|outcome| outcome.sales).sum();
+            let prizes: Balance = round_outcomes.iter().map(|outcome| outcome.prizes).sum();
+            let tickets: u64 = round_outcomes.iter().map(|outcome| outcome.tickets).sum();
+            total_sales += sales;
+            total_prizes += prizes;
+            for outcome in &round_outcomes {
+                short_runs[outcome.run as usize] |= outcome.reserve_short;
+            }
+            let count = round_outcomes.len() as Balance;
+            let won = round_outcomes
+                .iter()
+                .filter(|outcome| outcome.jackpot_won)
+                .count() as Balance;
+
+            RoundSummary {
+                round,
+                jackpot_p5: quantile(&jackpots, 0.05),
+                jackpot_p50: quantile(&jackpots, 0.5),
+                jackpot_p95: quantile(&jackpots, 0.95),
+                jackpot_won_rate: ratio(won, count),
+                house_edge: 1.0 - ratio(total_prizes, total_sales),
+                ev_per_ticket: if tickets == 0 {
+                    0
+                } else {
+                    prizes / tickets as Balance
+                },
+                ev_ratio: ratio(prizes, sales),
+                reserve_p50: quantile(&reserves, 0.5),
+                depletion_risk: ratio(
+                    short_runs.iter().filter(|&&short| short).count() as Balance,
+                    runs as Balance,
+                ),
+            }
+        })
+        .collect()
+}
+
+/// An amount in NEAR with 6 decimals, rounded down
+pub fn format_near(amount: Balance) -> String {
+    let micro_near = amount / (ONE_NEAR / 1_000_000);
+    format!("{}.{:06}", micro_near / 1_000_000, micro_near % 1_000_000)
+}
+
+/// One row per run and round
+pub fn write_rounds_csv<W: Write>(mut writer: W, outcomes: &[RoundOutcome]) -> Result<()> {
+    writeln!(
+        writer,
+        "run,round,players,tickets,sales,pot,jackpot,jackpot_won,prizes,operate_fee,reserve_fee,carried_over,treasury,reserve,reserve_short"
+    )?;
+    for outcome in outcomes {
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            outcome.run,
+            outcome.round,
+            outcome.players,
+            outcome.tickets,
+            format_near(outcome.sales),
+            format_near(outcome.pot),
+            format_near(outcome.jackpot),
+            outcome.jackpot_won,
+            format_near(outcome.prizes),
+            format_near(outcome.operate_fee),
+            format_near(outcome.reserve_fee),
+            format_near(outcome.carried_over),
+            format_near(outcome.treasury),
+            format_near(outcome.reserve),
+            outcome.reserve_short,
+        )?;
+    }
+    Ok(())
+}
+
+/// One row per round index, over all the runs
+pub fn write_report_csv<W: Write>(mut writer: W, summaries: &[RoundSummary]) -> Result<()> {
+    writeln!(
+        writer,
+        "round,jackpot_p5,jackpot_p50,jackpot_p95,jackpot_won_rate,house_edge,ev_per_ticket,ev_ratio,reserve_p50,depletion_risk"
+    )?;
+    for summary in summaries {
+        writeln!(
+            writer,
+            "{},{},{},{},{:.4},{:.4},{},{:.4},{},{:.4}",
+            summary.round,
+            format_near(summary.jackpot_p5),
+            format_near(summary.jackpot_p50),
+            format_near(summary.jackpot_p95),
+            summary.jackpot_won_rate,
+            summary.house_edge,
+            format_near(summary.ev_per_ticket),
+            summary.ev_ratio,
+            format_near(summary.reserve_p50),
+            summary.depletion_risk,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,106 @@
+//! Scenarios of the simulator: the config lottery of the contract and the demand of the players
+use crate::error::{Error, Result};
+use contract::{check_config_lottery, ConfigLottery, ERR13_LOTTERY_DISCOUNT_DIVISOR_TOO_LOW};
+use near_sdk::json_types::U128;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Deserialize, Debug)]
+pub struct Scenario {
+    /// Independent runs, each one a sequence of rounds
+    #[serde(default = "default_runs")]
+    pub runs: u32,
+    #[serde(default = "default_rounds")]
+    pub rounds: u32,
+    #[serde(default)]
+    pub seed: u64,
+    /// Reserve before the first round, in yoctoNEAR
+    pub initial_reserve: U128,
+    /// Like the `_auto_injection` of the draw run by the keeper
+    #[serde(default = "default_auto_injection")]
+    pub auto_injection: bool,
+    /// `max_number_tickets_per_buy_or_claim` of the contract, the players buy in chunks of it
+    #[serde(default = "default_max_tickets_per_buy")]
+    pub max_tickets_per_buy: u64,
+    /// `max_reserve_fee` and `min_discount_divisor` of the contract, the config lottery is checked against
+    #[serde(default = "default_max_reserve_fee")]
+    pub max_reserve_fee: u128,
+    #[serde(default)]
+    pub min_discount_divisor: u128,
+    /// Same as the `config_lottery` of the contract, the amounts in yoctoNEAR
+    pub config_lottery: ConfigLottery,
+    pub demand: Demand,
+}
+
+/// Players of a round and the tickets they buy, drawn uniformly in the ranges
+#[derive(Deserialize, Debug, Clone)]
+pub struct Demand {
+    pub min_players: u32,
+    pub max_players: u32,
+    pub min_tickets_per_player: u64,
+    pub max_tickets_per_player: u64,
+    /// Players drawn in by a large pot, added for each NEAR carried over from the previous round
+    #[serde(default)]
+    pub players_per_near_carried: f64,
+}
+
+fn default_runs() -> u32 {
+    1000
+}
+
+fn default_rounds() -> u32 {
+    52
+}
+
+fn default_auto_injection() -> bool {
+    true
+}
+
+fn default_max_tickets_per_buy() -> u64 {
+    12
+}
+
+fn default_max_reserve_fee() -> u128 {
+    3000
+}
+
+impl Scenario {
+    pub fn parse(content: &str) -> Result<Self> {
+        // toml does not deserialize u128, the config lottery is read like the JSON the contract takes
+        let scenario: toml::Value = toml::from_str(content)?;
+        let scenario: Self = serde_json::from_value(serde_json::to_value(scenario)?)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// The checks of `start_lottery` and `buy_tickets`, a scenario the contract would reject is not simulated
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Err(Error::InvalidScenario(reason.to_string()));
+        let config = &self.config_lottery;
+        check_config_lottery(config, self.max_reserve_fee, self.min_discount_divisor)
+            .or_else(invalid)?;
+        // the bulk discount of a buy is only defined up to the divisor
+        if self.max_tickets_per_buy == 0
+            || config.discount_divisor.0 != 0
+                && config.discount_divisor.0 < self.max_tickets_per_buy as u128
+        {
+            return invalid(ERR13_LOTTERY_DISCOUNT_DIVISOR_TOO_LOW);
+        }
+        if self.runs == 0 || self.rounds == 0 {
+            return invalid("runs and rounds must not be 0");
+        }
+        let demand = &self.demand;
+        if demand.min_players > demand.max_players
+            || demand.min_tickets_per_player > demand.max_tickets_per_player
+            || demand.players_per_near_carried.is_nan()
+            || demand.players_per_near_carried < 0.0
+        {
+            return invalid("the demand ranges must not be empty");
+        }
+        Ok(())
+    }
+}
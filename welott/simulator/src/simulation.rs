@@ -0,0 +1,207 @@
+//! Rounds of a scenario, the prizes are calculated by the functions the draw of the contract calls
+use crate::scenario::Scenario;
+use contract::{
+    _calculate_count_winners_per_bracket, _calculate_draw_fees, _calculate_prizes,
+    _calculate_total_price_for_bulk_tickets, Lottery, Status, TicketNumber,
+};
+use near_sdk::Balance;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+pub const ONE_NEAR: Balance = 10u128.pow(24);
+
+/// A drawn round of a run, the amounts in yoctoNEAR
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundOutcome {
+    pub run: u32,
+    pub round: u32,
+    pub players: u32,
+    pub tickets: u64,
+    pub sales: Balance,
+    /// Amount collected by the lottery, the sales and the amount carried over from the previous round
+    pub pot: Balance,
+    /// Prize of a single winning ticket in the top bracket
+    pub jackpot: Balance,
+    pub jackpot_won: bool,
+    pub prizes: Balance,
+    pub operate_fee: Balance,
+    pub reserve_fee: Balance,
+    /// Pending injection of the next round
+    pub carried_over: Balance,
+    /// Brackets without winners owed to the treasury, without auto injection
+    pub treasury: Balance,
+    /// Reserve once the round is drawn
+    pub reserve: Balance,
+    /// The reserve could not pay the fixed prizes in full or guarantee the next minimum jackpot
+    pub reserve_short: bool,
+}
+
+/// Lottery of a round, like `start_lottery` opens it
+fn open_lottery(scenario: &Scenario, carried_over: Balance) -> Lottery {
+    let config = &scenario.config_lottery;
+    Lottery {
+        status: Status::Open,
+        price_ticket_in_near: config.price_ticket_in_near.0,
+        discount_divisor: config.discount_divisor.0,
+        rewards_breakdown: config.rewards_breakdown.clone(),
+        reserve_fee: config.reserve_fee.0,
+        near_per_bracket: vec![0, 0, 0, 0, 0, 0],
+        count_winners_per_bracket: vec![0, 0, 0, 0, 0, 0],
+        amount_collected_in_near: carried_over,
+        last_pot_size: carried_over,
+        operate_fee: config.operate_fee.0,
+        max_tickets_per_account: config.max_tickets_per_account,
+        max_tickets_per_lottery: config.max_tickets_per_lottery,
+        min_jackpot: config.min_jackpot.0,
+        fixed_prize_per_bracket: config
+            .fixed_prize_per_bracket
+            .iter()
+            .map(|prize| prize.0)
+            .collect(),
+        fixed_prizes_scale: 10000,
+        max_prize_per_bracket: config
+            .max_prize_per_bracket
+            .iter()
+            .map(|prize| prize.0)
+            .collect(),
+        overflow_policy: config.overflow_policy.clone(),
+        overflow_per_bracket: vec![0, 0, 0, 0, 0, 0],
+        overflow_received_per_bracket: vec![0, 0, 0, 0, 0, 0],
+        ..Default::default()
+    }
+}
+
+/// Number of tickets bought in each bracket, keyed by the trailing digits like `_bracket_tickets_number`
+struct BracketTickets(Vec<HashMap<TicketNumber, u128>>);
+
+impl BracketTickets {
+    fn new() -> Self {
+        Self(vec![HashMap::new(); 6])
+    }
+
+    fn add(&mut self, number: TicketNumber) {
+        for (j, tickets) in self.0.iter_mut().enumerate() {
+            *tickets.entry(number % 10u32.pow(j as u32 + 1)).or_default() += 1;
+        }
+    }
+
+    fn get(&self, j: u32, final_number: TicketNumber) -> u128 {
+        self.0[j as usize]
+            .get(&(final_number % 10u32.pow(j + 1)))
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Prize of a single winning ticket in the top bracket, had the round been won
+fn jackpot_if_won(
+    lottery: &Lottery,
+    scenario: &Scenario,
+    amount_to_share: u128,
+    reserve: u128,
+) -> u128 {
+    let mut lottery = Lottery {
+        amount_collected_in_near: lottery.amount_collected_in_near,
+        first_ticket_id_next_lottery: lottery.first_ticket_id_next_lottery.max(1),
+        count_winners_per_bracket: vec![0, 0, 0, 0, 0, 1],
+        ..open_lottery(scenario, lottery.last_pot_size)
+    };
+    _calculate_prizes(&mut lottery, amount_to_share, reserve);
+    lottery.near_per_bracket[5]
+}
+
+/// Runs of a scenario, one after the other, each one seeded from the seed of the scenario
+pub fn simulate(scenario: &Scenario) -> Vec<RoundOutcome> {
+    (0..scenario.runs)
+        .flat_map(|run| simulate_run(scenario, run))
+        .collect()
+}
+
+/// Rounds of a run, every round starting with what the previous draw carried over
+pub fn simulate_run(scenario: &Scenario, run: u32) -> Vec<RoundOutcome> {
+    let mut rng = StdRng::seed_from_u64(scenario.seed.wrapping_add(run as u64));
+    let config = &scenario.config_lottery;
+    let demand = &scenario.demand;
+    let mut reserve = scenario.initial_reserve.0;
+    let mut carried_over: Balance = 0;
+    let mut outcomes = Vec::with_capacity(scenario.rounds as usize);
+
+    for round in 0..scenario.rounds {
+        let mut lottery = open_lottery(scenario, carried_over);
+
+        // a larger pot draws in more players
+        let players = rng.gen_range(demand.min_players..=demand.max_players)
+            + (demand.players_per_near_carried * (carried_over / ONE_NEAR) as f64) as u32;
+        let mut bracket_tickets = BracketTickets::new();
+        let mut tickets: u64 = 0;
+        let mut sales: Balance = 0;
+        for _ in 0..players {
+            let wanted = rng
+                .gen_range(demand.min_tickets_per_player..=demand.max_tickets_per_player)
+                .min(config.max_tickets_per_account)
+                .min(config.max_tickets_per_lottery - tickets);
+            let mut bought = 0;
+            while bought < wanted {
+                let chunk = (wanted - bought).min(scenario.max_tickets_per_buy);
+                sales += _calculate_total_price_for_bulk_tickets(
+                    lottery.discount_divisor,
+                    lottery.price_ticket_in_near,
+                    chunk as u128,
+                );
+                for _ in 0..chunk {
+                    bracket_tickets.add(rng.gen_range(1000000..=1999999));
+                }
+                bought += chunk;
+            }
+            tickets += wanted;
+        }
+        lottery.amount_collected_in_near += sales;
+        lottery.first_ticket_id_next_lottery = tickets as u32;
+
+        let final_number: TicketNumber = rng.gen_range(1000000..=1999999);
+        let (operate_fee, reserve_fee, amount_to_share) = _calculate_draw_fees(&lottery);
+        if tickets > 0 {
+            lottery.count_winners_per_bracket =
+                _calculate_count_winners_per_bracket(&lottery, |j| {
+                    bracket_tickets.get(j, final_number)
+                });
+        }
+        let jackpot = jackpot_if_won(&lottery, scenario, amount_to_share, reserve);
+        let draw = _calculate_prizes(&mut lottery, amount_to_share, reserve);
+        let prizes: Balance = (0..6)
+            .map(|j| lottery.near_per_bracket[j] * lottery.count_winners_per_bracket[j])
+            .sum();
+
+        // the same split of the pot as the draw of the contract
+        reserve -= draw.fixed_prizes_from_reserve + draw.jackpot_top_up;
+        let mut treasury = 0;
+        if scenario.auto_injection {
+            carried_over = draw.amount_to_withdraw_to_next_lottery + reserve_fee;
+        } else {
+            carried_over = 0;
+            reserve += reserve_fee;
+            treasury = draw.amount_to_withdraw_to_next_lottery;
+        }
+        carried_over += lottery.overflow_rolled_over + lottery.dust;
+
+        outcomes.push(RoundOutcome {
+            run,
+            round,
+            players,
+            tickets,
+            sales,
+            pot: lottery.amount_collected_in_near,
+            jackpot,
+            jackpot_won: lottery.count_winners_per_bracket[5] > 0,
+            prizes,
+            operate_fee,
+            reserve_fee,
+            carried_over,
+            treasury,
+            reserve,
+            reserve_short: lottery.fixed_prizes_scale < 10000 || reserve < config.min_jackpot.0,
+        });
+    }
+    outcomes
+}
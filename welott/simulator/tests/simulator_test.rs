@@ -0,0 +1,125 @@
+use welott_simulator::*;
+
+fn example() -> Scenario {
+    let mut scenario = Scenario::parse(include_str!("../scenario.example.toml")).unwrap();
+    scenario.runs = 20;
+    scenario.rounds = 12;
+    scenario
+}
+
+#[test]
+fn test_example_scenario() {
+    let scenario = example();
+    assert_eq!(scenario.max_tickets_per_buy, 12);
+    assert_eq!(scenario.config_lottery.max_tickets_per_account, 120);
+
+    let mut scenario = example();
+    scenario.config_lottery.rewards_breakdown[0] += 1;
+    assert!(matches!(
+        scenario.validate(),
+        Err(Error::InvalidScenario(reason)) if reason.starts_with("E14")
+    ));
+    // the bulk discount is not defined for buys larger than the divisor
+    let mut scenario = example();
+    scenario.config_lottery.discount_divisor.0 = 11;
+    assert!(scenario.validate().is_err());
+    scenario.config_lottery.discount_divisor.0 = 12;
+    scenario.validate().unwrap();
+}
+
+#[test]
+fn test_deterministic_runs() {
+    let scenario = example();
+    let outcomes = simulate(&scenario);
+    assert_eq!(outcomes.len(), 20 * 12);
+    assert_eq!(outcomes, simulate(&scenario));
+    assert_eq!(simulate_run(&scenario, 3), outcomes[36..48].to_vec());
+
+    let mut reseeded = example();
+    reseeded.seed += 100;
+    assert_ne!(outcomes, simulate(&reseeded));
+}
+
+#[test]
+fn test_rounds_conserve_the_pot() {
+    for auto_injection in [true, false] {
+        let mut scenario = example();
+        scenario.auto_injection = auto_injection;
+        for run in 0..scenario.runs {
+            let mut reserve = scenario.initial_reserve.0;
+            let mut carried_over = 0;
+            for outcome in simulate_run(&scenario, run) {
+                assert_eq!(outcome.pot, carried_over + outcome.sales);
+                // the pot and the reserve are split between the prizes, the fees, the treasury and the next round
+                assert_eq!(
+                    outcome.pot + reserve,
+                    outcome.prizes
+                        + outcome.operate_fee
+                        + outcome.carried_over
+                        + outcome.treasury
+                        + outcome.reserve
+                );
+                assert!(outcome.tickets <= outcome.players as u64 * 24);
+                carried_over = outcome.carried_over;
+                reserve = outcome.reserve;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rounds_without_players() {
+    let mut scenario = example();
+    scenario.demand.min_players = 0;
+    scenario.demand.max_players = 0;
+    scenario.demand.players_per_near_carried = 0.0;
+    scenario.config_lottery.min_jackpot.0 = 0;
+    let outcomes = simulate_run(&scenario, 0);
+    assert!(outcomes.iter().all(|outcome| outcome.tickets == 0
+        && outcome.prizes == 0
+        && outcome.pot == outcome.carried_over
+        && outcome.reserve == scenario.initial_reserve.0));
+
+    let summaries = summarize(&outcomes);
+    assert_eq!(summaries.len(), 12);
+    assert_eq!(summaries[11].ev_per_ticket, 0);
+    assert_eq!(summaries[11].depletion_risk, 0.0);
+}
+
+#[test]
+fn test_report() {
+    assert_eq!(quantile(&[1, 2, 3, 4, 5], 0.5), 3);
+    assert_eq!(quantile(&[1, 2, 3, 4, 5], 0.95), 5);
+    assert_eq!(quantile(&[], 0.5), 0);
+    assert_eq!(
+        format_near(1_234_567_890 * ONE_NEAR / 1000),
+        "1234567.890000"
+    );
+    assert_eq!(format_near(ONE_NEAR / 3), "0.333333");
+
+    let scenario = example();
+    let outcomes = simulate(&scenario);
+    let summaries = summarize(&outcomes);
+    assert_eq!(summaries.len(), 12);
+    for summary in &summaries {
+        assert!(summary.jackpot_p5 <= summary.jackpot_p50);
+        assert!(summary.jackpot_p50 <= summary.jackpot_p95);
+        // the minimum jackpot is guaranteed by the reserve
+        assert!(summary.jackpot_p5 >= scenario.config_lottery.min_jackpot.0);
+        assert!((0.0..=1.0).contains(&summary.depletion_risk));
+    }
+    // the risk is cumulative over the rounds
+    assert!(summaries
+        .windows(2)
+        .all(|pair| pair[0].depletion_risk <= pair[1].depletion_risk));
+
+    let mut csv = vec![];
+    write_report_csv(&mut csv, &summaries).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), 13);
+    assert!(csv.starts_with("round,jackpot_p5,jackpot_p50,jackpot_p95,"));
+
+    let mut csv = vec![];
+    write_rounds_csv(&mut csv, &outcomes).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 20 * 12 + 1);
+}
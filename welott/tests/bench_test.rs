@@ -0,0 +1,390 @@
+use crate::utils::*;
+use contract::{LotteryId, LotteryUserData, PendingChange, TicketId};
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::Account;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+mod utils;
+
+// final number of the benchmarked draws, queued in the staging build
+const FINAL_NUMBER: u32 = 1_234_567;
+// tickets per call of the buys and the claims, up to the cap of an account
+const BATCH_SIZES: [u64; 6] = [1, 6, 12, 24, 60, 120];
+const MAX_TICKETS_PER_ACCOUNT: u64 = 120;
+const DEFAULT_TICKETS_PER_BUY: u64 = 12;
+
+/// A measured call, with the tickets already held by its account and sold by its lottery
+struct Measure {
+    method: &'static str,
+    tickets: u64,
+    account_tickets: u64,
+    lottery_tickets: u64,
+    gas_burnt: u64,
+    storage_bytes: i64,
+    success: bool,
+}
+
+impl Measure {
+    const HEADER: &'static str =
+        "method,tickets,account_tickets,lottery_tickets,gas_burnt,tgas,storage_bytes,status";
+
+    fn row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{:.1},{},{}",
+            self.method,
+            self.tickets,
+            self.account_tickets,
+            self.lottery_tickets,
+            self.gas_burnt,
+            self.gas_burnt as f64 / 1e12,
+            self.storage_bytes,
+            if self.success { "ok" } else { "failed" }
+        )
+    }
+}
+
+/// Numbers winning the first bracket only: the last digit of the final number, but not its last two,
+/// the other digits spread over the brackets like the tickets of many players
+fn winning_numbers(first: u64, count: u64) -> Vec<u32> {
+    (first..first + count)
+        .map(|i| {
+            let tens = (i % 9) as u32;
+            let tens = if tens >= FINAL_NUMBER / 10 % 10 {
+                tens + 1
+            } else {
+                tens
+            };
+            1_000_000 + (i * 7919 % 10_000) as u32 * 100 + tens * 10 + FINAL_NUMBER % 10
+        })
+        .collect()
+}
+
+/// Storage used by the contract account, in bytes
+async fn storage_usage(env: &Env) -> anyhow::Result<i64> {
+    Ok(env.contract.view_account().await?.storage_usage as i64)
+}
+
+async fn measure(
+    env: &Env,
+    method: &'static str,
+    (tickets, account_tickets, lottery_tickets): (u64, u64, u64),
+    storage_before: i64,
+    result: &ExecutionFinalResult,
+) -> anyhow::Result<Measure> {
+    Ok(Measure {
+        method,
+        tickets,
+        account_tickets,
+        lottery_tickets,
+        gas_burnt: result.total_gas_burnt.as_gas(),
+        storage_bytes: storage_usage(env).await? - storage_before,
+        success: result.is_success(),
+    })
+}
+
+/// Views called in a transaction, like another contract would call them, to measure their gas
+async fn measure_view(
+    env: &Env,
+    caller: &Account,
+    method: &'static str,
+    args: Value,
+    (tickets, account_tickets, lottery_tickets): (u64, u64, u64),
+) -> anyhow::Result<Measure> {
+    let storage_before = storage_usage(env).await?;
+    let result = env.call_with_deposit(caller, method, args, 0).await?;
+    let counts = (tickets, account_tickets, lottery_tickets);
+    measure(env, method, counts, storage_before, &result).await
+}
+
+/// Player registered with the storage of the cap of tickets of an account
+async fn new_player(env: &Env, name: &str) -> anyhow::Result<Account> {
+    let player = env.create_player(name, 500).await?;
+    env.storage_deposit(&[&player], 50).await?;
+    Ok(player)
+}
+
+async fn ticket_ids(
+    env: &Env,
+    player: &Account,
+    lottery_id: LotteryId,
+) -> anyhow::Result<Vec<TicketId>> {
+    let user_data: LotteryUserData = env
+        .view(
+            "view_user_info_for_lottery_id",
+            json!({
+                "_user": player.id(),
+                "_lottery_id": lottery_id,
+                "_cursor": null,
+                "_size": MAX_TICKETS_PER_ACCOUNT,
+            }),
+        )
+        .await?;
+    Ok(user_data.ticket_ids)
+}
+
+/// Apply a queued change once the sandbox has passed its delay
+async fn execute_change(env: &Env, change_id: u64) -> anyhow::Result<()> {
+    let pending_changes: Vec<PendingChange> = env.view("view_pending_changes", json!({})).await?;
+    let pending_change = pending_changes
+        .iter()
+        .find(|pending_change| pending_change.change_id == change_id)
+        .expect("the change should be queued");
+    env.fast_forward_until(pending_change.effective_at).await?;
+    env.call_with_deposit(
+        &env.owner,
+        "execute_change",
+        json!({ "_change_id": change_id }),
+        0,
+    )
+    .await?
+    .into_result()?;
+    Ok(())
+}
+
+/// Raise the tickets per buy or claim to the cap of an account, through the timelock
+async fn raise_max_tickets_per_buy(env: &Env) -> anyhow::Result<()> {
+    let change_id: u64 = env
+        .call_with_deposit(
+            &env.owner,
+            "set_config",
+            json!({
+                "_max_number_tickets_per_buy": MAX_TICKETS_PER_ACCOUNT,
+                "_min_discount_divisor": MAX_TICKETS_PER_ACCOUNT,
+            }),
+            1,
+        )
+        .await?
+        .json()?;
+    execute_change(env, change_id).await
+}
+
+/// Close and draw a lottery, measuring both calls
+async fn bench_draw(
+    env: &Env,
+    lottery_id: LotteryId,
+    lottery_tickets: u64,
+) -> anyhow::Result<Vec<Measure>> {
+    env.pass_end_time(lottery_id).await?;
+    let storage_before = storage_usage(env).await?;
+    let result = env.call(&env.operator, "close_lottery", json!({})).await?;
+    let close = measure(
+        env,
+        "close_lottery",
+        (0, 0, lottery_tickets),
+        storage_before,
+        &result,
+    )
+    .await?;
+
+    let storage_before = storage_usage(env).await?;
+    let result = env
+        .call(
+            &env.operator,
+            "draw_final_number_and_make_lottery_claimable",
+            json!({ "_lottery_id": lottery_id, "_auto_injection": true }),
+        )
+        .await?;
+    let draw = measure(
+        env,
+        "draw_final_number_and_make_lottery_claimable",
+        (0, 0, lottery_tickets),
+        storage_before,
+        &result,
+    )
+    .await?;
+    assert_eq!(env.lottery(lottery_id).await?.final_number, FINAL_NUMBER);
+    Ok(vec![close, draw])
+}
+
+/// Where the report is written, `welott/bench/report.csv` unless `WELOTT_BENCH_REPORT` is set
+fn report_path() -> PathBuf {
+    std::env::var("WELOTT_BENCH_REPORT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("bench")
+                .join("report.csv")
+        })
+}
+
+#[tokio::test]
+#[ignore = "benchmark on a sandbox node, run with --ignored"]
+async fn bench_entry_points() -> anyhow::Result<()> {
+    let env = Env::init_staging(default_config_lottery()).await?;
+    raise_max_tickets_per_buy(&env).await?;
+    env.call(
+        &env.owner,
+        "queue_final_numbers",
+        json!({ "_final_numbers": vec![FINAL_NUMBER; 3] }),
+    )
+    .await?
+    .into_result()?;
+    let mut measures = vec![];
+
+    // draws of a lottery without tickets and of a lottery with a single buy
+    let lottery_id = env.start_lottery().await?;
+    measures.extend(bench_draw(&env, lottery_id, 0).await?);
+    let lottery_id = env.start_lottery().await?;
+    let early = new_player(&env, "early").await?;
+    env.buy_tickets(
+        &early,
+        lottery_id,
+        &winning_numbers(0, DEFAULT_TICKETS_PER_BUY),
+    )
+    .await?
+    .into_result()?;
+    measures.extend(bench_draw(&env, lottery_id, DEFAULT_TICKETS_PER_BUY).await?);
+
+    // buys of growing sizes, each one by a new account
+    let lottery_id = env.start_lottery().await?;
+    let mut lottery_tickets = 0;
+    let mut claimers = vec![];
+    for size in BATCH_SIZES {
+        let player = new_player(&env, &format!("batch{}", size)).await?;
+        let storage_before = storage_usage(&env).await?;
+        let result = env
+            .buy_tickets(&player, lottery_id, &winning_numbers(lottery_tickets, size))
+            .await?;
+        let buy = measure(
+            &env,
+            "buy_tickets",
+            (size, 0, lottery_tickets),
+            storage_before,
+            &result,
+        )
+        .await?;
+        if buy.success {
+            lottery_tickets += size;
+            claimers.push((player, size));
+        }
+        measures.push(buy);
+    }
+
+    // buys of the default size by an account holding more and more tickets
+    let holder = new_player(&env, "holder").await?;
+    for account_tickets in (0..MAX_TICKETS_PER_ACCOUNT).step_by(DEFAULT_TICKETS_PER_BUY as usize) {
+        let numbers = winning_numbers(lottery_tickets, DEFAULT_TICKETS_PER_BUY);
+        let storage_before = storage_usage(&env).await?;
+        let result = env.buy_tickets(&holder, lottery_id, &numbers).await?;
+        measures.push(
+            measure(
+                &env,
+                "buy_tickets",
+                (DEFAULT_TICKETS_PER_BUY, account_tickets, lottery_tickets),
+                storage_before,
+                &result,
+            )
+            .await?,
+        );
+        lottery_tickets += DEFAULT_TICKETS_PER_BUY;
+    }
+
+    // views of the tickets of the holder, by pages of the default size and of the cap of an account
+    let holder_ticket_ids = ticket_ids(&env, &holder, lottery_id).await?;
+    let held = MAX_TICKETS_PER_ACCOUNT;
+    measures.push(
+        measure_view(
+            &env,
+            &holder,
+            "view_lottery",
+            json!({ "_lottery_id": lottery_id }),
+            (0, held, lottery_tickets),
+        )
+        .await?,
+    );
+    for size in [DEFAULT_TICKETS_PER_BUY, MAX_TICKETS_PER_ACCOUNT] {
+        measures.push(
+            measure_view(
+                &env,
+                &holder,
+                "calculate_total_price_for_bulk_tickets",
+                json!({ "_lottery_id": lottery_id, "_number_tickets": size }),
+                (size, held, lottery_tickets),
+            )
+            .await?,
+        );
+        measures.push(
+            measure_view(
+                &env,
+                &holder,
+                "view_user_info_for_lottery_id",
+                json!({
+                    "_user": holder.id(),
+                    "_lottery_id": lottery_id,
+                    "_cursor": null,
+                    "_size": size,
+                }),
+                (size, held, lottery_tickets),
+            )
+            .await?,
+        );
+        measures.push(
+            measure_view(
+                &env,
+                &holder,
+                "view_numbers_and_statuses_for_ticket_ids",
+                json!({
+                    "_ticket_ids": holder_ticket_ids[..size as usize],
+                    "_lottery_id": lottery_id,
+                }),
+                (size, held, lottery_tickets),
+            )
+            .await?,
+        );
+    }
+
+    measures.extend(bench_draw(&env, lottery_id, lottery_tickets).await?);
+    measures.push(
+        measure_view(
+            &env,
+            &holder,
+            "view_rewards_for_ticket_id",
+            json!({
+                "_lottery_id": lottery_id,
+                "_ticket_id": holder_ticket_ids[0],
+                "_bracket": 0,
+            }),
+            (1, held, lottery_tickets),
+        )
+        .await?,
+    );
+
+    // claims of growing sizes, every ticket winning the first bracket
+    for (player, size) in &claimers {
+        let ticket_ids = ticket_ids(&env, player, lottery_id).await?;
+        let storage_before = storage_usage(&env).await?;
+        let result = env
+            .call(
+                player,
+                "claim_tickets",
+                json!({
+                    "_lottery_id": lottery_id,
+                    "_ticket_ids": ticket_ids,
+                    "_brackets": vec![0; ticket_ids.len()],
+                }),
+            )
+            .await?;
+        measures.push(
+            measure(
+                &env,
+                "claim_tickets",
+                (*size, *size, lottery_tickets),
+                storage_before,
+                &result,
+            )
+            .await?,
+        );
+    }
+
+    let report: Vec<String> = std::iter::once(Measure::HEADER.to_string())
+        .chain(measures.iter().map(Measure::row))
+        .collect();
+    let path = report_path();
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, report.join("\n") + "\n")?;
+    println!("{}", report.join("\n"));
+    println!("report written to {}", path.display());
+    Ok(())
+}
@@ -0,0 +1,255 @@
+use crate::utils::*;
+use contract::{
+    ConfigLottery, ConfigVersion, LotteryNumberAndStatusData, LotteryUserData, PendingChange,
+    TicketStatus,
+};
+use near_contract_standards::storage_management::{StorageBalance, StorageBalanceBounds};
+use near_sdk::json_types::{Base64VecU8, U128};
+use serde_json::json;
+mod utils;
+
+#[tokio::test]
+async fn test_new() -> anyhow::Result<()> {
+    let env = Env::init().await?;
+
+    let owner_id: String = env.view("get_owner", json!({})).await?;
+    assert_eq!(owner_id, env.owner.id().as_str());
+    let operators: Vec<String> = env
+        .view("view_role_members", json!({ "role": "Operator" }))
+        .await?;
+    assert_eq!(operators, vec![env.operator.id().to_string()]);
+    let latest_lottery_id: u32 = env.view("view_latest_lottery_id", json!({})).await?;
+    assert_eq!(latest_lottery_id, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_storage() -> anyhow::Result<()> {
+    let env = Env::init().await?;
+    let bounds: StorageBalanceBounds = env.view("storage_balance_bounds", json!({})).await?;
+    let lottery_id = env.start_lottery().await?;
+
+    // an account has to register before buying tickets
+    let result = env.buy_tickets(&env.bob, lottery_id, &[1234567]).await?;
+    assert!(result.is_failure());
+
+    env.storage_deposit(&[&env.alice], 2).await?;
+    let balance: Option<StorageBalance> = env
+        .view(
+            "storage_balance_of",
+            json!({ "account_id": env.alice.id() }),
+        )
+        .await?;
+    assert_eq!(balance.unwrap().total.0, 2 * bounds.min.0);
+    env.buy_tickets(&env.alice, lottery_id, &[1234567])
+        .await?
+        .into_result()?;
+
+    // the storage not used by the tickets can be withdrawn
+    let balance: StorageBalance = env
+        .call(&env.alice, "storage_withdraw", json!({}))
+        .await?
+        .json()?;
+    assert_eq!(balance.available.0, 0);
+    assert!(balance.total.0 < 2 * bounds.min.0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_full_round() -> anyhow::Result<()> {
+    let env = Env::init().await?;
+    env.storage_deposit(&[&env.alice, &env.bob], 5).await?;
+    let lottery_id = env.start_lottery().await?;
+    assert_eq!(lottery_id, 1);
+
+    // a ticket for every last digit, one of them wins at least the first bracket
+    let numbers: Vec<u32> = (1_000_000..1_000_010).collect();
+    env.buy_tickets(&env.alice, lottery_id, &numbers)
+        .await?
+        .into_result()?;
+    env.buy_tickets(&env.bob, lottery_id, &[1_234_567, 1_765_432])
+        .await?
+        .into_result()?;
+    env.call_with_deposit(
+        &env.injector,
+        "inject_funds",
+        json!({ "_lottery_id": lottery_id }),
+        10 * ONE_NEAR,
+    )
+    .await?
+    .into_result()?;
+    assert!(env.lottery(lottery_id).await?.amount_collected_in_near > 10 * ONE_NEAR);
+
+    // the lottery can not be closed before its end time
+    let result = env.call(&env.operator, "close_lottery", json!({})).await?;
+    assert!(format!("{:?}", result.into_result().unwrap_err()).contains("E40"));
+    // the draw pays the operate fee to the treasury
+    let treasury_balance = env.treasury.view_account().await?.balance;
+    env.close_and_draw(lottery_id).await?;
+    assert!(env.treasury.view_account().await?.balance > treasury_balance);
+    let lottery = env.lottery(lottery_id).await?;
+    assert!((1_000_000..=1_999_999).contains(&lottery.final_number));
+
+    // claim every winning ticket in its highest bracket paying a prize
+    let user_data: LotteryUserData = env
+        .view(
+            "view_user_info_for_lottery_id",
+            json!({
+                "_user": env.alice.id(),
+                "_lottery_id": lottery_id,
+                "_cursor": null,
+                "_size": 100,
+            }),
+        )
+        .await?;
+    assert_eq!(user_data.ticket_ids.len(), 10);
+    let mut ticket_ids = vec![];
+    let mut brackets = vec![];
+    for (ticket_id, ticket_number) in user_data.ticket_ids.iter().zip(&user_data.ticket_numbers) {
+        if let Some(bracket) = lottery
+            .highest_bracket(*ticket_number)
+            .filter(|&bracket| lottery.near_per_bracket[bracket as usize] > 0)
+        {
+            ticket_ids.push(*ticket_id);
+            brackets.push(bracket);
+        }
+    }
+    assert!(!ticket_ids.is_empty());
+
+    let claim_args = json!({
+        "_lottery_id": lottery_id,
+        "_ticket_ids": ticket_ids,
+        "_brackets": brackets,
+    });
+    let balance = env.alice.view_account().await?.balance;
+    env.call(&env.alice, "claim_tickets", claim_args.clone())
+        .await?
+        .into_result()?;
+    assert!(env.alice.view_account().await?.balance > balance);
+
+    let statuses: LotteryNumberAndStatusData = env
+        .view(
+            "view_numbers_and_statuses_for_ticket_ids",
+            json!({ "_ticket_ids": ticket_ids, "_lottery_id": lottery_id }),
+        )
+        .await?;
+    assert!(statuses
+        .ticket_status
+        .iter()
+        .all(|status| matches!(status, TicketStatus::Claimed)));
+    // a ticket is claimed once
+    assert!(env
+        .call(&env.alice, "claim_tickets", claim_args)
+        .await?
+        .is_failure());
+
+    // the next round starts from the drawn one
+    assert_eq!(env.start_lottery().await?, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_view_tickets_pages() -> anyhow::Result<()> {
+    let env = Env::init().await?;
+    env.storage_deposit(&[&env.chandra], 5).await?;
+    let lottery_id = env.start_lottery().await?;
+
+    for _ in 0..3 {
+        env.buy_tickets(&env.chandra, lottery_id, &[1039219; 12])
+            .await?
+            .into_result()?;
+    }
+    let user_data: LotteryUserData = env
+        .view(
+            "view_user_info_for_lottery_id",
+            json!({
+                "_user": env.chandra.id(),
+                "_lottery_id": lottery_id,
+                "_cursor": 0,
+                "_size": 25,
+            }),
+        )
+        .await?;
+    assert_eq!(user_data.ticket_numbers.len(), 25);
+    assert_eq!(user_data.ticket_numbers[0], 1039219);
+    assert_eq!(user_data.cursor, 25);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_governance_by_dao() -> anyhow::Result<()> {
+    let env = Env::init().await?;
+    let dao = env.deploy_mock_dao(vec![&env.alice]).await?;
+
+    // the owner, alone in the multisig, designates the DAO
+    env.call(
+        &env.owner,
+        "set_governance",
+        json!({ "governance_id": dao.id() }),
+    )
+    .await?
+    .into_result()?;
+
+    // the council approves a proposal calling the lottery
+    let mut config_lottery = default_config_lottery();
+    config_lottery.operate_fee = U128(800);
+    let args = serde_json::to_vec(&json!({ "_config_lottery": config_lottery }))?;
+    env.alice
+        .call(dao.id(), "add_proposal")
+        .args_json(json!({
+            "proposal": {
+                "description": "raise the operate fee to 8%",
+                "kind": {
+                    "FunctionCall": {
+                        "receiver_id": env.contract.id(),
+                        "actions": [{
+                            "method_name": "apply_governance_config",
+                            "args": Base64VecU8(args),
+                            "deposit": "0",
+                            "gas": "50000000000000",
+                        }]
+                    }
+                }
+            }
+        }))
+        .gas(MAX_GAS)
+        .transact()
+        .await?
+        .into_result()?;
+    env.alice
+        .call(dao.id(), "act_proposal")
+        .args_json(json!({ "id": 0, "action": "VoteApprove" }))
+        .gas(MAX_GAS)
+        .transact()
+        .await?
+        .into_result()?;
+
+    // the voted config is queued behind the timelock delay, then executed by anyone
+    let pending_changes: Vec<PendingChange> = env.view("view_pending_changes", json!({})).await?;
+    assert_eq!(pending_changes.len(), 1);
+    assert_eq!(pending_changes[0].proposed_by.as_str(), dao.id().as_str());
+    let config_lottery: ConfigLottery = env.view("view_config_lottery", json!({})).await?;
+    assert_eq!(config_lottery.operate_fee, U128(500));
+    env.fast_forward_until(pending_changes[0].effective_at)
+        .await?;
+    env.call_with_deposit(
+        &env.bob,
+        "execute_change",
+        json!({ "_change_id": pending_changes[0].change_id }),
+        0,
+    )
+    .await?
+    .into_result()?;
+
+    let config_lottery: ConfigLottery = env.view("view_config_lottery", json!({})).await?;
+    assert_eq!(config_lottery.operate_fee, U128(800));
+    let config_versions: Vec<ConfigVersion> = env
+        .view(
+            "view_config_versions",
+            json!({ "_cursor": null, "_size": null }),
+        )
+        .await?;
+    assert_eq!(config_versions.len(), 2);
+    assert_eq!(config_versions[1].applied_by.as_str(), dao.id().as_str());
+    Ok(())
+}
@@ -1,95 +1,262 @@
-use near_sdk::AccountId;
-use near_sdk_sim::{deploy, init_simulator, to_yocto, ContractAccount, UserAccount};
+// shared by the sandbox test crates, each one using a part of the harness
+#![allow(dead_code)]
 
-near_sdk_sim::lazy_static_include::lazy_static_include_bytes! {
-    NEARLOTT_WASM_BYTES => "out/nearlott.wasm",
+use contract::{ConfigLottery, Lottery, LotteryId, OverflowPolicy, Status};
+use near_contract_standards::storage_management::StorageBalanceBounds;
+use near_sdk::json_types::U128;
+use near_sdk::serde::de::DeserializeOwned;
+use near_workspaces::network::Sandbox;
+use near_workspaces::result::ExecutionFinalResult;
+use near_workspaces::types::{Gas, NearToken};
+use near_workspaces::{Account, Contract, Worker};
+use serde_json::{json, Value};
+use std::path::Path;
+
+pub const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+pub const MAX_GAS: Gas = Gas::from_tgas(300);
+// a lottery lasts the 10 hours minimum of the contract, passed by fast forwarding the sandbox
+pub const TIME_RUN_LOTTERY: u64 = 36_000_000_000_000;
+const FAST_FORWARD_BLOCKS: u64 = 100;
+
+/// Wasm built by `./build.sh`
+pub fn wasm(name: &str) -> Vec<u8> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("out").join(name);
+    std::fs::read(&path)
+        .unwrap_or_else(|_| panic!("{} is missing, build it with ./build.sh", path.display()))
+}
+
+pub fn default_config_lottery() -> ConfigLottery {
+    ConfigLottery {
+        time_run_lottery: TIME_RUN_LOTTERY,
+        price_ticket_in_near: U128(ONE_NEAR),
+        discount_divisor: U128(2000),
+        rewards_breakdown: vec![125, 375, 750, 1250, 2500, 5000],
+        reserve_fee: U128(2000),
+        operate_fee: U128(500),
+        max_tickets_per_account: 120,
+        max_tickets_per_lottery: 10000,
+        min_jackpot: U128(0),
+        fixed_prize_per_bracket: vec![U128(0); 6],
+        max_prize_per_bracket: vec![U128(0); 6],
+        overflow_policy: OverflowPolicy::RollOver,
+    }
 }
-use contract::NearLottContract as NearlottContract;
-pub const DEFAULT_GAS: u64 = near_sdk_sim::DEFAULT_GAS;
 
-pub fn init() -> (
-    ContractAccount<NearlottContract>,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-    UserAccount,
-) {
-    let root = init_simulator(None);
+/// Sandbox node with the contract initialized, its roles and players
+pub struct Env {
+    pub worker: Worker<Sandbox>,
+    pub root: Account,
+    pub contract: Contract,
+    pub owner: Account,
+    pub operator: Account,
+    pub injector: Account,
+    pub treasury: Account,
+    pub alice: Account,
+    pub bob: Account,
+    pub chandra: Account,
+}
 
-    let treasury = root.create_user(
-        AccountId::new_unchecked("treasury".to_string()),
-        to_yocto("100"),
-    );
+async fn create_account(root: &Account, name: &str, balance: u128) -> anyhow::Result<Account> {
+    Ok(root
+        .create_subaccount(name)
+        .initial_balance(NearToken::from_near(balance))
+        .transact()
+        .await?
+        .into_result()?)
+}
 
-    let injector = root.create_user(
-        AccountId::new_unchecked("injector".to_string()),
-        to_yocto("100"),
-    );
+impl Env {
+    pub async fn init() -> anyhow::Result<Self> {
+        Self::init_with(default_config_lottery()).await
+    }
 
-    let operator = root.create_user(
-        AccountId::new_unchecked("operator".to_string()),
-        to_yocto("100"),
-    );
+    pub async fn init_with(config_lottery: ConfigLottery) -> anyhow::Result<Self> {
+        Self::init_wasm("nearlott.wasm", config_lottery).await
+    }
 
-    let owner = root.create_user(
-        AccountId::new_unchecked("owner".to_string()),
-        to_yocto("100"),
-    );
+    /// Staging build, whose final numbers can be queued by the owner
+    pub async fn init_staging(config_lottery: ConfigLottery) -> anyhow::Result<Self> {
+        Self::init_wasm("nearlott_staging.wasm", config_lottery).await
+    }
 
-    root.create_user(account_from("g"), to_yocto("100"));
+    async fn init_wasm(wasm_name: &str, config_lottery: ConfigLottery) -> anyhow::Result<Self> {
+        let worker = near_workspaces::sandbox().await?;
+        let root = worker.root_account()?;
+        let contract = create_account(&root, "welott", 100)
+            .await?
+            .deploy(&wasm(wasm_name))
+            .await?
+            .into_result()?;
+        let owner = create_account(&root, "owner", 100).await?;
+        let operator = create_account(&root, "operator", 100).await?;
+        let injector = create_account(&root, "injector", 1000).await?;
+        let treasury = create_account(&root, "treasury", 100).await?;
+        let alice = create_account(&root, "alice", 100).await?;
+        let bob = create_account(&root, "bob", 100).await?;
+        let chandra = create_account(&root, "chandra", 1000).await?;
 
-    root.create_user(account_from("h"), to_yocto("100"));
+        contract
+            .call("new")
+            .args_json(json!({
+                "owner_id": owner.id(),
+                "injector_address": injector.id(),
+                "operator_address": operator.id(),
+                "treasury_address": treasury.id(),
+                "config_lottery": config_lottery,
+            }))
+            .transact()
+            .await?
+            .into_result()?;
 
-    root.create_user(account_from("i"), to_yocto("100"));
+        Ok(Self {
+            worker,
+            root,
+            contract,
+            owner,
+            operator,
+            injector,
+            treasury,
+            alice,
+            bob,
+            chandra,
+        })
+    }
 
-    root.create_user(account_from("j"), to_yocto("100"));
+    pub async fn view<T: DeserializeOwned>(&self, method: &str, args: Value) -> anyhow::Result<T> {
+        Ok(self.contract.view(method).args_json(args).await?.json()?)
+    }
 
-    root.create_user(account_from("k"), to_yocto("100"));
+    /// Call with one yocto, like the privileged and the claiming methods require
+    pub async fn call(
+        &self,
+        account: &Account,
+        method: &str,
+        args: Value,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        self.call_with_deposit(account, method, args, 1).await
+    }
 
-    root.create_user(account_from("l"), to_yocto("100"));
+    pub async fn call_with_deposit(
+        &self,
+        account: &Account,
+        method: &str,
+        args: Value,
+        deposit: u128,
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        Ok(account
+            .call(self.contract.id(), method)
+            .args_json(args)
+            .deposit(NearToken::from_yoctonear(deposit))
+            .gas(MAX_GAS)
+            .transact()
+            .await?)
+    }
 
-    root.create_user(account_from("m"), to_yocto("100"));
+    pub async fn lottery(&self, lottery_id: LotteryId) -> anyhow::Result<Lottery> {
+        self.view("view_lottery", json!({ "_lottery_id": lottery_id }))
+            .await
+    }
 
-    let alice = root.create_user(account_from("x"), to_yocto("100"));
+    pub async fn block_timestamp(&self) -> anyhow::Result<u64> {
+        Ok(self.worker.view_block().await?.timestamp())
+    }
 
-    let bob = root.create_user(account_from("y"), to_yocto("100"));
+    /// Time travel: fast forward the sandbox until the block time passes the end of the lottery
+    pub async fn pass_end_time(&self, lottery_id: LotteryId) -> anyhow::Result<()> {
+        let lottery = self.lottery(lottery_id).await?;
+        self.fast_forward_until(lottery.end_time).await
+    }
 
-    let chandra = root.create_user(account_from("z"), to_yocto("10000"));
+    /// Fast forward the sandbox until the block time reaches a timestamp, a block lasting at least a second
+    pub async fn fast_forward_until(&self, timestamp: u64) -> anyhow::Result<()> {
+        loop {
+            let block_timestamp = self.block_timestamp().await?;
+            if block_timestamp >= timestamp {
+                return Ok(());
+            }
+            let blocks = (timestamp - block_timestamp) / 1_000_000_000;
+            self.worker
+                .fast_forward(blocks.max(FAST_FORWARD_BLOCKS))
+                .await?;
+        }
+    }
 
-    let darmaji = root.create_user(account_from("n"), to_yocto("100"));
+    /// Player account created by the root account
+    pub async fn create_player(&self, name: &str, balance: u128) -> anyhow::Result<Account> {
+        create_account(&self.root, name, balance).await
+    }
 
-    let nearlott_contract = deploy!(
-        contract: NearlottContract,
-        contract_id: &AccountId::new_unchecked("mk".repeat(32)),
-        bytes: &NEARLOTT_WASM_BYTES,
-        signer_account: root,
-        init_method: new(
-            owner.account_id(),
-            injector.account_id(),
-            operator.account_id(),
-            treasury.account_id()
+    /// Register the accounts with `times` the minimum storage balance
+    pub async fn storage_deposit(&self, accounts: &[&Account], times: u128) -> anyhow::Result<()> {
+        let bounds: StorageBalanceBounds = self.view("storage_balance_bounds", json!({})).await?;
+        for account in accounts {
+            self.call_with_deposit(account, "storage_deposit", json!({}), bounds.min.0 * times)
+                .await?
+                .into_result()?;
+        }
+        Ok(())
+    }
+
+    /// Start a lottery by the operator, returning its id
+    pub async fn start_lottery(&self) -> anyhow::Result<LotteryId> {
+        self.call(&self.operator, "start_lottery", json!({}))
+            .await?
+            .into_result()?;
+        self.view("view_latest_lottery_id", json!({})).await
+    }
+
+    /// Buy tickets at the price of the lottery
+    pub async fn buy_tickets(
+        &self,
+        player: &Account,
+        lottery_id: LotteryId,
+        ticket_numbers: &[u32],
+    ) -> anyhow::Result<ExecutionFinalResult> {
+        let price: u128 = self
+            .view(
+                "calculate_total_price_for_bulk_tickets",
+                json!({ "_lottery_id": lottery_id, "_number_tickets": ticket_numbers.len() }),
+            )
+            .await?;
+        self.call_with_deposit(
+            player,
+            "buy_tickets",
+            json!({ "_lottery_id": lottery_id, "_ticket_numbers": ticket_numbers }),
+            price,
         )
-    );
-
-    (
-        nearlott_contract,
-        owner,
-        operator,
-        injector,
-        treasury,
-        alice,
-        bob,
-        chandra,
-        darmaji,
-        root,
-    )
-}
+        .await
+    }
+
+    /// Close the lottery after its end time and draw it
+    pub async fn close_and_draw(&self, lottery_id: LotteryId) -> anyhow::Result<()> {
+        self.pass_end_time(lottery_id).await?;
+        self.call(&self.operator, "close_lottery", json!({}))
+            .await?
+            .into_result()?;
+        self.call(
+            &self.operator,
+            "draw_final_number_and_make_lottery_claimable",
+            json!({ "_lottery_id": lottery_id, "_auto_injection": true }),
+        )
+        .await?
+        .into_result()?;
+        assert_eq!(self.lottery(lottery_id).await?.status, Status::Claimable);
+        Ok(())
+    }
 
-pub fn account_from(s: &str) -> AccountId {
-    AccountId::new_unchecked(s.repeat(64))
+    /// Deploy a mock Sputnik DAO whose council approves the proposals
+    pub async fn deploy_mock_dao(&self, council: Vec<&Account>) -> anyhow::Result<Contract> {
+        let dao = create_account(&self.root, "dao", 100)
+            .await?
+            .deploy(&wasm("mock_dao.wasm"))
+            .await?
+            .into_result()?;
+        let council: Vec<_> = council.iter().map(|account| account.id()).collect();
+        dao.call("new")
+            .args_json(json!({ "council": council }))
+            .transact()
+            .await?
+            .into_result()?;
+        Ok(dao)
+    }
 }